@@ -0,0 +1,11 @@
+//! Unpacks the raw input, of any length, into every structure of the fuzz corpus.
+
+#![no_main]
+#[macro_use] extern crate libfuzzer_sys;
+extern crate packed_struct_tests;
+
+use packed_struct_tests::fuzz_corpus;
+
+fuzz_target!(|data: &[u8]| {
+    fuzz_corpus::fuzz_unpack(data);
+});
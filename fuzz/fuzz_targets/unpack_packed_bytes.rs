@@ -0,0 +1,15 @@
+//! Splits the input into the exact packed sizes of the structures of the fuzz corpus and
+//! unpacks them.
+
+#![no_main]
+#[macro_use] extern crate libfuzzer_sys;
+extern crate arbitrary;
+extern crate packed_struct_tests;
+
+use arbitrary::Unstructured;
+use packed_struct_tests::fuzz_corpus;
+
+fuzz_target!(|data: &[u8]| {
+    let mut u = Unstructured::new(data);
+    let _ = fuzz_corpus::fuzz_packed_bytes(&mut u);
+});
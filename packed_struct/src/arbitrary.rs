@@ -0,0 +1,221 @@
+//! Property testing of the packed structures, with the ```proptest``` feature.
+//!
+//! The strategies are driven by the runtime schema of the structure. Integers take any
+//! value that fits their bits, enums take one of their variants, booleans take one of
+//! their two patterns, text fields take short ASCII strings with the padding of the field
+//! and the reserved bits keep their fixed values. Every generated value unpacks, nothing
+//! is filtered out. Values that shrink become zeroes, the first variants and empty strings.
+//!
+//! Structures with ```#[packed_struct(proptest)]``` implement proptest's ```Arbitrary``` with
+//! this strategy, so they can be generated with ```any::<T>()```. The attribute implies the
+//! schema, and the structure has to implement ```Debug```.
+//!
+//! ```rust
+//! extern crate packed_struct;
+//! #[macro_use] extern crate packed_struct_codegen;
+//! #[macro_use] extern crate proptest;
+//!
+//! use packed_struct::prelude::*;
+//! use packed_struct::arbitrary::*;
+//! use proptest::prelude::*;
+//!
+//! #[derive(PackedStruct, Debug, PartialEq)]
+//! #[packed_struct(bit_numbering="msb0", proptest)]
+//! pub struct Status {
+//!     #[packed_field(bits="0")]
+//!     ready: bool,
+//!     #[packed_field(bits="1:7")]
+//!     level: Integer<u8, packed_bits::Bits7>
+//! }
+//!
+//! fn main() {
+//!     proptest!(|(status in any::<Status>())| {
+//!         prop_assert!(*status.level < 128);
+//!     });
+//!
+//!     assert_roundtrip::<Status>();
+//! }
+//! ```
+
+use internal_prelude::v1::*;
+
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::test_runner::{Config, TestRunner};
+
+use packing::*;
+use schema::*;
+use types_str::StringPadding;
+
+#[doc(hidden)]
+pub use proptest::arbitrary::Arbitrary;
+#[doc(hidden)]
+pub use proptest::strategy::BoxedStrategy;
+
+/// Number of the cases that ```assert_roundtrip``` checks.
+pub const ROUNDTRIP_CASES: u32 = 4096;
+
+/// A strategy that generates valid values of a packed structure.
+pub fn packed_struct_strategy<T>() -> BoxedStrategy<T> where T: PackedStructSchemaInfo + PackedStructSlice + Debug + 'static {
+    let schema = T::packed_struct_schema();
+
+    vec(any::<u64>(), count_draws(schema))
+        .prop_map(move |draws| {
+            let mut bytes = vec![0; schema.num_bytes];
+            fill_fields(schema, &mut bytes, 0, &mut draws.into_iter());
+            match T::unpack_from_slice(&bytes) {
+                Ok(value) => value,
+                Err(e) => panic!("The generated bytes of {} don't unpack: {:?}, {:?}", schema.name, e, bytes)
+            }
+        })
+        .boxed()
+}
+
+/// A strategy that generates the packed bytes of valid structures.
+pub fn packed_bytes_strategy<T>() -> BoxedStrategy<Vec<u8>> where T: PackedStructSchemaInfo + PackedStructSlice + Debug + 'static {
+    packed_struct_strategy::<T>().prop_map(|value| value.pack_to_vec().expect("Packing failed")).boxed()
+}
+
+/// Checks the round trips of a structure over thousands of generated inputs. Panics
+/// with the smallest failing input.
+///
+/// * ```unpack(pack(x)) == x``` for the generated structures
+/// * ```pack(unpack(bytes))``` is canonical for random bytes: unpacking and packing it again
+///   gives the same bytes
+pub fn assert_roundtrip<T>() where T: PackedStructSchemaInfo + PackedStructSlice + Debug + PartialEq + 'static {
+    assert_roundtrip_with::<T>(Config { cases: ROUNDTRIP_CASES, ..Config::default() })
+}
+
+/// Checks the round trips of a structure, with a custom test configuration.
+pub fn assert_roundtrip_with<T>(config: Config) where T: PackedStructSchemaInfo + PackedStructSlice + Debug + PartialEq + 'static {
+    let mut runner = TestRunner::new(config.clone());
+    let result = runner.run(&packed_struct_strategy::<T>(), |value| {
+        let packed = value.pack_to_vec().expect("Packing failed");
+        let unpacked = T::unpack_from_slice(&packed);
+        prop_assert_eq!(Ok(&value), unpacked.as_ref(), "packed bytes: {:?}", packed);
+        Ok(())
+    });
+    if let Err(e) = result {
+        panic!("Round trip of {} failed: {}", T::packed_struct_schema().name, e);
+    }
+
+    let mut runner = TestRunner::new(config);
+    let result = runner.run(&vec(any::<u8>(), T::packed_bytes()), |bytes| {
+        if let Ok(value) = T::unpack_from_slice(&bytes) {
+            let canonical = value.pack_to_vec().expect("Packing failed");
+            let unpacked = T::unpack_from_slice(&canonical);
+            prop_assert_eq!(Ok(&value), unpacked.as_ref(), "canonical bytes: {:?}", canonical);
+            let repacked = unpacked.ok().and_then(|v| v.pack_to_vec().ok());
+            prop_assert_eq!(Some(&canonical), repacked.as_ref());
+        }
+        Ok(())
+    });
+    if let Err(e) = result {
+        panic!("Canonical packing of {} failed: {}", T::packed_struct_schema().name, e);
+    }
+}
+
+/// The number of random words that filling the fields uses.
+fn count_draws(schema: &PackedStructSchema) -> usize {
+    schema.fields.iter().map(|field| {
+        match (field.kind, field.nested) {
+            (PackedFieldKind::Reserved { .. }, _) => 0,
+            (PackedFieldKind::Bool { .. }, _) => 1,
+            (PackedFieldKind::Struct, Some(nested)) => count_draws(nested()),
+            (PackedFieldKind::Str { .. }, _) => field.bits() / 8 + 1,
+            _ => (field.bits() + 63) / 64
+        }
+    }).sum()
+}
+
+fn fill_fields<I: Iterator<Item=u64>>(schema: &PackedStructSchema, bytes: &mut [u8], bit_offset: usize, draws: &mut I) {
+    for field in schema.fields {
+        let start = bit_offset + field.bits_msb0.start;
+        let bits = field.bits();
+
+        match (field.kind, field.nested, field.enum_schema) {
+            (PackedFieldKind::Reserved { ones }, _, _) => {
+                if ones {
+                    for i in start..start + bits {
+                        set_bits(bytes, i, 1, 1);
+                    }
+                }
+            },
+            (PackedFieldKind::Struct, Some(nested), _) => {
+                fill_fields(nested(), bytes, start, draws);
+            },
+            (PackedFieldKind::Bool { true_bits, false_bits, .. }, _, _) => {
+                let value = if draws.next().unwrap_or(0) & 1 == 1 { true_bits } else { false_bits };
+                set_bits(bytes, start, bits, value);
+            },
+            (PackedFieldKind::Str { padding }, _, _) => {
+                let num_bytes = bits / 8;
+                let (max_len, pad) = match padding {
+                    StringPadding::Nul => (num_bytes, 0),
+                    StringPadding::Space => (num_bytes, b' '),
+                    StringPadding::NulTerminated => (num_bytes.saturating_sub(1), 0)
+                };
+                let len = draws.next().unwrap_or(0) as usize % (max_len + 1);
+                for i in 0..num_bytes {
+                    let c = draws.next().unwrap_or(0);
+                    let b = if i < len { b'a' + (c % 26) as u8 } else { pad };
+                    set_bits(bytes, start + i * 8, 8, b as u64);
+                }
+            },
+            (PackedFieldKind::Enum, _, Some(enum_schema)) if bits <= 64 && !enum_schema.variants.is_empty() => {
+                let draw = draws.next().unwrap_or(0);
+                let catch_all = field.type_name.starts_with("EnumCatchAll<");
+                let value = if catch_all && draw & 1 == 1 {
+                    draw >> 1
+                } else {
+                    let variant = &enum_schema.variants[(draw >> 1) as usize % enum_schema.variants.len()];
+                    let value = variant.value as u64;
+                    if field.endianness == Some(IntegerEndianness::Lsb) && bits % 8 == 0 {
+                        swap_bytes(value, bits / 8)
+                    } else {
+                        value
+                    }
+                };
+                set_bits(bytes, start, bits, value);
+            },
+            _ => {
+                let mut start = start;
+                let mut remaining = bits;
+                while remaining > 0 {
+                    let chunk = min(remaining, 64);
+                    set_bits(bytes, start, chunk, draws.next().unwrap_or(0));
+                    start += chunk;
+                    remaining -= chunk;
+                }
+            }
+        }
+    }
+}
+
+/// Writes the lowest bits of the value, the most significant bit first.
+fn set_bits(bytes: &mut [u8], start: usize, bits: usize, value: u64) {
+    for i in 0..bits {
+        let bit = (value >> (bits - 1 - i)) & 1;
+        let position = start + i;
+        let mask = 0x80 >> (position % 8);
+        if bit == 1 {
+            bytes[position / 8] |= mask;
+        } else {
+            bytes[position / 8] &= !mask;
+        }
+    }
+}
+
+fn swap_bytes(value: u64, num_bytes: usize) -> u64 {
+    (0..num_bytes).fold(0, |swapped, i| (swapped << 8) | ((value >> (i * 8)) & 0xFF))
+}
+
+#[test]
+fn test_set_bits() {
+    let mut bytes = [0; 2];
+    set_bits(&mut bytes, 4, 8, 0xAB);
+    assert_eq!([0x0A, 0xB0], bytes);
+    set_bits(&mut bytes, 0, 4, 0xFF);
+    assert_eq!([0xFA, 0xB0], bytes);
+    assert_eq!(0x3412, swap_bytes(0x1234, 2));
+}
@@ -1,4 +1,5 @@
-//! Helper structures for runtime packing visualization.
+//! Helper structures for runtime packing visualization. The layout of the display is
+//! configured with ```DisplayOptions```.
 
 use internal_prelude::v1::*;
 
@@ -6,6 +7,10 @@ use internal_prelude::v1::*;
 pub trait PackedStructDebug {
     fn fmt_fields(&self, fmt: &mut Formatter) -> Result<(), FmtError>;
     fn packed_struct_display_header() -> &'static str;
+    /// The fields with their display values, as listed in the fields table.
+    fn debug_fields(&self) -> Vec<DebugBitField<'static>> {
+        Vec::new()
+    }
 }
 
 pub struct DebugBinaryByteSlice<'a> {
@@ -38,41 +43,323 @@ pub struct DebugBitField<'a> {
 }
 
 
+/// A field whose value differs between two values of a structure.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldChange {
+    pub name: Cow<'static, str>,
+    /// Position of the field, MSB0.
+    pub bits: BitRange,
+    pub old_value: Cow<'static, str>,
+    pub new_value: Cow<'static, str>
+}
+
+/// Compares the packed bits of every field. The fields of both values have the same layout.
+pub fn field_changes(old_packed: &[u8], old_fields: Vec<DebugBitField<'static>>, new_packed: &[u8], new_fields: Vec<DebugBitField<'static>>) -> Vec<FieldChange> {
+    old_fields.into_iter().zip(new_fields)
+        .filter(|(_, new)| bits_differ(old_packed, new_packed, &new.bits))
+        .map(|(old, new)| {
+            FieldChange {
+                name: new.name,
+                bits: BitRange { start: new.bits.start, end: new.bits.end },
+                old_value: old.display_value,
+                new_value: new.display_value
+            }
+        })
+        .collect()
+}
+
+fn bit_at(slice: &[u8], i: usize) -> bool {
+    slice[i / 8] & (0x80 >> (i % 8)) != 0
+}
+
+fn bits_differ(old_packed: &[u8], new_packed: &[u8], bits: &Range<usize>) -> bool {
+    (bits.start..(bits.end + 1)).any(|i| bit_at(old_packed, i) != bit_at(new_packed, i))
+}
+
 pub fn packable_fmt_fields(f: &mut Formatter, packed_bytes: &[u8], fields: &[DebugBitField]) -> fmt::Result {
-    if fields.len() == 0 {
-		return Ok(());
-	}
+    packable_fmt_fields_with(f, packed_bytes, fields, &DisplayOptions::default())
+}
+
+/// How the raw bits of a field are shown in the fields table.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum FieldValueFormat {
+    Binary,
+    Hex,
+    Decimal
+}
+
+/// The bit numbering of the bit ranges in the fields table.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum DisplayBitNumbering {
+    Msb0,
+    Lsb0
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum DisplayStyle {
+    /// Aligned columns, for consoles and logs.
+    Text,
+    /// A Markdown table, for pasting into bug reports.
+    Markdown
+}
+
+/// Options of ```PackedStructDisplay``` and of the fields table. The defaults match the
+/// plain display.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DisplayOptions {
+    pub line_ending: &'static str,
+    pub style: DisplayStyle,
+    pub bit_numbering: DisplayBitNumbering,
+    /// The format of the fields without an override.
+    pub value_format: FieldValueFormat,
+    /// Formats of the single fields, by their names.
+    pub field_value_formats: Vec<(String, FieldValueFormat)>,
+    /// Separate every four binary digits with an ```_```.
+    pub group_nibbles: bool,
+    /// Show the raw bits even when a field is wider than 32 bits. Otherwise such tables
+    /// only have the names and the values.
+    pub wide_fields: bool,
+    /// Color the names, the raw bits and the values with ANSI escape codes.
+    pub ansi_colors: bool
+}
+
+impl Default for DisplayOptions {
+    fn default() -> Self {
+        DisplayOptions {
+            line_ending: "\r\n",
+            style: DisplayStyle::Text,
+            bit_numbering: DisplayBitNumbering::Msb0,
+            value_format: FieldValueFormat::Binary,
+            field_value_formats: Vec::new(),
+            group_nibbles: false,
+            wide_fields: false,
+            ansi_colors: false
+        }
+    }
+}
+
+impl DisplayOptions {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn line_ending(mut self, line_ending: &'static str) -> Self {
+        self.line_ending = line_ending;
+        self
+    }
+
+    pub fn style(mut self, style: DisplayStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Shorthand for the Markdown style.
+    pub fn markdown(self) -> Self {
+        self.style(DisplayStyle::Markdown)
+    }
+
+    pub fn bit_numbering(mut self, bit_numbering: DisplayBitNumbering) -> Self {
+        self.bit_numbering = bit_numbering;
+        self
+    }
+
+    pub fn value_format(mut self, format: FieldValueFormat) -> Self {
+        self.value_format = format;
+        self
+    }
+
+    /// Override the format of a single field.
+    pub fn field_value_format(mut self, field: &str, format: FieldValueFormat) -> Self {
+        self.field_value_formats.retain(|(name, _)| name != field);
+        self.field_value_formats.push((field.into(), format));
+        self
+    }
+
+    pub fn group_nibbles(mut self, group_nibbles: bool) -> Self {
+        self.group_nibbles = group_nibbles;
+        self
+    }
+
+    pub fn wide_fields(mut self, wide_fields: bool) -> Self {
+        self.wide_fields = wide_fields;
+        self
+    }
+
+    pub fn ansi_colors(mut self, ansi_colors: bool) -> Self {
+        self.ansi_colors = ansi_colors;
+        self
+    }
+
+    fn format_of(&self, field: &str) -> FieldValueFormat {
+        self.field_value_formats.iter().find(|(name, _)| name == field).map(|&(_, format)| format).unwrap_or(self.value_format)
+    }
+
+    fn paint(&self, color: &str, text: &str) -> String {
+        if self.ansi_colors && self.style == DisplayStyle::Text {
+            format!("\x1b[{}m{}\x1b[0m", color, text)
+        } else {
+            text.into()
+        }
+    }
+}
+
+/// The raw bits of a field, in the field's format.
+fn raw_value(packed_bytes: &[u8], bits: &Range<usize>, format: FieldValueFormat, group_nibbles: bool) -> String {
+    let width = bits.end - bits.start + 1;
+    let digits: Vec<bool> = (bits.start..(bits.end + 1)).map(|i| bit_at(packed_bytes, i)).collect();
+
+    match format {
+        FieldValueFormat::Decimal if width <= 64 => {
+            format!("{}", digits.iter().fold(0u64, |v, &b| (v << 1) | b as u64))
+        },
+        FieldValueFormat::Binary => {
+            let mut s = String::from("0b");
+            for (i, &b) in digits.iter().enumerate() {
+                if group_nibbles && i > 0 && (width - i) % 4 == 0 {
+                    s.push('_');
+                }
+                s.push(if b { '1' } else { '0' });
+            }
+            s
+        },
+        _ => {
+            // hex, and the decimals too wide for an integer
+            let mut s = String::from("0x");
+            let num_digits = (width + 3) / 4;
+            for d in 0..num_digits {
+                let end = width - (num_digits - 1 - d) * 4;
+                let start = end.saturating_sub(4);
+                let nibble = digits[start..end].iter().fold(0u8, |v, &b| (v << 1) | b as u8);
+                if group_nibbles && d > 0 && (num_digits - d) % 4 == 0 {
+                    s.push('_');
+                }
+                s.push_str(&format!("{:X}", nibble));
+            }
+            s
+        }
+    }
+}
+
+/// The fields table, with the options.
+pub fn packable_fmt_fields_with(f: &mut Formatter, packed_bytes: &[u8], fields: &[DebugBitField], options: &DisplayOptions) -> fmt::Result {
+    if fields.is_empty() {
+        return Ok(());
+    }
+
+    let le = options.line_ending;
+    let last_bit = packed_bytes.len() * 8 - 1;
+    let bits_of = |field: &DebugBitField| match options.bit_numbering {
+        DisplayBitNumbering::Msb0 => (field.bits.start, field.bits.end),
+        DisplayBitNumbering::Lsb0 => (last_bit - field.bits.end, last_bit - field.bits.start)
+    };
 
     let max_field_length_name = fields.iter().map(|x| x.name.len()).max().unwrap();
-	let max_bit_width = fields.iter().map(|x| x.bits.len()).max().unwrap();
-
-    if max_bit_width > 32 {
-        for field in fields {
-            try!(write!(f, "{name:>0$} | {base_value:?}\r\n",
-                            max_field_length_name + 1,
-                            base_value = field.display_value,
-                            name = field.name
-                            ));
+    let max_bit_width = fields.iter().map(|x| x.bits.len()).max().unwrap();
+    let show_raw = max_bit_width <= 32 || options.wide_fields;
+    let raw: Vec<String> = fields.iter().map(|field| raw_value(packed_bytes, &field.bits, options.format_of(&field.name), options.group_nibbles)).collect();
+    let max_raw_length = raw.iter().map(|r| r.len()).max().unwrap();
+
+    if options.style == DisplayStyle::Markdown {
+        write!(f, "| Field | Bits | Raw | Value |{}", le)?;
+        write!(f, "|-------|------|-----|-------|{}", le)?;
+        for (field, raw) in fields.iter().zip(&raw) {
+            let (start, end) = bits_of(field);
+            let bits = BitRange { start, end };
+            write!(f, "| {} | {} | `{}` | {} |{}", field.name, bits, raw, field.display_value.replace('|', "\\|"), le)?;
+        }
+        return Ok(());
+    }
+
+    for (field, raw) in fields.iter().zip(&raw) {
+        let name = options.paint("1", &format!("{:>1$}", field.name, max_field_length_name + 1));
+        let value = options.paint("32", &format!("{:?}", field.display_value));
+
+        if !show_raw {
+            write!(f, "{} | {}{}", name, value, le)?;
+            continue;
         }
-    } else {    
-        for field in fields {
 
-            let debug_binary = DebugBinaryByteSlice {
-                bits: &field.bits,
-                slice: packed_bytes
+        let (start, end) = bits_of(field);
+        let raw = options.paint("33", &format!("{:1$}", raw, max_raw_length));
+        write!(f, "{} | bits {:>3}:{:<3} | {} | {}{}", name, start, end, raw, value, le)?;
+    }
+
+    Ok(())
+}
+
+/// The fields table of the new value, with the changed fields marked with ```*```, their
+/// old and new values and the changed bits underlined with ```^```.
+pub fn packable_fmt_fields_diff(f: &mut Formatter, old_packed: &[u8], new_packed: &[u8], old_fields: &[DebugBitField], new_fields: &[DebugBitField]) -> fmt::Result {
+    packable_fmt_fields_diff_with(f, old_packed, new_packed, old_fields, new_fields, &DisplayOptions::default())
+}
+
+/// The fields table of the changes, with the options. The changed bits are only underlined
+/// in the binary format.
+pub fn packable_fmt_fields_diff_with(f: &mut Formatter, old_packed: &[u8], new_packed: &[u8], old_fields: &[DebugBitField], new_fields: &[DebugBitField], options: &DisplayOptions) -> fmt::Result {
+    if new_fields.is_empty() {
+        return Ok(());
+    }
+
+    let le = options.line_ending;
+    let last_bit = new_packed.len() * 8 - 1;
+    let bits_of = |field: &DebugBitField| match options.bit_numbering {
+        DisplayBitNumbering::Msb0 => (field.bits.start, field.bits.end),
+        DisplayBitNumbering::Lsb0 => (last_bit - field.bits.end, last_bit - field.bits.start)
+    };
+
+    let max_field_length_name = new_fields.iter().map(|x| x.name.len()).max().unwrap();
+    let max_bit_width = new_fields.iter().map(|x| x.bits.len()).max().unwrap();
+    let show_raw = max_bit_width <= 32 || options.wide_fields;
+    let raw: Vec<String> = new_fields.iter().map(|field| raw_value(new_packed, &field.bits, options.format_of(&field.name), options.group_nibbles)).collect();
+    let max_raw_length = raw.iter().map(|r| r.len()).max().unwrap();
+
+    if options.style == DisplayStyle::Markdown {
+        write!(f, "| | Field | Bits | Raw | Value |{}", le)?;
+        write!(f, "|-|-------|------|-----|-------|{}", le)?;
+    }
+
+    for ((old, new), raw) in old_fields.iter().zip(new_fields).zip(&raw) {
+        let changed = bits_differ(old_packed, new_packed, &new.bits);
+        let marker = if changed { "*" } else { " " };
+        let (start, end) = bits_of(new);
+
+        if options.style == DisplayStyle::Markdown {
+            let value = if changed {
+                format!("{} -> {}", old.display_value, new.display_value)
+            } else {
+                new.display_value.to_string()
             };
+            write!(f, "| {} | {} | {} | `{}` | {} |{}", marker, new.name, BitRange { start, end }, raw, value.replace('|', "\\|"), le)?;
+            continue;
+        }
 
-            try!(write!(f, "{name:>0$} | bits {bits_start:>3}:{bits_end:<3} | 0b{binary_value:>0width_bits$b}{dummy:>0spaces$} | {base_value:?}\r\n",
-                            max_field_length_name + 1,
-                            base_value = field.display_value,
-                            binary_value = debug_binary,
-                            dummy = "",
-                            bits_start = field.bits.start,
-                            bits_end = field.bits.end,
-                            width_bits = field.bits.len(),
-                            spaces = (max_bit_width - field.bits.len()) as usize,
-                            name = field.name
-                            ));
+        let name = options.paint("1", &format!("{:>1$}", new.name, max_field_length_name + 1));
+        let value = if changed {
+            format!("{:?} -> {:?}", old.display_value, new.display_value)
+        } else {
+            format!("{:?}", new.display_value)
+        };
+        let value = options.paint("32", &value);
+
+        if !show_raw {
+            write!(f, "{} {} | {}{}", marker, name, value, le)?;
+            continue;
+        }
+
+        let padded_raw = options.paint("33", &format!("{:1$}", raw, max_raw_length));
+        write!(f, "{} {} | bits {:>3}:{:<3} | {} | {}{}", marker, name, start, end, padded_raw, value, le)?;
+
+        if changed && options.format_of(&new.name) == FieldValueFormat::Binary {
+            // under the digits, after the 0b prefix and the nibble separators
+            let width = new.bits.end - new.bits.start + 1;
+            let mut marks = String::from("  ");
+            for (i, bit) in (new.bits.start..(new.bits.end + 1)).enumerate() {
+                if options.group_nibbles && i > 0 && (width - i) % 4 == 0 {
+                    marks.push(' ');
+                }
+                marks.push(if bit_at(old_packed, bit) != bit_at(new_packed, bit) { '^' } else { ' ' });
+            }
+            write!(f, "  {name:>0$} | {bits:12} | {marks:<1$} |{le}", max_field_length_name + 1, max_raw_length, name = "", bits = "", marks = marks, le = le)?;
         }
     }
 
@@ -86,7 +373,8 @@ pub struct PackedStructDisplay<'a, P: 'a, B: 'a> {
     pub raw_decimal: bool,
     pub raw_hex: bool,
     pub raw_binary: bool,
-    pub fields: bool
+    pub fields: bool,
+    pub options: DisplayOptions
 }
 
 impl<'a, P, B> PackedStructDisplay<'a, P, B> {
@@ -99,73 +387,61 @@ impl<'a, P, B> PackedStructDisplay<'a, P, B> {
             raw_decimal: true,
             raw_hex: true,
             raw_binary: true,
-            fields: true
+            fields: true,
+            options: DisplayOptions::default()
         }
     }
+
+    /// Display with the options, like ```DisplayOptions::new().markdown()```.
+    pub fn with_options(mut self, options: DisplayOptions) -> Self {
+        self.options = options;
+        self
+    }
 }
 
 use packing::{PackedStruct, PackedStructSlice};
+use schema::BitRange;
 
 impl<'a, P, B> fmt::Display for PackedStructDisplay<'a, P, B> where P: PackedStruct<B> + PackedStructSlice + PackedStructDebug {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let le = self.options.line_ending;
+        let markdown = self.options.style == DisplayStyle::Markdown;
+
         match self.packed_struct.pack_to_vec() {
             Ok(packed) => {
-                let l = packed.len();
-
                 if self.header {
-                    try!(f.write_str(P::packed_struct_display_header()));
-                    try!(f.write_str("\r\n"));
-                    try!(f.write_str("\r\n"));
+                    if markdown {
+                        f.write_str("### ")?;
+                    }
+                    write!(f, "{}{}{}", P::packed_struct_display_header(), le, le)?;
                 }
 
-                // decimal
-                if self.raw_decimal {
-                    try!(f.write_str("Decimal\r\n"));
-                    try!(f.write_str("["));
-                    for i in 0..l {
-                        try!(write!(f, "{}", packed[i]));
-                        if (i + 1) != l {
-                            try!(f.write_str(", "));
-                        }
+                let raw_sections = [(self.raw_decimal, "Decimal"), (self.raw_hex, "Hex"), (self.raw_binary, "Binary")];
+                for &(enabled, title) in &raw_sections {
+                    if !enabled {
+                        continue;
                     }
-                    try!(f.write_str("]"));
 
-                    try!(f.write_str("\r\n"));
-                    try!(f.write_str("\r\n"));
-                }
-                                
-                // hex
-                if self.raw_hex {
-                    try!(f.write_str("Hex\r\n"));
-                    try!(f.write_str("["));
-                    for i in 0..l {
-                        try!(write!(f, "0x{:X}", packed[i]));
-                        if (i + 1) != l {
-                            try!(f.write_str(", "));
-                        }
+                    let bytes: Vec<String> = packed.iter().map(|&b| match title {
+                        "Decimal" => format!("{}", b),
+                        "Hex" => format!("0x{:X}", b),
+                        _ => format!("0b{:08b}", b)
+                    }).collect();
+                    if markdown {
+                        write!(f, "{}: `[{}]`{}{}", title, bytes.join(", "), le, le)?;
+                    } else {
+                        write!(f, "{}{}[{}]{}{}", title, le, bytes.join(", "), le, le)?;
                     }
-                    try!(f.write_str("]"));
-                    try!(f.write_str("\r\n"));
-                    try!(f.write_str("\r\n"));
                 }
 
-                if self.raw_binary {
-                    try!(f.write_str("Binary\r\n"));
-                    try!(f.write_str("["));
-                    for i in 0..l {
-                        try!(write!(f, "0b{:08b}", packed[i]));
-                        if (i + 1) != l {
-                            try!(f.write_str(", "));
-                        }
+                if self.fields {
+                    let fields = self.packed_struct.debug_fields();
+                    if fields.is_empty() {
+                        self.packed_struct.fmt_fields(f)?;
+                    } else {
+                        packable_fmt_fields_with(f, &packed, &fields, &self.options)?;
                     }
-                    try!(f.write_str("]"));
-                    try!(f.write_str("\r\n"));
-                    try!(f.write_str("\r\n"));
                 }
-
-                if self.fields {
-                    try!(self.packed_struct.fmt_fields(f));
-                }                
             },
             Err(e) => {
                 write!(f, "Error packing for display: {:?}", e)?;
@@ -175,3 +451,50 @@ impl<'a, P, B> fmt::Display for PackedStructDisplay<'a, P, B> where P: PackedStr
         Ok(())
     }
 }
+
+/// Displays the changes between two values of a structure, with the fields table of the
+/// new value.
+pub struct PackedStructDiffDisplay<'a, P: 'a> {
+    pub old: &'a P,
+    pub new: &'a P,
+    pub header: bool,
+    pub options: DisplayOptions
+}
+
+impl<'a, P> PackedStructDiffDisplay<'a, P> {
+    pub fn new(old: &'a P, new: &'a P) -> Self {
+        PackedStructDiffDisplay {
+            old,
+            new,
+            header: true,
+            options: DisplayOptions::default()
+        }
+    }
+
+    /// Display with the options, like ```DisplayOptions::new().markdown()```.
+    pub fn with_options(mut self, options: DisplayOptions) -> Self {
+        self.options = options;
+        self
+    }
+}
+
+impl<'a, P> fmt::Display for PackedStructDiffDisplay<'a, P> where P: PackedStructSlice + PackedStructDebug {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match (self.old.pack_to_vec(), self.new.pack_to_vec()) {
+            (Ok(old_packed), Ok(new_packed)) => {
+                if self.header {
+                    if self.options.style == DisplayStyle::Markdown {
+                        f.write_str("### ")?;
+                    }
+                    let le = self.options.line_ending;
+                    write!(f, "{}{}{}", P::packed_struct_display_header(), le, le)?;
+                }
+
+                packable_fmt_fields_diff_with(f, &old_packed, &new_packed, &self.old.debug_fields(), &self.new.debug_fields(), &self.options)
+            },
+            (Err(e), _) | (_, Err(e)) => {
+                write!(f, "Error packing for display: {:?}", e)
+            }
+        }
+    }
+}
@@ -0,0 +1,92 @@
+//! Writes the fields table of a structure into any ```core::fmt::Write```, without
+//! allocating. Available without the ```std``` and ```alloc``` features, for example to
+//! dump the decoded registers over a serial port. Implemented for the structures with
+//! ```#[packed_struct(debug_write)]```, whose fields all implement ```Debug```.
+//!
+//! ```rust
+//! extern crate packed_struct;
+//! #[macro_use] extern crate packed_struct_codegen;
+//!
+//! use packed_struct::prelude::*;
+//! use packed_struct::debug_write::PackedStructDebugWrite;
+//!
+//! #[derive(PackedStruct, Debug)]
+//! #[packed_struct(bit_numbering="msb0", debug_write)]
+//! pub struct Status {
+//!     #[packed_field(bits="0")]
+//!     ready: bool,
+//!     #[packed_field(bits="1:7")]
+//!     level: Integer<u8, packed_bits::Bits7>
+//! }
+//!
+//! fn main() {
+//!     let mut out = String::new();
+//!     Status { ready: true, level: 5.into() }.write_fields(&mut out).unwrap();
+//!     assert_eq!(" ready | bits   0:0   | 0b1       | true\r\n level | bits   1:7   | 0b0000101 | 5\r\n", out);
+//! }
+//! ```
+
+use internal_prelude::v1::*;
+
+/// A field with a reference to its value, for the fields table.
+pub struct DebugFieldRef<'a> {
+    pub name: &'static str,
+    /// Position of the field, MSB0. The end bit is included, as in ```DebugBitField```.
+    pub bits: Range<usize>,
+    pub value: &'a dyn Debug
+}
+
+/// The writer of the fields table, any ```core::fmt::Write```.
+pub type FieldsWriter<'a> = dyn fmt::Write + 'a;
+
+/// A structure that can write its fields table without allocating.
+pub trait PackedStructDebugWrite {
+    /// Write the fields table, with ```\r\n``` line endings.
+    fn write_fields(&self, w: &mut FieldsWriter) -> fmt::Result;
+
+    /// Visit the fields, in the order of the table. The array elements are visited one by one.
+    fn visit_fields<'a>(&'a self, visitor: &mut dyn FnMut(DebugFieldRef<'a>));
+
+    /// A displayable fields table, for ```write!```.
+    fn fields_display(&self) -> PackedStructFieldsDisplay<'_, Self> where Self: Sized {
+        PackedStructFieldsDisplay { packed_struct: self }
+    }
+}
+
+/// Displays the fields table of a structure.
+pub struct PackedStructFieldsDisplay<'a, P: 'a> {
+    pub packed_struct: &'a P
+}
+
+impl<'a, P> Display for PackedStructFieldsDisplay<'a, P> where P: PackedStructDebugWrite {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.packed_struct.write_fields(f)
+    }
+}
+
+/// Writes the fields table, the same table as ```packable_fmt_fields``` with the values
+/// written by their ```Debug``` implementations.
+pub fn write_fields(w: &mut FieldsWriter, packed_bytes: &[u8], fields: &[DebugFieldRef]) -> fmt::Result {
+    let max_field_length_name = match fields.iter().map(|x| x.name.len()).max() {
+        Some(max) => max,
+        None => return Ok(())
+    };
+    let max_bit_width = fields.iter().map(|x| x.bits.len() + 1).max().unwrap_or(0);
+
+    for field in fields {
+        write!(w, "{name:>0$} | ", max_field_length_name + 1, name = field.name)?;
+
+        if max_bit_width <= 32 {
+            write!(w, "bits {:>3}:{:<3} | 0b", field.bits.start, field.bits.end)?;
+            for i in field.bits.start..(field.bits.end + 1) {
+                let bit = packed_bytes[i / 8] & (0x80 >> (i % 8)) != 0;
+                w.write_char(if bit { '1' } else { '0' })?;
+            }
+            write!(w, "{:1$} | ", "", max_bit_width - (field.bits.len() + 1))?;
+        }
+
+        write!(w, "{:?}\r\n", field.value)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,181 @@
+//! C header export of the packed structures and primitive enums.
+
+use internal_prelude::v1::*;
+
+use schema::*;
+use export_common::*;
+
+/// Renders packed structures and their enums into a C header, with `#define`
+/// masks and shifts for every field, enum `typedef`s and asserts that the fields fit.
+///
+/// The `_OFFSET` and `_BYTES` definitions of a field select the bytes that hold
+/// it, and `_SHIFT` and `_MASK` are relative to those bytes when read as a
+/// big-endian integer. Structures of up to 8 bytes are treated as a single
+/// register, so the offset of all of their fields is 0. The `_GET` and `_SET`
+/// accessors of little endian fields swap the bytes of the value, and little
+/// endian fields that aren't whole bytes get no accessors.
+///
+/// ```rust
+/// extern crate packed_struct;
+/// #[macro_use] extern crate packed_struct_codegen;
+///
+/// use packed_struct::prelude::*;
+/// use packed_struct::export::CHeader;
+///
+/// #[derive(PackedStruct)]
+/// #[packed_struct(bit_numbering="msb0", schema)]
+/// pub struct Status {
+///     #[packed_field(bits="0")]
+///     ready: bool,
+///     #[packed_field(bits="1:7")]
+///     level: Integer<u8, packed_bits::Bits7>
+/// }
+///
+/// fn main() {
+///     let header = CHeader::new("STATUS_H")
+///         .add_struct(Status::packed_struct_schema())
+///         .to_string();
+///     assert!(header.contains("#define STATUS_LEVEL_MASK 0x7FULL"));
+/// }
+/// ```
+pub struct CHeader {
+    guard: String,
+    schemas: SchemaSet
+}
+
+impl CHeader {
+    /// A new header, `guard` being the name of the include guard macro.
+    pub fn new(guard: &str) -> Self {
+        CHeader {
+            guard: guard.into(),
+            schemas: SchemaSet::new()
+        }
+    }
+
+    /// Add a structure, along with the nested structures and enums of its fields.
+    pub fn add_struct(mut self, schema: &'static PackedStructSchema) -> Self {
+        self.schemas.push_struct(schema);
+        self
+    }
+
+    /// Add an enum that isn't used by any of the structures.
+    pub fn add_enum(mut self, schema: &'static PrimitiveEnumSchema) -> Self {
+        self.schemas.push_enum(schema);
+        self
+    }
+
+    fn fmt_enum(f: &mut fmt::Formatter, schema: &PrimitiveEnumSchema) -> fmt::Result {
+        let prefix = c_name(schema.name);
+
+        writeln!(f, "/* {} ({}) */", schema.name, schema.primitive)?;
+        writeln!(f, "typedef enum {{")?;
+        for (i, variant) in schema.variants.iter().enumerate() {
+            let separator = if i + 1 < schema.variants.len() { "," } else { "" };
+            write!(f, "    {}_{} = {}{}", prefix, c_name(variant.name), variant.value, separator)?;
+            if !variant.doc.is_empty() {
+                write!(f, " /* {} */", c_comment(variant.doc))?;
+            }
+            writeln!(f)?;
+        }
+        writeln!(f, "}} {}_t;", to_snake_case(schema.name))?;
+        writeln!(f)
+    }
+
+    fn fmt_struct(f: &mut fmt::Formatter, schema: &PackedStructSchema) -> fmt::Result {
+        let prefix = c_name(schema.name);
+        let type_name = format!("{}_t", to_snake_case(schema.name));
+        let register = schema.num_bytes <= 8;
+        let size = format!("{} {}", schema.num_bytes, if schema.num_bytes == 1 { "byte" } else { "bytes" });
+
+        if schema.doc.is_empty() {
+            writeln!(f, "/* {}, {} */", schema.name, size)?;
+        } else {
+            writeln!(f, "/* {}, {}. {} */", schema.name, size, c_comment(schema.doc))?;
+        }
+        writeln!(f, "#define {}_SIZE {}", prefix, schema.num_bytes)?;
+        writeln!(f, "typedef struct {{ uint8_t bytes[{}_SIZE]; }} {};", prefix, type_name)?;
+        writeln!(f)?;
+
+        for field in schema.fields {
+            let name = format!("{}_{}", prefix, c_name(field.name));
+
+            write!(f, "/* {}: {}, bits {} (MSB0)", field.name, field.type_name, field.bits_msb0)?;
+            if field.reserved {
+                write!(f, ", reserved")?;
+            }
+            if is_lsb(field) {
+                write!(f, ", stored as little endian")?;
+            }
+            if !field.doc.is_empty() {
+                write!(f, ". {}", c_comment(field.doc))?;
+            }
+            writeln!(f, " */")?;
+
+            let (offset, bytes) = if register {
+                (0, schema.num_bytes)
+            } else {
+                (field.bits_msb0.start / 8, field.bits_msb0.end / 8 - field.bits_msb0.start / 8 + 1)
+            };
+
+            writeln!(f, "#define {}_OFFSET {}", name, offset)?;
+            writeln!(f, "#define {}_BYTES {}", name, bytes)?;
+            writeln!(f, "#define {}_WIDTH {}", name, field.bits())?;
+
+            if bytes <= 8 {
+                let shift = (offset + bytes) * 8 - 1 - field.bits_msb0.end;
+                let mask = if field.bits() == 64 { !0 } else { ((1u64 << field.bits()) - 1) << shift };
+
+                writeln!(f, "#define {}_SHIFT {}", name, shift)?;
+                writeln!(f, "#define {}_MASK 0x{:0width$X}ULL", name, mask, width = bytes * 2)?;
+                if !is_lsb(field) {
+                    writeln!(f, "#define {}_GET(v) (((uint64_t)(v) & {}_MASK) >> {}_SHIFT)", name, name, name)?;
+                    writeln!(f, "#define {}_SET(v, x) (((uint64_t)(v) & ~{}_MASK) | (((uint64_t)(x) << {}_SHIFT) & {}_MASK))", name, name, name, name)?;
+                } else if field.bits() % 8 == 0 {
+                    let num_bytes = field.bits() / 8;
+                    writeln!(f, "#define {}_GET(v) packed_struct_swap_bytes(((uint64_t)(v) & {}_MASK) >> {}_SHIFT, {})", name, name, name, num_bytes)?;
+                    writeln!(f, "#define {}_SET(v, x) (((uint64_t)(v) & ~{}_MASK) | ((packed_struct_swap_bytes((uint64_t)(x), {}) << {}_SHIFT) & {}_MASK))", name, name, num_bytes, name, name)?;
+                }
+            }
+            writeln!(f, "_Static_assert({}_OFFSET + {}_BYTES <= {}_SIZE && {}_WIDTH <= {}_BYTES * 8, \"{} has to fit into {}\");",
+                name, name, prefix, name, name, field.name, type_name)?;
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for CHeader {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "/* Generated by packed_struct, do not edit. */")?;
+        writeln!(f, "#ifndef {}", self.guard)?;
+        writeln!(f, "#define {}", self.guard)?;
+        writeln!(f)?;
+        writeln!(f, "#include <stdint.h>")?;
+        writeln!(f)?;
+
+        if self.schemas.structs.iter().any(|s| s.fields.iter().any(is_lsb)) {
+            writeln!(f, "/* Reverses the lowest bytes of a little endian field's value */")?;
+            writeln!(f, "static inline uint64_t packed_struct_swap_bytes(uint64_t x, unsigned bytes) {{")?;
+            writeln!(f, "    uint64_t r = 0;")?;
+            writeln!(f, "    for (unsigned i = 0; i < bytes; i++) {{ r = (r << 8) | ((x >> (i * 8)) & 0xFF); }}")?;
+            writeln!(f, "    return r;")?;
+            writeln!(f, "}}")?;
+            writeln!(f)?;
+        }
+
+        for e in &self.schemas.enums {
+            Self::fmt_enum(f, e)?;
+        }
+
+        for s in &self.schemas.structs {
+            Self::fmt_struct(f, s)?;
+        }
+
+        writeln!(f, "#endif /* {} */", self.guard)
+    }
+}
+
+fn is_lsb(field: &PackedFieldSchema) -> bool {
+    field.endianness == Some(IntegerEndianness::Lsb)
+}
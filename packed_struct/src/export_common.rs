@@ -0,0 +1,97 @@
+//! Helpers shared by the exports of the packed layouts.
+
+use internal_prelude::v1::*;
+
+use schema::*;
+
+/// Structures and enums to be exported, with the dependencies of each structure
+/// listed before it.
+pub struct SchemaSet {
+    pub structs: Vec<&'static PackedStructSchema>,
+    pub enums: Vec<&'static PrimitiveEnumSchema>
+}
+
+impl SchemaSet {
+    pub fn new() -> Self {
+        SchemaSet {
+            structs: vec![],
+            enums: vec![]
+        }
+    }
+
+    /// Add a structure, along with the nested structures and enums of its fields.
+    pub fn push_struct(&mut self, schema: &'static PackedStructSchema) {
+        if self.structs.iter().any(|s| s.name == schema.name) {
+            return;
+        }
+
+        // dependencies are declared first
+        for field in schema.fields {
+            if let Some(enum_schema) = field.enum_schema {
+                self.push_enum(enum_schema);
+            }
+            if let Some(nested) = field.nested {
+                self.push_struct(nested());
+            }
+        }
+
+        self.structs.push(schema);
+    }
+
+    pub fn push_enum(&mut self, schema: &'static PrimitiveEnumSchema) {
+        if !self.enums.iter().any(|e| e.name == schema.name) {
+            self.enums.push(schema);
+        }
+    }
+}
+
+/// Upper case identifier, for example `palette[3][1]` is `PALETTE_3_1`.
+pub fn c_name(name: &str) -> String {
+    let snake = to_snake_case(name);
+    let mut s = String::new();
+    for c in snake.chars() {
+        if c.is_ascii_alphanumeric() {
+            s.push(c.to_ascii_uppercase());
+        } else if !s.is_empty() && !s.ends_with('_') {
+            s.push('_');
+        }
+    }
+    s.trim_end_matches('_').into()
+}
+
+/// Doc comments on a single line, without the comment terminators.
+pub fn c_comment(doc: &str) -> String {
+    doc.lines().map(|l| l.trim()).collect::<Vec<_>>().join(" ").replace("*/", "* /")
+}
+
+/// `PowerMode` is `power_mode`, `USBStatus` is `usb_status`.
+pub fn to_snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut s = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let prev = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).map(|n| n.is_lowercase()).unwrap_or(false);
+            if prev.is_lowercase() || prev.is_ascii_digit() || (prev.is_uppercase() && next_is_lower) {
+                s.push('_');
+            }
+        }
+        s.extend(c.to_lowercase());
+    }
+    s
+}
+
+/// Lower case identifier, for example `palette[3][1]` is `palette_3_1`.
+pub fn snake_name(name: &str) -> String {
+    c_name(name).to_lowercase()
+}
+
+#[test]
+fn test_c_names() {
+    assert_eq!("POWER_MODE", c_name("PowerMode"));
+    assert_eq!("PALETTE_3_1", c_name("palette[3][1]"));
+    assert_eq!("RESERVED", c_name("_reserved"));
+    assert_eq!("usb_status", to_snake_case("USBStatus"));
+    assert_eq!("led_matrix", to_snake_case("LedMatrix"));
+    assert_eq!("palette_3_1", snake_name("palette[3][1]"));
+}
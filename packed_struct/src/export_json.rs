@@ -0,0 +1,172 @@
+//! JSON export of the packed layouts.
+
+use internal_prelude::v1::*;
+
+use schema::*;
+use export_common::*;
+
+/// Renders packed structures and their enums into a JSON layout description.
+///
+/// ```json
+/// {
+///   "structs": [
+///     {
+///       "name": "Status",
+///       "doc": "",
+///       "num_bytes": 1,
+///       "num_bits": 8,
+///       "fields": [
+///         {
+///           "name": "ready",
+///           "type": "bool",
+///           "kind": "bool",
+///           "bits_msb0": [0, 0],
+///           "bits_lsb0": [7, 7],
+///           "width": 1,
+///           "endianness": null,
+///           "enum": null,
+///           "struct": null,
+///           "reserved": false,
+///           "doc": ""
+///         }
+///       ]
+///     }
+///   ],
+///   "enums": []
+/// }
+/// ```
+pub struct JsonLayout {
+    schemas: SchemaSet
+}
+
+impl JsonLayout {
+    pub fn new() -> Self {
+        JsonLayout {
+            schemas: SchemaSet::new()
+        }
+    }
+
+    /// Add a structure, along with the nested structures and enums of its fields.
+    pub fn add_struct(mut self, schema: &'static PackedStructSchema) -> Self {
+        self.schemas.push_struct(schema);
+        self
+    }
+
+    /// Add an enum that isn't used by any of the structures.
+    pub fn add_enum(mut self, schema: &'static PrimitiveEnumSchema) -> Self {
+        self.schemas.push_enum(schema);
+        self
+    }
+
+    fn fmt_struct(f: &mut fmt::Formatter, schema: &PackedStructSchema) -> fmt::Result {
+        writeln!(f, "    {{")?;
+        writeln!(f, "      \"name\": {},", JsonStr(schema.name))?;
+        writeln!(f, "      \"doc\": {},", JsonStr(schema.doc))?;
+        writeln!(f, "      \"num_bytes\": {},", schema.num_bytes)?;
+        writeln!(f, "      \"num_bits\": {},", schema.num_bits)?;
+        writeln!(f, "      \"fields\": [")?;
+        for (i, field) in schema.fields.iter().enumerate() {
+            let kind = match field.kind {
+                PackedFieldKind::Bool { .. } => "bool",
+                PackedFieldKind::Integer { signed: false } => "unsigned",
+                PackedFieldKind::Integer { signed: true } => "signed",
+                PackedFieldKind::Enum => "enum",
+                PackedFieldKind::Reserved { .. } => "reserved",
+                PackedFieldKind::Str { .. } => "str",
+                PackedFieldKind::Struct => "struct"
+            };
+            let endianness = match field.endianness {
+                Some(IntegerEndianness::Msb) => "\"msb\"",
+                Some(IntegerEndianness::Lsb) => "\"lsb\"",
+                None => "null"
+            };
+
+            writeln!(f, "        {{")?;
+            writeln!(f, "          \"name\": {},", JsonStr(field.name))?;
+            writeln!(f, "          \"type\": {},", JsonStr(field.type_name))?;
+            writeln!(f, "          \"kind\": \"{}\",", kind)?;
+            writeln!(f, "          \"bits_msb0\": [{}, {}],", field.bits_msb0.start, field.bits_msb0.end)?;
+            writeln!(f, "          \"bits_lsb0\": [{}, {}],", field.bits_lsb0.start, field.bits_lsb0.end)?;
+            writeln!(f, "          \"width\": {},", field.bits())?;
+            writeln!(f, "          \"endianness\": {},", endianness)?;
+            match field.enum_schema {
+                Some(e) => writeln!(f, "          \"enum\": {},", JsonStr(e.name))?,
+                None => writeln!(f, "          \"enum\": null,")?
+            }
+            match field.nested {
+                Some(nested) => writeln!(f, "          \"struct\": {},", JsonStr(nested().name))?,
+                None => writeln!(f, "          \"struct\": null,")?
+            }
+            writeln!(f, "          \"reserved\": {},", field.reserved)?;
+            writeln!(f, "          \"doc\": {}", JsonStr(field.doc))?;
+            writeln!(f, "        }}{}", if i + 1 < schema.fields.len() { "," } else { "" })?;
+        }
+        writeln!(f, "      ]")?;
+        write!(f, "    }}")
+    }
+
+    fn fmt_enum(f: &mut fmt::Formatter, schema: &PrimitiveEnumSchema) -> fmt::Result {
+        writeln!(f, "    {{")?;
+        writeln!(f, "      \"name\": {},", JsonStr(schema.name))?;
+        writeln!(f, "      \"primitive\": {},", JsonStr(schema.primitive))?;
+        writeln!(f, "      \"variants\": [")?;
+        for (i, variant) in schema.variants.iter().enumerate() {
+            writeln!(f, "        {{ \"name\": {}, \"value\": {}, \"doc\": {} }}{}",
+                JsonStr(variant.name), variant.value, JsonStr(variant.doc),
+                if i + 1 < schema.variants.len() { "," } else { "" })?;
+        }
+        writeln!(f, "      ]")?;
+        write!(f, "    }}")
+    }
+}
+
+impl Default for JsonLayout {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Display for JsonLayout {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{{")?;
+        writeln!(f, "  \"structs\": [")?;
+        for (i, s) in self.schemas.structs.iter().enumerate() {
+            Self::fmt_struct(f, s)?;
+            writeln!(f, "{}", if i + 1 < self.schemas.structs.len() { "," } else { "" })?;
+        }
+        writeln!(f, "  ],")?;
+        writeln!(f, "  \"enums\": [")?;
+        for (i, e) in self.schemas.enums.iter().enumerate() {
+            Self::fmt_enum(f, e)?;
+            writeln!(f, "{}", if i + 1 < self.schemas.enums.len() { "," } else { "" })?;
+        }
+        writeln!(f, "  ]")?;
+        writeln!(f, "}}")
+    }
+}
+
+/// A quoted and escaped JSON string.
+struct JsonStr<'a>(&'a str);
+
+impl<'a> Display for JsonStr<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("\"")?;
+        for c in self.0.chars() {
+            match c {
+                '"' => f.write_str("\\\"")?,
+                '\\' => f.write_str("\\\\")?,
+                '\n' => f.write_str("\\n")?,
+                '\r' => f.write_str("\\r")?,
+                '\t' => f.write_str("\\t")?,
+                c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+                c => f.write_char(c)?
+            }
+        }
+        f.write_str("\"")
+    }
+}
+
+#[test]
+fn test_json_str() {
+    assert_eq!("\"a \\\"b\\\"\\n\\u0001\"", format!("{}", JsonStr("a \"b\"\n\u{1}")));
+}
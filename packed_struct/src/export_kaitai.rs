@@ -0,0 +1,152 @@
+//! Kaitai Struct export of the packed layouts.
+
+use internal_prelude::v1::*;
+
+use schema::*;
+use types_str::StringPadding;
+use export_common::*;
+
+/// Renders a packed structure into a Kaitai Struct `.ksy` definition. Nested
+/// structures are declared as types and enums as Kaitai enums.
+///
+/// Byte aligned integers of 1, 2, 4 or 8 bytes are declared with their endianness,
+/// the rest as big-endian bit fields. Text fields strip their padding or stop at their
+/// terminator, like the unpacking does. The bits that aren't covered by any field
+/// are declared as `gap_*` bit fields.
+pub struct KaitaiStruct {
+    root: &'static PackedStructSchema,
+    schemas: SchemaSet
+}
+
+impl KaitaiStruct {
+    pub fn new(schema: &'static PackedStructSchema) -> Self {
+        let mut schemas = SchemaSet::new();
+        schemas.push_struct(schema);
+
+        KaitaiStruct {
+            root: schema,
+            schemas
+        }
+    }
+
+    fn fmt_seq(f: &mut fmt::Formatter, schema: &PackedStructSchema, indent: &str) -> fmt::Result {
+        writeln!(f, "{}seq:", indent)?;
+
+        let mut next_bit = 0;
+        let mut gaps = 0;
+        let mut fields: Vec<_> = schema.fields.iter().collect();
+        fields.sort_by_key(|f| f.bits_msb0.start);
+
+        for field in fields {
+            if field.bits_msb0.start > next_bit {
+                writeln!(f, "{}  - id: gap_{}", indent, gaps)?;
+                writeln!(f, "{}    type: b{}", indent, field.bits_msb0.start - next_bit)?;
+                gaps += 1;
+            }
+            next_bit = field.bits_msb0.end + 1;
+
+            let bits = field.bits();
+            let byte_aligned = field.bits_msb0.start % 8 == 0 && bits % 8 == 0;
+
+            writeln!(f, "{}  - id: {}", indent, snake_name(field.name))?;
+            match field.kind {
+                PackedFieldKind::Str { padding } => {
+                    writeln!(f, "{}    type: str", indent)?;
+                    writeln!(f, "{}    size: {}", indent, bits / 8)?;
+                    writeln!(f, "{}    encoding: UTF-8", indent)?;
+                    match padding {
+                        StringPadding::Nul => writeln!(f, "{}    pad-right: 0", indent)?,
+                        StringPadding::Space => writeln!(f, "{}    pad-right: 0x20", indent)?,
+                        StringPadding::NulTerminated => writeln!(f, "{}    terminator: 0", indent)?
+                    }
+                },
+                PackedFieldKind::Struct if byte_aligned => {
+                    let nested = field.nested.map(|n| n().name).unwrap_or(field.type_name);
+                    writeln!(f, "{}    type: {}", indent, to_snake_case(nested))?;
+                    writeln!(f, "{}    size: {}", indent, bits / 8)?;
+                },
+                PackedFieldKind::Integer { .. } | PackedFieldKind::Enum if byte_aligned && [8, 16, 32, 64].contains(&bits) => {
+                    let signed = if let PackedFieldKind::Integer { signed } = field.kind { signed } else { false };
+                    let endian = match (bits, field.endianness) {
+                        (8, _) => "",
+                        (_, Some(IntegerEndianness::Lsb)) => "le",
+                        _ => "be"
+                    };
+                    writeln!(f, "{}    type: {}{}{}", indent, if signed { "s" } else { "u" }, bits / 8, endian)?;
+                },
+                _ => {
+                    writeln!(f, "{}    type: b{}", indent, bits)?;
+                }
+            }
+            if let Some(e) = field.enum_schema {
+                writeln!(f, "{}    enum: {}", indent, to_snake_case(e.name))?;
+            }
+            if !field.doc.is_empty() {
+                writeln!(f, "{}    doc: {}", indent, YamlStr(field.doc))?;
+            }
+        }
+
+        if schema.num_bytes * 8 > next_bit {
+            writeln!(f, "{}  - id: gap_{}", indent, gaps)?;
+            writeln!(f, "{}    type: b{}", indent, schema.num_bytes * 8 - next_bit)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for KaitaiStruct {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "# Generated by packed_struct, do not edit.")?;
+        writeln!(f, "meta:")?;
+        writeln!(f, "  id: {}", to_snake_case(self.root.name))?;
+        writeln!(f, "  endian: be")?;
+        writeln!(f, "  bit-endian: be")?;
+        if !self.root.doc.is_empty() {
+            writeln!(f, "doc: {}", YamlStr(self.root.doc))?;
+        }
+        Self::fmt_seq(f, self.root, "")?;
+
+        let nested: Vec<_> = self.schemas.structs.iter().filter(|s| s.name != self.root.name).collect();
+        if !nested.is_empty() {
+            writeln!(f, "types:")?;
+            for s in nested {
+                writeln!(f, "  {}:", to_snake_case(s.name))?;
+                if !s.doc.is_empty() {
+                    writeln!(f, "    doc: {}", YamlStr(s.doc))?;
+                }
+                Self::fmt_seq(f, s, "    ")?;
+            }
+        }
+
+        if !self.schemas.enums.is_empty() {
+            writeln!(f, "enums:")?;
+            for e in &self.schemas.enums {
+                writeln!(f, "  {}:", to_snake_case(e.name))?;
+                for variant in e.variants {
+                    writeln!(f, "    {}: {}", variant.value, snake_name(variant.name))?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A double quoted YAML string.
+struct YamlStr<'a>(&'a str);
+
+impl<'a> Display for YamlStr<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("\"")?;
+        for c in self.0.chars() {
+            match c {
+                '"' => f.write_str("\\\"")?,
+                '\\' => f.write_str("\\\\")?,
+                '\n' => f.write_str("\\n")?,
+                c => f.write_char(c)?
+            }
+        }
+        f.write_str("\"")
+    }
+}
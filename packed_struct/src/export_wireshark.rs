@@ -0,0 +1,166 @@
+//! Wireshark dissector export of the packed layouts.
+
+use internal_prelude::v1::*;
+
+use schema::*;
+use export_common::*;
+
+/// Renders a packed structure into a skeleton of a Wireshark dissector, written in Lua.
+/// Every field is declared with a bit mask over the bytes that hold it, as an integer
+/// of the width of those bytes, and the fields of nested structures are shown in a
+/// subtree. The dissector still has to be registered, for example on a port.
+pub struct WiresharkDissector {
+    root: &'static PackedStructSchema,
+    schemas: SchemaSet
+}
+
+impl WiresharkDissector {
+    pub fn new(schema: &'static PackedStructSchema) -> Self {
+        let mut schemas = SchemaSet::new();
+        schemas.push_struct(schema);
+
+        WiresharkDissector {
+            root: schema,
+            schemas
+        }
+    }
+
+    fn proto_name(&self) -> String {
+        to_snake_case(self.root.name)
+    }
+
+    /// Declares the fields of a structure, nested at the bit offset.
+    fn fmt_declarations(&self, f: &mut fmt::Formatter, schema: &PackedStructSchema, bit_offset: usize, prefix: &str) -> fmt::Result {
+        let proto = self.proto_name();
+
+        for field in schema.fields {
+            let key = format!("{}{}", prefix, snake_name(field.name));
+            let abbr = format!("{}.{}", proto, key.replace("__", "."));
+            let (start, end) = (bit_offset + field.bits_msb0.start, bit_offset + field.bits_msb0.end);
+
+            if let Some(nested) = field.nested {
+                self.fmt_declarations(f, nested(), start, &format!("{}__", key))?;
+                continue;
+            }
+
+            let label = if field.reserved { format!("{} (reserved)", field.name) } else { field.name.to_string() };
+            let window_bytes = end / 8 - start / 8 + 1;
+            let shift = (start / 8 + window_bytes) * 8 - 1 - end;
+            let bits = field.bits();
+            let mask = if bits >= 64 { !0 } else { ((1u64 << bits) - 1) << shift };
+            let full_mask = window_bytes * 8 == bits;
+            let mask_str = if full_mask { "nil".to_string() } else { format!("0x{:X}", mask) };
+            let doc = if field.doc.is_empty() { "nil".to_string() } else { LuaStr(&field.doc.replace('\n', " ")).to_string() };
+
+            write!(f, "fields.{} = ", key)?;
+            match field.kind {
+                PackedFieldKind::Str { .. } => {
+                    writeln!(f, "ProtoField.string({}, {}, base.ASCII, {})", LuaStr(&abbr), LuaStr(&label), doc)?;
+                },
+                PackedFieldKind::Bool { .. } if bits == 1 => {
+                    writeln!(f, "ProtoField.bool({}, {}, {}, nil, 0x{:X}, {})", LuaStr(&abbr), LuaStr(&label), window_bytes * 8, mask, doc)?;
+                },
+                _ if window_bytes > 8 => {
+                    writeln!(f, "ProtoField.bytes({}, {}, base.NONE, {})", LuaStr(&abbr), LuaStr(&label), doc)?;
+                },
+                _ => {
+                    let signed = field.kind == PackedFieldKind::Integer { signed: true };
+                    let values = match field.enum_schema {
+                        Some(e) => format!("{}_values", to_snake_case(e.name)),
+                        None => "nil".to_string()
+                    };
+                    let base = if field.enum_schema.is_some() || signed { "base.DEC" } else { "base.HEX" };
+                    match window_bytes {
+                        1 | 2 | 3 | 4 | 8 => {
+                            writeln!(f, "ProtoField.{}int{}({}, {}, {}, {}, {}, {})",
+                                if signed { "" } else { "u" }, window_bytes * 8, LuaStr(&abbr), LuaStr(&label), base, values, mask_str, doc)?;
+                        },
+                        _ => {
+                            // the 40, 48 and 56 bit integers only have a field type, without a constructor
+                            writeln!(f, "ProtoField.new({}, {}, ftypes.{}INT{}, {}, {}, {}, {})",
+                                LuaStr(&label), LuaStr(&abbr), if signed { "" } else { "U" }, window_bytes * 8, values, base, mask_str, doc)?;
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Adds the fields of a structure to the tree.
+    fn fmt_tree(&self, f: &mut fmt::Formatter, schema: &PackedStructSchema, bit_offset: usize, prefix: &str, tree: &str, indent: &str) -> fmt::Result {
+        for field in schema.fields {
+            let key = format!("{}{}", prefix, snake_name(field.name));
+            let (start, end) = (bit_offset + field.bits_msb0.start, bit_offset + field.bits_msb0.end);
+            let window_start = start / 8;
+            let window_bytes = end / 8 - window_start + 1;
+
+            if let Some(nested) = field.nested {
+                let nested_tree = format!("{}_tree", key);
+                writeln!(f, "{}local {} = {}:add(buffer({}, {}), {})", indent, nested_tree, tree, window_start, window_bytes, LuaStr(field.name))?;
+                self.fmt_tree(f, nested(), start, &format!("{}__", key), &nested_tree, indent)?;
+                continue;
+            }
+
+            let add = if field.endianness == Some(IntegerEndianness::Lsb) { "add_le" } else { "add" };
+            writeln!(f, "{}{}:{}(fields.{}, buffer({}, {}))", indent, tree, add, key, window_start, window_bytes)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for WiresharkDissector {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let proto = self.proto_name();
+        let proto_var = format!("{}_proto", proto);
+        let num_bytes = self.root.num_bytes;
+
+        writeln!(f, "-- Generated by packed_struct, do not edit.")?;
+        writeln!(f, "local {} = Proto({}, {})", proto_var, LuaStr(&proto), LuaStr(self.root.name))?;
+        writeln!(f)?;
+
+        for e in &self.schemas.enums {
+            writeln!(f, "local {}_values = {{", to_snake_case(e.name))?;
+            for variant in e.variants {
+                writeln!(f, "    [{}] = {},", variant.value, LuaStr(variant.name))?;
+            }
+            writeln!(f, "}}")?;
+            writeln!(f)?;
+        }
+
+        writeln!(f, "local fields = {}.fields", proto_var)?;
+        self.fmt_declarations(f, self.root, 0, "")?;
+        writeln!(f)?;
+
+        writeln!(f, "function {}.dissector(buffer, pinfo, tree)", proto_var)?;
+        writeln!(f, "    if buffer:len() < {} then return 0 end", num_bytes)?;
+        writeln!(f, "    pinfo.cols.protocol = {}.name", proto_var)?;
+        writeln!(f, "    local subtree = tree:add({}, buffer(0, {}))", proto_var, num_bytes)?;
+        self.fmt_tree(f, self.root, 0, "", "subtree", "    ")?;
+        writeln!(f, "    return {}", num_bytes)?;
+        writeln!(f, "end")?;
+        writeln!(f)?;
+        writeln!(f, "-- Register the dissector, for example on a UDP port:")?;
+        writeln!(f, "-- DissectorTable.get(\"udp.port\"):add(5000, {})", proto_var)
+    }
+}
+
+/// A double quoted Lua string.
+struct LuaStr<'a>(&'a str);
+
+impl<'a> Display for LuaStr<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("\"")?;
+        for c in self.0.chars() {
+            match c {
+                '"' => f.write_str("\\\"")?,
+                '\\' => f.write_str("\\\\")?,
+                '\n' => f.write_str("\\n")?,
+                c => f.write_char(c)?
+            }
+        }
+        f.write_str("\"")
+    }
+}
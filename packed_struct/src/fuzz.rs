@@ -0,0 +1,109 @@
+//! Fuzzing the unpacking of the packed structures.
+//!
+//! The generated ```unpack``` and ```unpack_from_slice``` are meant to return a
+//! ```PackingError``` for any input they can't unpack: buffers that are too short, enum
+//! values without a variant, reserved bits with the wrong value and text that isn't valid.
+//! The ```fuzz``` directory of the repository holds the ```cargo fuzz``` targets that check
+//! this for the structures of ```packed_struct_tests::fuzz_corpus```.
+//!
+//! ```rust,ignore
+//! #![no_main]
+//! #[macro_use] extern crate libfuzzer_sys;
+//! extern crate packed_struct;
+//!
+//! use packed_struct::fuzz::*;
+//!
+//! fuzz_target!(|data: &[u8]| {
+//!     fuzz_unpack::<Frame>(data);
+//! });
+//! ```
+//!
+//! With the ```arbitrary``` feature, ```PackedBytes``` takes exactly the packed size of a
+//! structure from the fuzzer's input.
+//!
+//! ```rust,ignore
+//! fuzz_target!(|bytes: PackedBytes<Frame>| {
+//!     fuzz_unpack::<Frame>(&bytes.bytes);
+//! });
+//! ```
+
+use internal_prelude::v1::*;
+
+use packing::*;
+
+#[cfg(feature="arbitrary")]
+use arbitrary_crate::{Arbitrary, Unstructured};
+
+/// Unpacks untrusted bytes. Panics if the unpacking panics, if a buffer that is too
+/// short doesn't return ```BufferTooSmall```, or if a structure that unpacked doesn't
+/// pack back into bytes that unpack into the same packing.
+pub fn fuzz_unpack<T>(data: &[u8]) where T: PackedStructSlice {
+    match T::unpack_from_slice(data) {
+        Ok(value) => {
+            let packed = value.pack_to_vec().expect("An unpacked structure didn't pack");
+            let unpacked = T::unpack_from_slice(&packed).expect("The packed bytes didn't unpack");
+            let repacked = unpacked.pack_to_vec().expect("An unpacked structure didn't pack");
+            assert_eq!(packed, repacked, "The packing isn't canonical");
+        },
+        Err(e) => {
+            if data.len() < T::packed_bytes() {
+                assert_eq!(PackingError::BufferTooSmall, e);
+            }
+        }
+    }
+}
+
+#[cfg(feature="arbitrary")]
+/// Raw bytes of the packed size of a structure, taken from the fuzzer's input.
+pub struct PackedBytes<T> {
+    pub bytes: Vec<u8>,
+    _structure: PhantomData<T>
+}
+
+#[cfg(feature="arbitrary")]
+impl<T> PackedBytes<T> where T: PackedStructSlice {
+    /// Unpacks the bytes.
+    pub fn unpack(&self) -> Result<T, PackingError> {
+        T::unpack_from_slice(&self.bytes)
+    }
+}
+
+#[cfg(feature="arbitrary")]
+impl<'a, T> Arbitrary<'a> for PackedBytes<T> where T: PackedStructSlice {
+    fn arbitrary(u: &mut Unstructured<'a>) -> ::arbitrary_crate::Result<Self> {
+        Ok(PackedBytes {
+            bytes: u.bytes(T::packed_bytes())?.to_vec(),
+            _structure: PhantomData
+        })
+    }
+
+    fn size_hint(_depth: usize) -> (usize, Option<usize>) {
+        (T::packed_bytes(), Some(T::packed_bytes()))
+    }
+}
+
+#[cfg(feature="arbitrary")]
+impl<T> Debug for PackedBytes<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "PackedBytes({:?})", self.bytes)
+    }
+}
+
+#[cfg(feature="arbitrary")]
+impl<T> Clone for PackedBytes<T> {
+    fn clone(&self) -> Self {
+        PackedBytes {
+            bytes: self.bytes.clone(),
+            _structure: PhantomData
+        }
+    }
+}
+
+#[cfg(feature="arbitrary")]
+/// Takes the packed bytes of a structure from the fuzzer's input and unpacks them
+/// with ```fuzz_unpack```.
+pub fn fuzz_packed_bytes<T>(u: &mut Unstructured) -> ::arbitrary_crate::Result<()> where T: PackedStructSlice {
+    let bytes: PackedBytes<T> = u.arbitrary()?;
+    fuzz_unpack::<T>(&bytes.bytes);
+    Ok(())
+}
@@ -0,0 +1,240 @@
+//! Annotated hexdumps of raw bytes, decoded against the schema of a structure.
+//!
+//! Unlike ```PackedStructDisplay```, the bytes don't have to unpack. Every field is
+//! decoded on its own, so the dump still shows the enum values without a variant, the
+//! reserved bits with the wrong value and the text that isn't valid, next to the
+//! fields that are fine. The fields are decoded with the bool patterns, the enum
+//! signedness, the reserved values and the text padding of the schema.
+//!
+//! ```rust
+//! extern crate packed_struct;
+//! #[macro_use] extern crate packed_struct_codegen;
+//!
+//! use packed_struct::prelude::*;
+//! use packed_struct::hexdump::annotated_hexdump;
+//!
+//! #[derive(PrimitiveEnum_u8, Copy, Clone, Debug, PartialEq)]
+//! pub enum Mode {
+//!     Off = 0,
+//!     On = 1
+//! }
+//!
+//! #[derive(PackedStruct)]
+//! #[packed_struct(bit_numbering="msb0", schema)]
+//! pub struct Status {
+//!     #[packed_field(bits="0:3", ty="enum")]
+//!     mode: Mode,
+//!     #[packed_field(bits="4:7")]
+//!     _reserved: ReservedZero<packed_bits::Bits4>
+//! }
+//!
+//! fn main() {
+//!     let dump = annotated_hexdump::<Status>(&[0x95]).to_string();
+//!     assert!(dump.contains("mode [0:3] = invalid enum value 9"));
+//!     assert!(dump.contains("_reserved [4:7] = 0b0101, expected 0b0000"));
+//! }
+//! ```
+
+use internal_prelude::v1::*;
+
+use schema::*;
+use types_str::{StringPadding, unpack_str};
+
+/// Why a field couldn't be decoded.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldDecodeError {
+    /// The value doesn't match any of the enum's variants.
+    InvalidEnumValue(i128),
+    /// A boolean that matches neither of its patterns.
+    InvalidBoolPattern(u64),
+    /// Reserved bits that don't have their fixed value.
+    InvalidReservedBits { expected: u64, actual: u64 },
+    /// Text that isn't valid UTF-8.
+    InvalidUtf8,
+    /// A NUL terminated text without its terminator.
+    MissingTerminator,
+    /// The field doesn't fit into the bytes.
+    Truncated
+}
+
+impl Display for FieldDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FieldDecodeError::InvalidEnumValue(v) => write!(f, "invalid enum value {}", v),
+            FieldDecodeError::InvalidBoolPattern(v) => write!(f, "invalid boolean pattern {:#b}", v),
+            FieldDecodeError::InvalidReservedBits { .. } => write!(f, "invalid reserved bits"),
+            FieldDecodeError::InvalidUtf8 => write!(f, "invalid UTF-8"),
+            FieldDecodeError::MissingTerminator => write!(f, "missing NUL terminator"),
+            FieldDecodeError::Truncated => write!(f, "truncated")
+        }
+    }
+}
+
+/// A field decoded from raw bytes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedField {
+    /// Name of the field. The fields of nested structures have a dotted name, like `status.ready`.
+    pub name: String,
+    /// Position of the field in the bytes, MSB0.
+    pub bits: BitRange,
+    /// The display value, or the reason the field couldn't be decoded.
+    pub value: Result<String, FieldDecodeError>
+}
+
+/// Decodes every field of the raw bytes on its own. The nested structures are replaced
+/// by their fields.
+pub fn decode_fields(schema: &PackedStructSchema, bytes: &[u8]) -> Vec<DecodedField> {
+    let mut fields = Vec::new();
+    decode_into(schema, bytes, 0, "", &mut fields);
+    fields
+}
+
+fn decode_into(schema: &PackedStructSchema, bytes: &[u8], bit_offset: usize, prefix: &str, fields: &mut Vec<DecodedField>) {
+    for field in schema.fields {
+        let bits = BitRange { start: bit_offset + field.bits_msb0.start, end: bit_offset + field.bits_msb0.end };
+        let name = format!("{}{}", prefix, field.name);
+
+        if let (PackedFieldKind::Struct, Some(nested)) = (field.kind, field.nested) {
+            decode_into(nested(), bytes, bits.start, &format!("{}.", name), fields);
+            continue;
+        }
+
+        let value = if bits.end / 8 >= bytes.len() {
+            Err(FieldDecodeError::Truncated)
+        } else {
+            decode_field(field, bytes, bits)
+        };
+
+        fields.push(DecodedField {
+            name,
+            bits,
+            value
+        });
+    }
+}
+
+fn decode_field(field: &PackedFieldSchema, bytes: &[u8], bits: BitRange) -> Result<String, FieldDecodeError> {
+    let width = bits.bits();
+
+    match field.kind {
+        PackedFieldKind::Str { padding } => {
+            let text: Vec<u8> = (0..width / 8).map(|i| get_bits(bytes, bits.start + i * 8, 8) as u8).collect();
+            if padding == StringPadding::NulTerminated && !text.contains(&0) {
+                return Err(FieldDecodeError::MissingTerminator);
+            }
+            unpack_str(&text, padding).map(|s| format!("{:?}", s)).map_err(|_| FieldDecodeError::InvalidUtf8)
+        },
+        _ if width > 64 => {
+            let hex: Vec<_> = (0..(width + 7) / 8).map(|i| format!("{:02X}", get_bits(bytes, bits.start + i * 8, min(8, width - i * 8)))).collect();
+            Ok(format!("0x{}", hex.join("")))
+        },
+        PackedFieldKind::Bool { true_bits, false_bits, nonzero } => {
+            let value = get_bits(bytes, bits.start, width);
+            match value {
+                v if v == true_bits => Ok("true".to_string()),
+                v if v == false_bits => Ok("false".to_string()),
+                _ if nonzero => Ok("true".to_string()),
+                v => Err(FieldDecodeError::InvalidBoolPattern(v))
+            }
+        },
+        PackedFieldKind::Reserved { ones } => {
+            let actual = get_bits(bytes, bits.start, width);
+            let expected = if ones { u64::MAX >> (64 - width) } else { 0 };
+            if actual == expected {
+                Ok(format!("0b{:01$b}", actual, width))
+            } else {
+                Err(FieldDecodeError::InvalidReservedBits { expected, actual })
+            }
+        },
+        PackedFieldKind::Enum => {
+            let value = integer_value(field, bytes, bits);
+            let signed = field.enum_schema.map(|e| e.primitive.starts_with('i')).unwrap_or(false);
+            let value = if signed {
+                let shift = 64 - width;
+                (((value << shift) as i64) >> shift) as i128
+            } else {
+                value as i128
+            };
+            let variant = field.enum_schema.and_then(|e| e.variant_by_value(value));
+            match variant {
+                Some(variant) => Ok(variant.name.to_string()),
+                None if field.type_name.starts_with("EnumCatchAll<") => Ok(format!("{}", value)),
+                None => Err(FieldDecodeError::InvalidEnumValue(value))
+            }
+        },
+        PackedFieldKind::Integer { signed: true } => {
+            let value = integer_value(field, bytes, bits);
+            let shift = 64 - width;
+            Ok(format!("{}", ((value << shift) as i64) >> shift))
+        },
+        _ => Ok(format!("{}", integer_value(field, bytes, bits)))
+    }
+}
+
+fn integer_value(field: &PackedFieldSchema, bytes: &[u8], bits: BitRange) -> u64 {
+    let width = bits.bits();
+    let value = get_bits(bytes, bits.start, width);
+    if field.endianness == Some(IntegerEndianness::Lsb) && width % 8 == 0 {
+        (0..width / 8).fold(0, |swapped, i| (swapped << 8) | ((value >> (i * 8)) & 0xFF))
+    } else {
+        value
+    }
+}
+
+/// Reads up to 64 bits, the most significant bit first.
+fn get_bits(bytes: &[u8], start: usize, bits: usize) -> u64 {
+    (start..start + bits).fold(0, |value, i| (value << 1) | ((bytes[i / 8] >> (7 - i % 8)) & 1) as u64)
+}
+
+/// Raw bytes with the fields that overlap every byte.
+pub struct PackedHexdump<'a> {
+    pub schema: &'a PackedStructSchema,
+    pub bytes: &'a [u8]
+}
+
+/// An annotated hexdump of the raw bytes, decoded as the structure.
+pub fn annotated_hexdump<T>(bytes: &[u8]) -> PackedHexdump<'_> where T: PackedStructSchemaInfo {
+    PackedHexdump {
+        schema: T::packed_struct_schema(),
+        bytes
+    }
+}
+
+impl<'a> Display for PackedHexdump<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let fields = decode_fields(self.schema, self.bytes);
+        let num_bytes = max(self.schema.num_bytes, self.bytes.len());
+
+        write!(f, "{} ({} of {} bytes)\r\n", self.schema.name, self.bytes.len(), self.schema.num_bytes)?;
+
+        for i in 0..num_bytes {
+            match self.bytes.get(i) {
+                Some(b) => write!(f, "{:04X}  {:02X}  {:08b}", i, b, b)?,
+                None => write!(f, "{:04X}  --  --------", i)?
+            }
+
+            let overlapping = fields.iter().filter(|field| field.bits.start / 8 <= i && i <= field.bits.end / 8);
+            let mut annotated = false;
+            for field in overlapping {
+                f.write_str(if annotated { ", " } else { "  " })?;
+                annotated = true;
+
+                write!(f, "{} [{}] = ", field.name, field.bits)?;
+                match field.value {
+                    Ok(ref value) => write!(f, "{}", value)?,
+                    Err(FieldDecodeError::InvalidReservedBits { expected, actual }) => {
+                        write!(f, "0b{:02$b}, expected 0b{:02$b}", actual, expected, field.bits.bits())?
+                    },
+                    Err(ref e) => write!(f, "{}", e)?
+                }
+            }
+
+            if i >= self.schema.num_bytes {
+                f.write_str("  (trailing)")?;
+            }
+            f.write_str("\r\n")?;
+        }
+
+        Ok(())
+    }
+}
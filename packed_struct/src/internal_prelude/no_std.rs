@@ -9,12 +9,20 @@ pub use core::fmt::Error as FmtError;
 pub use core::ops::Range;
 pub use core::num::Wrapping;
 pub use core::cmp::*;
+pub use core::hash::{Hash, Hasher};
 pub use core::mem;
 pub use core::intrinsics::write_bytes;
 pub use core::ops::Deref;
 pub use core::slice;
+pub use core::str;
+pub use core::str::FromStr;
+pub use core::convert::TryFrom;
 
 #[cfg(feature="alloc")]
 pub use alloc::vec::Vec;
 #[cfg(feature="alloc")]
 pub use alloc::borrow::Cow;
+#[cfg(feature="alloc")]
+pub use alloc::collections::BTreeMap;
+#[cfg(feature="alloc")]
+pub use alloc::string::String;
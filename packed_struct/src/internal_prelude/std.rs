@@ -11,14 +11,18 @@ pub use std::fmt::Error as FmtError;
 pub use std::mem;
 pub use std::marker::PhantomData;
 pub use std::ops::Range;
-pub use std::cmp::{min, max};
+pub use std::cmp::{min, max, Ordering};
+pub use std::hash::{Hash, Hasher};
 pub use std::ptr::write_bytes;
 pub use std::iter;
 pub use std::borrow::Cow;
+pub use std::collections::BTreeMap;
 pub use std::str::FromStr;
 pub use std::io;
 pub use std::io::Write;
 pub use std::sync::Arc;
 pub use std::str::from_utf8;
 pub use std::ops::Deref;
-pub use std::slice;
\ No newline at end of file
+pub use std::slice;
+pub use std::str;
+pub use std::convert::TryFrom;
\ No newline at end of file
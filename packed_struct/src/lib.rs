@@ -18,10 +18,25 @@
 //!  * Primitive enum code generation helper
 //!  * MSB0 or LSB0 bit positioning
 //!  * Documents the field's packing table
-//!  * Runtime packing visualization
+//!  * Runtime packing visualization, as aligned text or Markdown tables
+//!  * Field tables written to any ```core::fmt::Write```, also without an allocator
+//!  * Field by field diffs of two values, with the changed bits highlighted
+//!  * Annotated hexdumps of raw bytes, including the fields that don't decode
 //!  * Nested packed types
 //!  * Arrays of packed structures as fields
+//!  * Multi-dimensional arrays, like ```[[u8; 3]; 16]```
 //!  * Reserved fields, their bits are always 0 or 1
+//!  * Fixed size text fields
+//!  * Runtime schema of the fields, for generic tooling
+//!  * C header export, with masks, shifts and enum ```typedef```s
+//!  * Layout export to JSON, Kaitai Struct and Wireshark dissectors
+//!  * Structures generated from CMSIS-SVD and IP-XACT register descriptions
+//!  * Registers with addresses, with read, write and modify helpers over any bus
+//!  * Property testing strategies and round trip checks, with the ```proptest``` feature
+//!  * Fuzzing of the unpacking of untrusted input, with ```cargo fuzz```
+//!  * Serde support with the ```serde``` feature, enums by their names and without the reserved fields
+//!  * A bit-packed serde format for the types that derive ```Serialize```, with the widths of the fields
+//!  * ```defmt::Format```, ```log``` key-values and ```tracing``` events with the fields, as optional features
 //!
 //! # Sample usage
 //!
@@ -111,6 +126,12 @@
 //! ```size_bytes``` | ```1``` ... n | Size of the packed byte stream
 //! ```bit_numbering``` | ```msb0``` or ```lsb0``` | Bit numbering for bit positioning of fields. Required if the bits attribute field is used.
 //! ```endian``` | ```msb``` or ```lsb``` | Default integer endianness
+//! ```address``` | ```0xA0``` | Address of the register that the structure describes, see the ```register``` module
+//! ```access``` | ```rw```, ```ro``` or ```wo``` | Allowed accesses of the register, read-write by default
+//! ```schema``` | | Generate the runtime schema of the structure, see below
+//! ```proptest``` | | Implement proptest's ```Arbitrary``` with the strategy of the ```arbitrary``` module. Implies ```schema```.
+//! ```serde``` | | Implement ```Serialize``` and ```Deserialize``` without the reserved fields, with the ```serde``` feature
+//! ```debug_write``` | | Implement ```PackedStructDebugWrite```, the fields table written without allocating. Every field has to implement ```Debug```.
 //!
 //! ## Per-field attributes
 //!
@@ -120,10 +141,19 @@
 //! ```bytes``` | ```0```, ```0..1```, ... | Same as above, multiplied by 8.
 //! ```size_bits``` | ```1```, ... | Specifies the size of the packed structure. Mandatory for certain types. Specifying a range of bits like ```bits="0..2"``` can substite the required usage of ```size_bits```.
 //! ```size_bytes``` | ```1```, ... | Same as above, multiplied by 8.
-//! ```element_size_bits``` | ```1```, ... | For packed arrays, specifies the size of a single element of the array. Explicitly stating the size of the entire array can substite the usage of this attribute.
+//! ```element_size_bits``` | ```1```, ... | For packed arrays, specifies the size of a single element of the array. Explicitly stating the size of the entire array can substite the usage of this attribute. Multi-dimensional arrays are packed as a flat array of their innermost elements.
 //! ```element_size_bytes``` | ```1```, ... | Same as above, multiplied by 8.
-//! ```ty``` | ```enum``` | Packing helper for primitive enums.
+//! ```element_padding_bits``` | ```1```, ... | For packed arrays, the number of unused bits after each element.
+//! ```element_padding_bytes``` | ```1```, ... | Same as above, multiplied by 8.
+//! ```element_stride_bits``` | ```1```, ... | For packed arrays, the distance between the starting bits of two elements. An alternative to the element padding.
+//! ```element_stride_bytes``` | ```1```, ... | Same as above, multiplied by 8.
+//! ```element_order``` | ```forward``` or ```reverse``` | For packed arrays, the reverse order places the first element into the last slot of the field.
+//! ```ty``` | ```enum```, ```exhaustive_enum``` or ```str``` | Packing helper for primitive enums or text fields. Exhaustive enums have a variant for every value of the field's bits and unpack without errors. Text fields can be ```String``` or the allocation free ```FixedString```, which takes its width from its ```BytesN``` type and fails to compile when it doesn't match the position of the field.
 //! ```endian``` | ```msb``` or ```lsb``` | Integer endianness. Applies to u16/i16 and larger types.
+//! ```active``` | ```high``` or ```low``` | Polarity of a bool field. Active low booleans pack ```true``` as zeroes.
+//! ```bool_true```, ```bool_false``` | ```0b11```, ```0x0```, ... | Bit patterns of a multi-bit bool field. By default, true is all ones and false is all zeroes.
+//! ```bool_mode``` | ```strict``` or ```nonzero``` | Unpacking of bool fields. In the ```nonzero``` mode, anything but the false pattern is unpacked as true.
+//! ```padding``` | ```nul```, ```space``` or ```nul_terminated``` | Padding of the unused bytes in a text field. Defaults to ```nul```. Unpacking fails on invalid UTF-8.
 //! 
 //! ## Bit and byte positioning
 //! 
@@ -250,6 +280,39 @@
 //!     VariantMin = -32768,
 //!     VariantMax = 32767
 //! }
+//!
+//! const READ: u8 = 0x20;
+//!
+//! /// The type can also come from the ```repr``` or the ```primitive``` attribute, for
+//! /// discriminants that are constants or expressions.
+//! #[derive(PrimitiveEnum, Clone, Copy)]
+//! #[repr(u8)]
+//! pub enum Command {
+//!     Reset = 1 << 3,
+//!     Read = READ,
+//!     ReadNext
+//! }
+//!
+//! /// Several values can unpack into the same variant, which packs into its discriminant.
+//! /// The name replaces the variant's identifier for display and parsing.
+//! #[derive(PrimitiveEnum_u8, Clone, Copy)]
+//! pub enum PowerLevel {
+//!     #[primitive(name = "low-power")]
+//!     Low = 1,
+//!     #[primitive(alias = 3)]
+//!     High = 2,
+//!     #[primitive(range = "8..=15")]
+//!     Reserved = 4
+//! }
+//!
+//! /// Implements ```Display```, ```FromStr```, ```TryFrom<u8>``` and ```From<Mode> for u8```.
+//! /// Parsing ignores the ASCII case and fails with a ```ParseEnumError```.
+//! #[derive(PrimitiveEnum_u8, Clone, Copy)]
+//! #[primitive(display, from_str, try_from, into)]
+//! pub enum Mode {
+//!     Idle = 0,
+//!     Run = 1
+//! }
 //! 
 //! # fn main() {}
 //! ```
@@ -277,6 +340,194 @@
 //! 
 //! # fn main() {}
 //! ```
+//!
+//! ```EnumCatchAll``` is compared, hashed and ordered by its primitive value, so it works as
+//! a key of a map or a set. ```as_enum()``` returns the variant and ```raw()``` the value. The
+//! unknown values are displayed as ```Unknown value: 9``` and parse back from that text or from
+//! the plain number. An unknown value that is wider than its field is cut to the bits of the field.
+//!
+//! The enum fields are checked at compile time, every variant has to fit into the bits
+//! of the field. ```PrimitiveEnumBounds::MAX_BITS``` is the number of bits that the enum needs,
+//! the enums that implement ```PrimitiveEnum``` by hand and not ```PrimitiveEnumBounds``` aren't checked.
+//!
+//! ```rust,compile_fail
+//! # use packed_struct::prelude::*;
+//! extern crate packed_struct;
+//! #[macro_use] extern crate packed_struct_codegen;
+//!
+//! #[derive(PrimitiveEnum_u8, Debug, Clone, Copy)]
+//! pub enum Field {
+//!     A = 1,
+//!     B = 7
+//! }
+//!
+//! #[derive(PackedStruct)]
+//! #[packed_struct(bit_numbering="msb0")]
+//! pub struct Register {
+//!     #[packed_field(bits="3..=4", ty="enum")]
+//!     field: Field
+//! }
+//!
+//! # fn main() {}
+//! ```
+//!
+//! An enum with ```#[primitive(bits = 2)]``` implements ```ExhaustivePrimitiveEnum```, with an
+//! infallible ```from_bits```. Every value of the declared bits has to be a variant, an alias
+//! or in a range of a variant, otherwise the derive fails. Without the attribute the enum isn't
+//! exhaustive, even when its variants happen to cover their bits. Fields with
+//! ```ty="exhaustive_enum"``` unpack through it.
+//!
+//! ```rust
+//! extern crate packed_struct;
+//! #[macro_use] extern crate packed_struct_codegen;
+//!
+//! #[derive(PrimitiveEnum_u8, Debug, Clone, Copy)]
+//! #[primitive(bits = 2)]
+//! pub enum Direction {
+//!     North = 0,
+//!     East = 1,
+//!     #[primitive(alias = 3)]
+//!     South = 2
+//! }
+//!
+//! #[derive(PackedStruct)]
+//! #[packed_struct(bit_numbering="msb0")]
+//! pub struct Heading {
+//!     #[packed_field(bits="6..=7", ty="exhaustive_enum")]
+//!     direction: Direction
+//! }
+//!
+//! # fn main() {}
+//! ```
+//!
+//! ```rust,compile_fail
+//! extern crate packed_struct;
+//! #[macro_use] extern crate packed_struct_codegen;
+//!
+//! // covers its two bits, but doesn't declare them
+//! #[derive(PrimitiveEnum_u8, Debug, Clone, Copy)]
+//! pub enum Direction {
+//!     North = 0,
+//!     East = 1,
+//!     South = 2,
+//!     West = 3
+//! }
+//!
+//! #[derive(PackedStruct)]
+//! #[packed_struct(bit_numbering="msb0")]
+//! pub struct Heading {
+//!     #[packed_field(bits="6..=7", ty="exhaustive_enum")]
+//!     direction: Direction
+//! }
+//!
+//! # fn main() {}
+//! ```
+//!
+//! # Runtime schema
+//! 
+//! The structures marked with ```#[packed_struct(schema)]``` describe their fields at runtime: their
+//! names, types, bit ranges in both numberings, endianness, enum variants, reserved status and doc
+//! comments. The nested structures of such a structure need the schema as well. The exports, the
+//! hex dumps, the key-value logging and the property testing strategies are built on the schema.
+//!
+//! The fields can also be read and written by their names, with ```get_field``` and ```set_field```.
+//! Fields of nested structures are named with a dot, like ```status.ready```.
+//! 
+//! ```rust
+//! extern crate packed_struct;
+//! #[macro_use] extern crate packed_struct_codegen;
+//!
+//! use packed_struct::prelude::*;
+//!
+//! #[derive(PackedStruct)]
+//! #[packed_struct(bit_numbering="msb0", schema)]
+//! pub struct Status {
+//!     /// Set when the device is ready
+//!     #[packed_field(bits="0")]
+//!     ready: bool,
+//!     #[packed_field(bits="1:7")]
+//!     level: Integer<u8, packed_bits::Bits7>
+//! }
+//!
+//! fn main() {
+//!     let schema = Status::packed_struct_schema();
+//!     for field in schema.fields {
+//!         println!("{} ({}): MSB0 {}, LSB0 {}, {}", field.name, field.type_name, field.bits_msb0, field.bits_lsb0, field.doc);
+//!     }
+//! }
+//! ```
+//!
+//! # Register descriptions
+//!
+//! The ```packed_struct_svd``` crate generates the packed structures and enums from CMSIS-SVD and
+//! IP-XACT register description files, from a build script. Every register becomes a ```lsb0```
+//! structure with its reserved bits and its schema, and the enumerated field values become primitive enums.
+//!
+//! ```rust,ignore
+//! // build.rs
+//! extern crate packed_struct_svd;
+//!
+//! fn main() {
+//!     let out = ::std::env::var("OUT_DIR").unwrap();
+//!     packed_struct_svd::generate_file("device.svd", format!("{}/device.rs", out)).unwrap();
+//! }
+//! ```
+//!
+//! # Untrusted input
+//!
+//! The generated ```unpack``` and ```unpack_from_slice``` are written to return a ```PackingError```
+//! for any bytes they can't unpack, instead of panicking, so that the structures can parse input from
+//! the network or from a device. The ```fuzz``` directory holds the ```cargo fuzz``` targets that unpack
+//! random input into a set of structures with every kind of field, and the ```fuzz_corpus``` test runs
+//! them on random bytes. The ```packed_struct::fuzz``` module has the same checks for your own
+//! targets, and with the ```arbitrary``` feature, ```PackedBytes<T>``` takes the packed size of a
+//! structure from the fuzzer's input.
+//!
+//! ```text
+//! cargo +nightly fuzz run unpack
+//! ```
+//!
+//! # Serde
+//!
+//! With the ```serde``` feature of both crates, the primitive enums marked with ```#[primitive(serde)]```
+//! and the packed structures marked with ```#[packed_struct(serde)]``` implement ```Serialize``` and
+//! ```Deserialize```, so register configurations can be stored as JSON or TOML and loaded back. The
+//! enums are stored by the name of the variant and also load from their value, ```EnumCatchAll```
+//! stores the unknown values as numbers, and the reserved fields are skipped. The formats that aren't
+//! human readable store the primitive values of the enums.
+//!
+//! ```toml
+//! [dependencies]
+//! packed_struct = { version = "0.3", features = ["serde"] }
+//! packed_struct_codegen = { version = "0.3", features = ["serde"] }
+//! ```
+//!
+//! The ```serde_bits``` module is a serde format of its own, which packs any type that derives
+//! ```Serialize``` into bits, with the number of bits of each field given at runtime or taken from
+//! the schema of a packed structure. Only the widths are taken from the schema, the values are
+//! written one after another, without the positions, the reserved bits or the endianness of the
+//! packed structure.
+//!
+//! # Logging
+//!
+//! With the ```defmt``` feature of both crates, the structures and the primitive enums implement
+//! ```defmt::Format```, with the names and values of the fields. ```Integer```, ```ReservedBits```,
+//! ```EnumCatchAll``` and ```FixedString``` implement it with the ```defmt``` feature of ```packed_struct```.
+//! With the ```log``` feature, ```packed_struct::log_kv::log_fields``` logs a structure with every field
+//! as a key-value of the record, for the structures with ```#[packed_struct(schema, debug_write)]```.
+//! With the ```tracing``` feature of both crates, ```trace_event``` records an event with every field
+//! as an attribute.
+//!
+//! The generated ```defmt::Format``` implementations and the ```defmt::write!``` macro refer to
+//! ```defmt``` by its name, so the crate that derives them needs ```defmt``` as a direct dependency
+//! (and an ```extern crate defmt;``` in its root, for the 2015 edition).
+//!
+//! ```toml
+//! [dependencies]
+//! defmt = "1.0"
+//! packed_struct = { version = "0.3", features = ["defmt"] }
+//! packed_struct_codegen = { version = "0.3", features = ["defmt"] }
+//! ```
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
@@ -287,8 +538,25 @@
 extern crate alloc;
 
 
-extern crate serde;
-#[macro_use] extern crate serde_derive;
+#[cfg(feature="serde")]
+pub extern crate serde;
+#[cfg(feature="serde")]
+#[macro_use] pub extern crate serde_derive;
+
+#[cfg(feature="proptest")]
+extern crate proptest;
+
+#[cfg(feature="arbitrary")]
+extern crate arbitrary as arbitrary_crate;
+
+#[cfg(feature="defmt")]
+extern crate defmt;
+
+#[cfg(feature="log")]
+extern crate log;
+
+#[cfg(feature="tracing")]
+extern crate tracing;
 
 mod internal_prelude;
 
@@ -302,11 +570,59 @@ pub use primitive_enum::*;
 #[cfg(any(feature="alloc", feature="std"))]
 pub mod debug_fmt;
 
+#[cfg(any(feature="alloc", feature="std"))]
+pub mod hexdump;
+
+pub mod debug_write;
+
+#[cfg(feature="serde")]
+pub mod serde_enum;
+
+#[cfg(all(feature="serde", any(feature="alloc", feature="std")))]
+pub mod serde_bits;
+
+#[cfg(feature="log")]
+pub mod log_kv;
+
+#[cfg(feature="tracing")]
+pub mod tracing_event;
+
+pub mod schema;
+
+pub mod register;
+
+#[cfg(feature="proptest")]
+pub mod arbitrary;
+
+#[cfg(any(feature="alloc", feature="std"))]
+pub mod fuzz;
+
+#[cfg(any(feature="alloc", feature="std"))]
+mod export_common;
+#[cfg(any(feature="alloc", feature="std"))]
+mod export_c;
+#[cfg(any(feature="alloc", feature="std"))]
+mod export_json;
+#[cfg(any(feature="alloc", feature="std"))]
+mod export_kaitai;
+#[cfg(any(feature="alloc", feature="std"))]
+mod export_wireshark;
+
+#[cfg(any(feature="alloc", feature="std"))]
+/// Exports of the packed layouts into formats used by other languages and tools.
+pub mod export {
+    pub use super::export_c::*;
+    pub use super::export_json::*;
+    pub use super::export_kaitai::*;
+    pub use super::export_wireshark::*;
+}
+
 mod types_array;
 mod types_basic;
 mod types_bits;
 mod types_num;
 mod types_reserved;
+mod types_str;
 
 /// Implementations and wrappers for various packing types.
 pub mod types {
@@ -320,6 +636,7 @@ pub mod types {
     pub use super::types_num::*;
     pub use super::types_array::*;
     pub use super::types_reserved::*;
+    pub use super::types_str::*;
 }
 
 pub use self::packing::*;
@@ -333,6 +650,7 @@ pub mod prelude {
     pub use PackingError;
 
     pub use PrimitiveEnum;
+    pub use PrimitiveEnumBounds;
     #[cfg(any(feature="alloc", feature="std"))]
     pub use PrimitiveEnumDynamicStr;
 
@@ -342,6 +660,9 @@ pub mod prelude {
 
     pub use EnumCatchAll;
 
+    pub use schema::{PackedStructSchemaInfo, PrimitiveEnumSchemaInfo, PackedStructFieldAccess, FieldValue, FieldAccessError};
+    pub use register::{Register, ReadableRegister, WritableRegister, RegisterInterface};
+
     pub use types::*;
     pub use types::bits as packed_bits;
 }
@@ -0,0 +1,78 @@
+//! Structured logging with the ```log``` crate. Every field is recorded as a key-value
+//! pair of the log record, with the value formatted by its ```Debug``` implementation.
+//! The structures need ```#[packed_struct(schema, debug_write)]```.
+//!
+//! ```rust
+//! extern crate packed_struct;
+//! #[macro_use] extern crate packed_struct_codegen;
+//!
+//! use packed_struct::prelude::*;
+//! use packed_struct::log_kv::log_fields;
+//!
+//! #[derive(PackedStruct, Debug)]
+//! #[packed_struct(bit_numbering="msb0", schema, debug_write)]
+//! pub struct Status {
+//!     #[packed_field(bits="0")]
+//!     ready: bool,
+//!     #[packed_field(bits="1:7")]
+//!     level: Integer<u8, packed_bits::Bits7>
+//! }
+//!
+//! fn main() {
+//!     // a record with the message "Status" and the keys "ready" and "level"
+//!     log_fields(module_path!(), packed_struct::log_kv::Level::Info, &Status { ready: true, level: 5.into() });
+//! }
+//! ```
+
+use log::kv::{self, Key, Value, VisitSource};
+use log::{Record, Metadata};
+
+pub use log::Level;
+
+use debug_write::PackedStructDebugWrite;
+use schema::PackedStructSchemaInfo;
+
+/// The fields of a structure, as the key-values of a log record.
+pub struct LogFields<'a, P: 'a> {
+    pub packed_struct: &'a P
+}
+
+impl<'a, P> LogFields<'a, P> where P: PackedStructDebugWrite {
+    pub fn new(packed_struct: &'a P) -> Self {
+        LogFields { packed_struct }
+    }
+}
+
+impl<'a, P> kv::Source for LogFields<'a, P> where P: PackedStructDebugWrite {
+    fn visit<'kvs>(&'kvs self, visitor: &mut dyn VisitSource<'kvs>) -> Result<(), kv::Error> {
+        let mut result = Ok(());
+        self.packed_struct.visit_fields(&mut |field| {
+            if result.is_ok() {
+                result = visitor.visit_pair(Key::from_str(field.name), Value::from_dyn_debug(field.value));
+            }
+        });
+        result
+    }
+
+    fn count(&self) -> usize {
+        let mut count = 0;
+        self.packed_struct.visit_fields(&mut |_| count += 1);
+        count
+    }
+}
+
+/// Logs the structure, with its name as the message and its fields as the key-values.
+pub fn log_fields<P>(target: &str, level: Level, packed_struct: &P) where P: PackedStructDebugWrite + PackedStructSchemaInfo {
+    let logger = ::log::logger();
+    let metadata = Metadata::builder().level(level).target(target).build();
+    if level > ::log::max_level() || !logger.enabled(&metadata) {
+        return;
+    }
+
+    let fields = LogFields::new(packed_struct);
+    logger.log(&Record::builder()
+        .metadata(metadata)
+        .args(format_args!("{}", P::packed_struct_schema().name))
+        .key_values(&fields)
+        .build());
+}
@@ -24,7 +24,8 @@ pub trait PackedStructInfo {
 pub trait PackedStructSlice where Self: Sized {
     /// Pack the structure into an output buffer.
     fn pack_to_slice(&self, output: &mut [u8]) -> Result<(), PackingError>;
-    /// Unpack the structure from a buffer.
+    /// Unpack the structure from a buffer. A buffer that is too short returns
+    /// ```BufferTooSmall```.
     fn unpack_from_slice(src: &[u8]) -> Result<Self, PackingError>;
     /// Number of bytes that this structure demands for packing or unpacking.
     fn packed_bytes() -> usize;
@@ -40,7 +41,8 @@ pub trait PackedStructSlice where Self: Sized {
 
 
 
-#[derive(Debug, Copy, Clone, PartialEq, Serialize)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature="serde", derive(Serialize, Deserialize))]
 /// Packing errors that might occur during packing or unpacking
 pub enum PackingError {
     InvalidValue,
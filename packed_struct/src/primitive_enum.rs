@@ -15,12 +15,76 @@ pub trait PrimitiveEnum where Self: Sized + Copy {
     fn from_str_lower(s: &str) -> Option<Self>;
 }
 
+/// The range of the discriminants of a primitive enum. Derived together with ```PrimitiveEnum```.
+pub trait PrimitiveEnumBounds where Self: PrimitiveEnum {
+    /// The smallest discriminant.
+    const MIN: Self::Primitive;
+    /// The largest discriminant.
+    const MAX: Self::Primitive;
+    /// Number of bits that hold every discriminant, see ```primitive_bits```.
+    const MAX_BITS: usize;
+
+    /// Number of bits that hold every discriminant.
+    fn max_bits() -> usize {
+        Self::MAX_BITS
+    }
+}
+
+/// The bits of an enum field, checked at compile time. The enums that don't implement
+/// ```PrimitiveEnumBounds``` fall back to ```EnumBitsCheckFallback``` and always fit.
+#[doc(hidden)]
+pub struct EnumBitsCheck<E>(PhantomData<E>);
+
+impl<E> EnumBitsCheck<E> where E: PrimitiveEnumBounds {
+    pub const MAX_BITS: usize = E::MAX_BITS;
+}
+
+#[doc(hidden)]
+pub trait EnumBitsCheckFallback {
+    const MAX_BITS: usize = 0;
+}
+
+impl<E> EnumBitsCheckFallback for EnumBitsCheck<E> { }
+
+/// A primitive enum with a variant for every value of its bits. Derived for the enums
+/// with ```#[primitive(bits = 2)]```, whose variants cover all of the values of those bits.
+pub trait ExhaustivePrimitiveEnum where Self: PrimitiveEnum {
+    /// Number of bits, every value of them is a variant.
+    const BITS: usize;
+
+    /// Convert from the lowest ```BITS``` bits of the value, the other bits are ignored.
+    fn from_bits(bits: Self::Primitive) -> Self;
+}
+
+/// Number of bits that hold every value from ```min``` to ```max```. Two's complement
+/// when ```min``` is negative, so ```-4..=3``` takes 3 bits.
+pub const fn primitive_bits(min: i128, max: i128) -> usize {
+    let mut bits = 0;
+    if min < 0 {
+        bits = 1;
+        while bits < 128 && (min < -(1 << (bits - 1)) || max >= (1 << (bits - 1))) {
+            bits += 1;
+        }
+    } else {
+        while bits < 128 && (max >> bits) != 0 {
+            bits += 1;
+        }
+    }
+    bits
+}
+
 /// Static display formatters.
 pub trait PrimitiveEnumStaticStr where Self: Sized + Copy + PrimitiveEnum {
     /// Display value, same as the name of a particular variant.
     fn to_display_str(&self) -> &'static str;
     /// A list all possible string variants.
     fn all_variants() -> &'static [Self];
+
+    /// Convert from a string value representing the variant, ignoring the ASCII case.
+    fn from_str_ignore_case(s: &str) -> Option<Self> where Self: 'static {
+        <Self as PrimitiveEnumStaticStr>::all_variants().iter().cloned()
+            .find(|v| PrimitiveEnumStaticStr::to_display_str(v).eq_ignore_ascii_case(s))
+    }
 }
 
 #[cfg(any(feature="alloc", feature="std"))]
@@ -32,8 +96,47 @@ pub trait PrimitiveEnumDynamicStr where Self: Sized + Copy + PrimitiveEnum {
     fn all_variants() -> Cow<'static, [Self]>;
 }
 
+/// The text isn't the name of any variant. Returned by the ```FromStr``` implementations
+/// of the derived enums.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ParseEnumError {
+    pub enum_name: &'static str
+}
+
+impl Display for ParseEnumError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Unknown variant of {}", self.enum_name)
+    }
+}
+
+#[cfg(feature="std")]
+impl ::std::error::Error for ParseEnumError { }
+
+/// The value isn't the discriminant of any variant. Returned by the ```TryFrom```
+/// implementations of the derived enums.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TryFromPrimitiveError<P> {
+    pub enum_name: &'static str,
+    pub value: P
+}
+
+impl<P> Display for TryFromPrimitiveError<P> where P: Debug {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid value {:?} of {}", self.value, self.enum_name)
+    }
+}
+
+#[cfg(feature="std")]
+impl<P> ::std::error::Error for TryFromPrimitiveError<P> where P: Debug { }
+
 /// A wrapper for primitive enums that supports catching and retaining any values
 /// that don't have defined discriminants.
+///
+/// The wrapper is compared, hashed and ordered by its primitive value, so a known value
+/// held in ```CatchAll``` is equal to its variant. The unknown values are displayed as
+/// ```Unknown value: 9``` and parsed back from that text or from the plain number. When
+/// packed into a field, the unknown values are cut to the bits of the field, like the
+/// ```Integer``` type.
 #[derive(Copy, Clone, Debug)]
 pub enum EnumCatchAll<E> where E: PrimitiveEnum {
     /// A matched discriminant
@@ -46,6 +149,35 @@ impl<E> EnumCatchAll<E> where E: PrimitiveEnum {
     pub fn from_enum(v: E) -> Self {
         EnumCatchAll::Enum(v)
     }
+
+    /// The variant for a known value, otherwise the value is kept as it is.
+    pub fn from_raw(v: E::Primitive) -> Self {
+        match E::from_primitive(v) {
+            Some(e) => EnumCatchAll::Enum(e),
+            None => EnumCatchAll::CatchAll(v)
+        }
+    }
+
+    /// The variant of the enum, also for a known value held in ```CatchAll```.
+    pub fn as_enum(&self) -> Option<E> {
+        match *self {
+            EnumCatchAll::Enum(e) => Some(e),
+            EnumCatchAll::CatchAll(v) => E::from_primitive(v)
+        }
+    }
+
+    /// The primitive value.
+    pub fn raw(&self) -> E::Primitive {
+        match *self {
+            EnumCatchAll::Enum(p) => p.to_primitive(),
+            EnumCatchAll::CatchAll(v) => v
+        }
+    }
+
+    /// Is the value one of the variants of the enum?
+    pub fn is_known(&self) -> bool {
+        self.as_enum().is_some()
+    }
 }
 
 impl<E> From<E> for EnumCatchAll<E> where E: PrimitiveEnum {
@@ -56,35 +188,124 @@ impl<E> From<E> for EnumCatchAll<E> where E: PrimitiveEnum {
 
 impl<E> PartialEq<Self> for EnumCatchAll<E> where E: PrimitiveEnum {
     fn eq(&self, other: &Self) -> bool {
-        self.to_primitive() == other.to_primitive()
+        self.raw() == other.raw()
     }
 }
 
+impl<E> Eq for EnumCatchAll<E> where E: PrimitiveEnum, E::Primitive: Eq { }
+
+impl<E> Hash for EnumCatchAll<E> where E: PrimitiveEnum, E::Primitive: Hash {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.raw().hash(state)
+    }
+}
+
+impl<E> PartialOrd<Self> for EnumCatchAll<E> where E: PrimitiveEnum, E::Primitive: PartialOrd {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.raw().partial_cmp(&other.raw())
+    }
+}
+
+impl<E> Ord for EnumCatchAll<E> where E: PrimitiveEnum, E::Primitive: Ord {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.raw().cmp(&other.raw())
+    }
+}
+
+const UNKNOWN_VALUE_PREFIX: &str = "Unknown value: ";
+
 impl<E> PrimitiveEnum for EnumCatchAll<E> 
     where E: PrimitiveEnum
 {
     type Primitive = E::Primitive;
 
     fn from_primitive(val: E::Primitive) -> Option<Self> {
-        match E::from_primitive(val) {
-            Some(p) => Some(EnumCatchAll::Enum(p)),
-            None => Some(EnumCatchAll::CatchAll(val))
-        }
+        Some(EnumCatchAll::from_raw(val))
     }
 
     fn to_primitive(&self) -> E::Primitive {
-        match *self {
-            EnumCatchAll::Enum(p) => p.to_primitive(),
-            EnumCatchAll::CatchAll(v) => v
-        }
+        self.raw()
     }
 
+    /// The name of a variant. The unknown values are parsed by the ```FromStr``` implementation.
     fn from_str(s: &str) -> Option<Self> {
-        E::from_str(s).map(|e| EnumCatchAll::Enum(e))
+        E::from_str(s).map(EnumCatchAll::Enum)
     }
 
     fn from_str_lower(s: &str) -> Option<Self> {
-        E::from_str_lower(s).map(|e| EnumCatchAll::Enum(e))
+        E::from_str_lower(s).map(EnumCatchAll::Enum)
+    }
+}
+
+impl<E> PrimitiveEnumBounds for EnumCatchAll<E>
+    where E: PrimitiveEnumBounds
+{
+    const MIN: E::Primitive = E::MIN;
+    const MAX: E::Primitive = E::MAX;
+    /// Only the known variants, the other values are kept as they are.
+    const MAX_BITS: usize = E::MAX_BITS;
+}
+
+fn parse_catch_all_value<E>(s: &str) -> Option<EnumCatchAll<E>> where E: PrimitiveEnum, E::Primitive: FromStr {
+    let value = match s.get(..UNKNOWN_VALUE_PREFIX.len()) {
+        Some(prefix) if prefix.eq_ignore_ascii_case(UNKNOWN_VALUE_PREFIX) => &s[UNKNOWN_VALUE_PREFIX.len()..],
+        _ => s
+    };
+    value.trim().parse().ok().map(EnumCatchAll::from_raw)
+}
+
+/// Shows the variant, or ```Unknown value: 9``` for the unknown values.
+impl<E> Display for EnumCatchAll<E> where E: PrimitiveEnum + Display {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.as_enum() {
+            Some(ref e) => e.fmt(f),
+            None => write!(f, "{}{:?}", UNKNOWN_VALUE_PREFIX, self.raw())
+        }
+    }
+}
+
+/// The name of a variant, or a value as displayed for the unknown ones or as a plain number.
+impl<E> FromStr for EnumCatchAll<E> where E: PrimitiveEnum, E::Primitive: FromStr {
+    type Err = ParseEnumError;
+
+    fn from_str(s: &str) -> Result<Self, ParseEnumError> {
+        E::from_str(s).map(EnumCatchAll::Enum)
+            .or_else(|| parse_catch_all_value(s))
+            .ok_or(ParseEnumError { enum_name: "EnumCatchAll" })
+    }
+}
+
+/// The known values are serialized like the enum, the unknown ones as their primitive value.
+#[cfg(feature="serde")]
+impl<E> ::serde::Serialize for EnumCatchAll<E>
+    where E: PrimitiveEnumStaticStr, E::Primitive: ::serde::Serialize
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: ::serde::Serializer {
+        match self.as_enum() {
+            Some(ref p) => ::serde_enum::serialize(p, serializer),
+            None => self.raw().serialize(serializer)
+        }
+    }
+}
+
+#[cfg(feature="serde")]
+impl<'de, E> ::serde::Deserialize<'de> for EnumCatchAll<E>
+    where E: PrimitiveEnum, E::Primitive: ::serde::Deserialize<'de> + TryFrom<u64> + TryFrom<i64> + FromStr
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: ::serde::Deserializer<'de> {
+        ::serde_enum::deserialize_with(deserializer, |s| s.parse().ok())
+    }
+}
+
+#[cfg(feature="defmt")]
+impl<E> ::defmt::Format for EnumCatchAll<E>
+    where E: PrimitiveEnum + ::defmt::Format, E::Primitive: ::defmt::Format
+{
+    fn format(&self, f: ::defmt::Formatter) {
+        match self.as_enum() {
+            Some(ref p) => p.format(f),
+            None => ::defmt::write!(f, "Unknown value: {}", self.raw())
+        }
     }
 }
 
@@ -94,9 +315,9 @@ impl<E> PrimitiveEnumDynamicStr for EnumCatchAll<E>
 {
     /// Display value, same as the name of a particular variant.
     fn to_display_str(&self) -> Cow<'static, str> {
-        match *self {
-            EnumCatchAll::Enum(p) => p.to_display_str(),
-            EnumCatchAll::CatchAll(v) => format!("Unknown value: {:?}", v).into()
+        match self.as_enum() {
+            Some(p) => p.to_display_str(),
+            None => format!("{}{:?}", UNKNOWN_VALUE_PREFIX, self.raw()).into()
         }
     }
 
@@ -104,4 +325,18 @@ impl<E> PrimitiveEnumDynamicStr for EnumCatchAll<E>
         let l: Vec<_> = E::all_variants().iter().map(|v| EnumCatchAll::Enum(*v)).collect();
         Cow::from(l)
     }
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_primitive_bits() {
+    assert_eq!(0, primitive_bits(0, 0));
+    assert_eq!(1, primitive_bits(0, 1));
+    assert_eq!(3, primitive_bits(0, 7));
+    assert_eq!(4, primitive_bits(2, 8));
+    assert_eq!(8, primitive_bits(0, 255));
+    assert_eq!(1, primitive_bits(-1, 0));
+    assert_eq!(3, primitive_bits(-4, 3));
+    assert_eq!(4, primitive_bits(-4, 4));
+    assert_eq!(8, primitive_bits(-128, 127));
+    assert_eq!(64, primitive_bits(i64::MIN as i128, i64::MAX as i128));
+}
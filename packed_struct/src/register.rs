@@ -0,0 +1,197 @@
+//! Registers, packed structures that are located at an address of a device.
+//!
+//! A structure becomes a register with the ```address``` attribute. The optional
+//! ```access``` attribute is one of ```rw``` (the default), ```ro``` or ```wo```.
+//! Reading a write-only register or writing a read-only one doesn't compile.
+//!
+//! ```rust
+//! extern crate packed_struct;
+//! #[macro_use] extern crate packed_struct_codegen;
+//!
+//! use packed_struct::prelude::*;
+//! use packed_struct::register::MemoryInterface;
+//!
+//! #[derive(PackedStruct, Debug, PartialEq)]
+//! #[packed_struct(bit_numbering="msb0", address="0xA0", access="rw")]
+//! pub struct Control {
+//!     #[packed_field(bits="0")]
+//!     enabled: bool,
+//!     #[packed_field(bits="1:7")]
+//!     divider: Integer<u8, packed_bits::Bits7>
+//! }
+//!
+//! fn main() {
+//!     let mut bus = MemoryInterface::new();
+//!     bus.write(&Control { enabled: false, divider: 3.into() }).unwrap();
+//!     bus.modify(|c: &mut Control| c.enabled = true).unwrap();
+//!
+//!     assert_eq!(&[0x83], bus.get(0xA0, 1).as_slice());
+//!     assert_eq!(Control { enabled: true, divider: 3.into() }, bus.read().unwrap());
+//! }
+//! ```
+//!
+//! Writing a read-only register fails to compile:
+//!
+//! ```rust,compile_fail
+//! extern crate packed_struct;
+//! #[macro_use] extern crate packed_struct_codegen;
+//!
+//! use packed_struct::prelude::*;
+//! use packed_struct::register::MemoryInterface;
+//!
+//! #[derive(PackedStruct)]
+//! #[packed_struct(address="0x04", access="ro")]
+//! pub struct Status {
+//!     value: u8
+//! }
+//!
+//! fn main() {
+//!     MemoryInterface::new().write(&Status { value: 1 }).unwrap();
+//! }
+//! ```
+//!
+//! As does reading a write-only register:
+//!
+//! ```rust,compile_fail
+//! extern crate packed_struct;
+//! #[macro_use] extern crate packed_struct_codegen;
+//!
+//! use packed_struct::prelude::*;
+//! use packed_struct::register::MemoryInterface;
+//!
+//! #[derive(PackedStruct)]
+//! #[packed_struct(address="0x08", access="wo")]
+//! pub struct Command {
+//!     value: u8
+//! }
+//!
+//! fn main() {
+//!     let _command: Command = MemoryInterface::new().read().unwrap();
+//! }
+//! ```
+
+use internal_prelude::v1::*;
+
+use packing::*;
+
+/// The accesses that a register allows.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum RegisterAccess {
+    ReadOnly,
+    WriteOnly,
+    ReadWrite
+}
+
+/// A packed structure located at an address.
+pub trait Register {
+    /// The packed bytes of the register.
+    type Bytes: AsRef<[u8]> + AsMut<[u8]>;
+    const ADDRESS: u64;
+    const ACCESS: RegisterAccess;
+    /// Zeroed bytes, used as the buffer when reading the register.
+    const ZEROED: Self::Bytes;
+}
+
+/// A register that can be read.
+pub trait ReadableRegister: Register { }
+
+/// A register that can be written.
+pub trait WritableRegister: Register { }
+
+/// A bus, or any other transport, that reads and writes the bytes of the registers.
+pub trait RegisterInterface {
+    type Error: From<PackingError>;
+
+    /// Read the bytes starting at the address.
+    fn read_bytes(&mut self, address: u64, bytes: &mut [u8]) -> Result<(), Self::Error>;
+    /// Write the bytes starting at the address.
+    fn write_bytes(&mut self, address: u64, bytes: &[u8]) -> Result<(), Self::Error>;
+
+    /// Read and unpack a register.
+    fn read<R>(&mut self) -> Result<R, Self::Error> where R: ReadableRegister + PackedStruct<<R as Register>::Bytes> {
+        let mut bytes = R::ZEROED;
+        self.read_bytes(R::ADDRESS, bytes.as_mut())?;
+        Ok(R::unpack(&bytes)?)
+    }
+
+    /// Pack and write a register.
+    fn write<R>(&mut self, register: &R) -> Result<(), Self::Error> where R: WritableRegister + PackedStruct<<R as Register>::Bytes> {
+        let bytes = register.pack();
+        self.write_bytes(R::ADDRESS, bytes.as_ref())
+    }
+
+    /// Read a register, change it and write it back. Returns the written value.
+    fn modify<R, F>(&mut self, f: F) -> Result<R, Self::Error>
+        where R: ReadableRegister + WritableRegister + PackedStruct<<R as Register>::Bytes>, F: FnOnce(&mut R)
+    {
+        let mut register = self.read::<R>()?;
+        f(&mut register);
+        self.write(&register)?;
+        Ok(register)
+    }
+}
+
+impl<I> RegisterInterface for &mut I where I: RegisterInterface {
+    type Error = I::Error;
+
+    fn read_bytes(&mut self, address: u64, bytes: &mut [u8]) -> Result<(), Self::Error> {
+        (**self).read_bytes(address, bytes)
+    }
+
+    fn write_bytes(&mut self, address: u64, bytes: &[u8]) -> Result<(), Self::Error> {
+        (**self).write_bytes(address, bytes)
+    }
+}
+
+#[cfg(any(feature="alloc", feature="std"))]
+/// An access made through the ```MemoryInterface```.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MemoryAccess {
+    Read { address: u64, len: usize },
+    Write { address: u64, bytes: Vec<u8> }
+}
+
+#[cfg(any(feature="alloc", feature="std"))]
+/// An in-memory register interface, for tests. The bytes that were never written
+/// read as zeroes. Every access is recorded.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryInterface {
+    memory: BTreeMap<u64, u8>,
+    pub accesses: Vec<MemoryAccess>
+}
+
+#[cfg(any(feature="alloc", feature="std"))]
+impl MemoryInterface {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Set the memory contents, without recording an access.
+    pub fn set(&mut self, address: u64, bytes: &[u8]) {
+        for (i, b) in bytes.iter().enumerate() {
+            self.memory.insert(address + i as u64, *b);
+        }
+    }
+
+    /// Get the memory contents, without recording an access.
+    pub fn get(&self, address: u64, len: usize) -> Vec<u8> {
+        (0..len as u64).map(|i| self.memory.get(&(address + i)).cloned().unwrap_or(0)).collect()
+    }
+}
+
+#[cfg(any(feature="alloc", feature="std"))]
+impl RegisterInterface for MemoryInterface {
+    type Error = PackingError;
+
+    fn read_bytes(&mut self, address: u64, bytes: &mut [u8]) -> Result<(), PackingError> {
+        bytes.copy_from_slice(&self.get(address, bytes.len()));
+        self.accesses.push(MemoryAccess::Read { address, len: bytes.len() });
+        Ok(())
+    }
+
+    fn write_bytes(&mut self, address: u64, bytes: &[u8]) -> Result<(), PackingError> {
+        self.set(address, bytes);
+        self.accesses.push(MemoryAccess::Write { address, bytes: bytes.to_vec() });
+        Ok(())
+    }
+}
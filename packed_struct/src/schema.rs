@@ -0,0 +1,280 @@
+//! Static descriptions of the packed structures and primitive enums, as emitted
+//! by the derive macros. Useful for generic tools like register browsers, loggers
+//! or test harnesses that shouldn't require per-structure code.
+
+use internal_prelude::v1::*;
+
+use primitive_enum::{PrimitiveEnum, EnumCatchAll};
+use types_str::StringPadding;
+
+/// An inclusive range of bits, `start` always being the lower bit number.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BitRange {
+    pub start: usize,
+    pub end: usize
+}
+
+impl BitRange {
+    /// Number of bits in this range.
+    pub fn bits(&self) -> usize {
+        self.end - self.start + 1
+    }
+}
+
+/// Byte order of a packed integer field.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum IntegerEndianness {
+    Msb,
+    Lsb
+}
+
+/// How the value of a field is represented.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum PackedFieldKind {
+    /// A boolean, packed as one of two bit patterns. In the ```nonzero``` mode, anything but the
+    /// false pattern unpacks as true.
+    Bool { true_bits: u64, false_bits: u64, nonzero: bool },
+    Integer { signed: bool },
+    Enum,
+    /// Reserved bits, all of them either zeroes or ones.
+    Reserved { ones: bool },
+    /// A text, with the padding of its unused bytes.
+    Str { padding: StringPadding },
+    /// A nested packed structure.
+    Struct
+}
+
+/// Description of a single packed field. Arrays are described with one field per element.
+#[derive(Debug)]
+pub struct PackedFieldSchema {
+    /// Name of the field, or of the array element, like `palette[3][1]`.
+    pub name: &'static str,
+    /// The Rust type of the field.
+    pub type_name: &'static str,
+    /// Position of the field, with the most significant bit of the first byte as bit 0.
+    pub bits_msb0: BitRange,
+    /// Position of the field, with the least significant bit of the last byte as bit 0.
+    pub bits_lsb0: BitRange,
+    pub kind: PackedFieldKind,
+    /// Only set for integers that span more than a single byte.
+    pub endianness: Option<IntegerEndianness>,
+    /// The variants of an enum field.
+    pub enum_schema: Option<&'static PrimitiveEnumSchema>,
+    pub reserved: bool,
+    /// The doc comment of the field, an empty string if there isn't one.
+    pub doc: &'static str,
+    /// The schema of a nested structure.
+    pub nested: Option<fn() -> &'static PackedStructSchema>
+}
+
+impl PackedFieldSchema {
+    /// Number of bits that this field occupies.
+    pub fn bits(&self) -> usize {
+        self.bits_msb0.bits()
+    }
+}
+
+/// Description of a packed structure.
+#[derive(Debug)]
+pub struct PackedStructSchema {
+    pub name: &'static str,
+    /// The doc comment of the structure, an empty string if there isn't one.
+    pub doc: &'static str,
+    pub num_bytes: usize,
+    pub num_bits: usize,
+    pub fields: &'static [PackedFieldSchema]
+}
+
+impl PackedStructSchema {
+    /// Find a field or an array element by its name.
+    pub fn field(&self, name: &str) -> Option<&'static PackedFieldSchema> {
+        self.fields.iter().find(|f| f.name == name)
+    }
+}
+
+/// A single variant of a primitive enum.
+#[derive(Debug)]
+pub struct PrimitiveEnumVariantSchema {
+    pub name: &'static str,
+    pub value: i128,
+    pub doc: &'static str
+}
+
+/// Description of a primitive enum.
+#[derive(Debug)]
+pub struct PrimitiveEnumSchema {
+    pub name: &'static str,
+    /// The primitive type, like `u8`.
+    pub primitive: &'static str,
+    pub variants: &'static [PrimitiveEnumVariantSchema]
+}
+
+impl PrimitiveEnumSchema {
+    /// Find a variant by its value.
+    pub fn variant_by_value(&self, value: i128) -> Option<&'static PrimitiveEnumVariantSchema> {
+        self.variants.iter().find(|v| v.value == value)
+    }
+
+    /// Find a variant by its name. Case sensitive.
+    pub fn variant_by_name(&self, name: &str) -> Option<&'static PrimitiveEnumVariantSchema> {
+        self.variants.iter().find(|v| v.name == name)
+    }
+}
+
+/// A packed structure that can describe its layout.
+pub trait PackedStructSchemaInfo {
+    fn packed_struct_schema() -> &'static PackedStructSchema;
+}
+
+/// A primitive enum that can describe its variants.
+pub trait PrimitiveEnumSchemaInfo {
+    const SCHEMA: &'static PrimitiveEnumSchema;
+}
+
+impl<E> PrimitiveEnumSchemaInfo for EnumCatchAll<E> where E: PrimitiveEnum + PrimitiveEnumSchemaInfo {
+    const SCHEMA: &'static PrimitiveEnumSchema = E::SCHEMA;
+}
+
+impl Display for BitRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.start == self.end {
+            write!(f, "{}", self.start)
+        } else {
+            write!(f, "{}:{}", self.start, self.end)
+        }
+    }
+}
+
+/// The value of a single field, for access by the name of the field.
+pub enum FieldValue<'a> {
+    Bool(bool),
+    Unsigned(u64),
+    Signed(i64),
+    /// An enum value. The name is `None` for values without a matching variant.
+    /// When setting a field, the variant is selected by the name if it is present.
+    Enum { name: Option<&'a str>, value: i128 },
+    Str(&'a str),
+    /// A nested structure. Its fields are set with a dotted name, like `status.ready`.
+    Struct(&'a dyn PackedStructFieldAccess)
+}
+
+impl<'a> FieldValue<'a> {
+    /// The value as an integer, for the integer and enum values.
+    pub fn to_integer(&self) -> Option<i128> {
+        match *self {
+            FieldValue::Unsigned(v) => Some(v as i128),
+            FieldValue::Signed(v) => Some(v as i128),
+            FieldValue::Enum { value, .. } => Some(value),
+            _ => None
+        }
+    }
+}
+
+impl<'a> Debug for FieldValue<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FieldValue::Bool(v) => write!(f, "Bool({:?})", v),
+            FieldValue::Unsigned(v) => write!(f, "Unsigned({:?})", v),
+            FieldValue::Signed(v) => write!(f, "Signed({:?})", v),
+            FieldValue::Enum { name, value } => write!(f, "Enum {{ name: {:?}, value: {:?} }}", name, value),
+            FieldValue::Str(s) => write!(f, "Str({:?})", s),
+            FieldValue::Struct(_) => write!(f, "Struct(..)")
+        }
+    }
+}
+
+impl<'a> Display for FieldValue<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FieldValue::Bool(v) => write!(f, "{}", v),
+            FieldValue::Unsigned(v) => write!(f, "{}", v),
+            FieldValue::Signed(v) => write!(f, "{}", v),
+            FieldValue::Enum { name: Some(name), .. } => write!(f, "{}", name),
+            FieldValue::Enum { name: None, value } => write!(f, "{}", value),
+            FieldValue::Str(s) => write!(f, "{}", s),
+            FieldValue::Struct(_) => write!(f, "{{..}}")
+        }
+    }
+}
+
+impl<'a> PartialEq for FieldValue<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (&FieldValue::Bool(a), &FieldValue::Bool(b)) => a == b,
+            (&FieldValue::Unsigned(a), &FieldValue::Unsigned(b)) => a == b,
+            (&FieldValue::Signed(a), &FieldValue::Signed(b)) => a == b,
+            (&FieldValue::Enum { name: a_name, value: a }, &FieldValue::Enum { name: b_name, value: b }) => a_name == b_name && a == b,
+            (&FieldValue::Str(a), &FieldValue::Str(b)) => a == b,
+            (&FieldValue::Struct(a), &FieldValue::Struct(b)) => a as *const _ as *const u8 == b as *const _ as *const u8,
+            _ => false
+        }
+    }
+}
+
+/// Errors of setting a field by its name
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum FieldAccessError {
+    /// There is no such field, or it is reserved
+    UnknownField,
+    /// The value doesn't fit in the field's bits or doesn't match its type
+    InvalidValue,
+    /// The text is longer than the field
+    TooLong
+}
+
+impl Display for FieldAccessError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[cfg(feature="std")]
+impl ::std::error::Error for FieldAccessError {
+    fn description(&self) -> &str {
+        match *self {
+            FieldAccessError::UnknownField => "Unknown field",
+            FieldAccessError::InvalidValue => "Invalid value",
+            FieldAccessError::TooLong => "Value too long"
+        }
+    }
+}
+
+/// Runtime access to the fields of a packed structure, by their names as listed in the schema.
+/// Fields of nested structures are accessed with a dotted name, like `status.ready`.
+/// Reserved fields can't be accessed. Generated for the structures with
+/// ```#[packed_struct(schema)]```.
+pub trait PackedStructFieldAccess {
+    /// The current value of a field, `None` if there is no such field.
+    fn get_field<'a>(&'a self, name: &str) -> Option<FieldValue<'a>>;
+    /// Set a field. Values that don't fit in the field's bits or that don't match its
+    /// type are rejected with `FieldAccessError::InvalidValue`.
+    fn set_field(&mut self, name: &str, value: FieldValue) -> Result<(), FieldAccessError>;
+}
+
+#[doc(hidden)]
+/// Checks if the integer can be represented with the number of bits.
+pub fn integer_fits(value: i128, bits: usize, signed: bool) -> bool {
+    if bits >= 64 {
+        if signed {
+            value >= i64::MIN as i128 && value <= i64::MAX as i128
+        } else {
+            value >= 0 && value <= u64::MAX as i128
+        }
+    } else if signed {
+        let max = (1i128 << (bits - 1)) - 1;
+        value >= -max - 1 && value <= max
+    } else {
+        value >= 0 && value < (1i128 << bits)
+    }
+}
+
+#[test]
+fn test_integer_fits() {
+    assert!(integer_fits(15, 4, false));
+    assert!(!integer_fits(16, 4, false));
+    assert!(!integer_fits(-1, 4, false));
+    assert!(integer_fits(-8, 4, true));
+    assert!(!integer_fits(8, 4, true));
+    assert!(integer_fits(u64::MAX as i128, 64, false));
+    assert!(!integer_fits(u64::MAX as i128, 64, true));
+}
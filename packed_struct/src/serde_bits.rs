@@ -0,0 +1,836 @@
+//! A serde data format that packs the values bit by bit, for the types that derive
+//! ```Serialize``` and ```Deserialize``` instead of ```PackedStruct```.
+//!
+//! Every value takes the number of bits of its field in ```BitWidths```, or the size of its
+//! type when the field doesn't have a width. The values are written one after another,
+//! the most significant bit first, like a ```msb0``` structure with ```msb``` integers, and
+//! the last byte is padded with zeroes. Signed integers are stored in two's complement.
+//!
+//! * ```bool``` takes a single bit, ```char``` 32 bits and the floats their own size
+//! * ```Option``` takes a bit that is set when there is a value, followed by the value
+//! * Unit variants are stored as their index, 8 bits unless the field has a width
+//! * Arrays and tuples are stored element by element, every element with the width of the field
+//! * Texts, byte arrays, ```Vec```s and maps are preceded by their length, 16 bits by default
+//!
+//! The field names of nested structures are joined with a dot, like ```status.level```, the
+//! same as in the schema of a packed structure. ```BitWidths::from_schema``` takes only the widths
+//! of the fields of a packed structure. The values are still written one after another, in the
+//! order of the serialized type, so the bytes match the packed structure only when its fields
+//! are contiguous and in the same order, ```msb0``` with ```msb``` integers and without
+//! reserved fields. The positions, the reserved bits and the ```lsb``` endianness of the
+//! packed structure aren't reproduced.
+//!
+//! ```rust
+//! extern crate packed_struct;
+//! #[macro_use] extern crate serde_derive;
+//!
+//! use packed_struct::serde_bits::*;
+//!
+//! #[derive(Serialize, Deserialize, Debug, PartialEq)]
+//! struct Reading {
+//!     valid: bool,
+//!     channel: u8,
+//!     value: i16
+//! }
+//!
+//! fn main() {
+//!     let widths = BitWidths::new().field("channel", 3).field("value", 12);
+//!     let reading = Reading { valid: true, channel: 5, value: -2 };
+//!
+//!     let bytes = to_bytes(&reading, &widths).unwrap();
+//!     assert_eq!(vec![0b1101_1111, 0b1111_1110], bytes);
+//!     assert_eq!(reading, from_bytes::<Reading>(&bytes, &widths).unwrap());
+//! }
+//! ```
+
+use internal_prelude::v1::*;
+
+use serde::ser::{self, Serialize};
+use serde::de::{self, Deserialize, DeserializeSeed, IntoDeserializer, Visitor};
+
+use schema::{PackedStructSchema, PackedFieldKind};
+
+/// Errors of the bit-packed serde format.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BitsError {
+    /// The value doesn't fit into the bits of its field.
+    ValueTooWide { field: String, bits: usize },
+    /// The bytes ended before the value.
+    UnexpectedEnd,
+    /// Whole bytes were left after the value.
+    TrailingBytes,
+    InvalidUtf8,
+    InvalidChar,
+    /// The format can only decode the types that are known in advance. Sequences need
+    /// their length up front.
+    NotSupported(&'static str),
+    Custom(String)
+}
+
+impl Display for BitsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BitsError::ValueTooWide { ref field, bits } => write!(f, "The value of {} doesn't fit into {} bits", field, bits),
+            BitsError::UnexpectedEnd => write!(f, "Unexpected end of the input"),
+            BitsError::TrailingBytes => write!(f, "Trailing bytes after the value"),
+            BitsError::InvalidUtf8 => write!(f, "Invalid UTF-8"),
+            BitsError::InvalidChar => write!(f, "Invalid character"),
+            BitsError::NotSupported(what) => write!(f, "Not supported: {}", what),
+            BitsError::Custom(ref msg) => f.write_str(msg)
+        }
+    }
+}
+
+impl ::serde::de::StdError for BitsError { }
+
+impl ser::Error for BitsError {
+    fn custom<T: Display>(msg: T) -> Self {
+        BitsError::Custom(format!("{}", msg))
+    }
+}
+
+impl de::Error for BitsError {
+    fn custom<T: Display>(msg: T) -> Self {
+        BitsError::Custom(format!("{}", msg))
+    }
+}
+
+/// The number of bits of the fields.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BitWidths {
+    fields: Vec<(String, usize)>,
+    length_bits: usize,
+    variant_bits: usize
+}
+
+impl Default for BitWidths {
+    fn default() -> Self {
+        BitWidths {
+            fields: Vec::new(),
+            length_bits: 16,
+            variant_bits: 8
+        }
+    }
+}
+
+impl BitWidths {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The widths of the fields of a packed structure. The array elements share the width
+    /// of their field and the reserved fields are left out, as they aren't serialized.
+    /// The bit positions and the endianness of the fields are ignored.
+    pub fn from_schema(schema: &PackedStructSchema) -> Self {
+        let mut widths = Self::new();
+        widths.add_schema(schema, "");
+        widths
+    }
+
+    fn add_schema(&mut self, schema: &PackedStructSchema, prefix: &str) {
+        for field in schema.fields {
+            let name = field.name.split('[').next().unwrap_or(field.name);
+            let name = format!("{}{}", prefix, name);
+
+            match (field.kind, field.nested) {
+                _ if field.reserved => (),
+                (PackedFieldKind::Struct, Some(nested)) => self.add_schema(nested(), &format!("{}.", name)),
+                _ => {
+                    if self.get(&name).is_none() {
+                        self.fields.push((name, field.bits()));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Sets the number of bits of a field.
+    pub fn field(mut self, name: &str, bits: usize) -> Self {
+        self.fields.retain(|f| f.0 != name);
+        self.fields.push((name.into(), bits));
+        self
+    }
+
+    /// Sets the number of bits of the lengths of the texts, sequences and maps.
+    pub fn length_bits(mut self, bits: usize) -> Self {
+        self.length_bits = bits;
+        self
+    }
+
+    /// Sets the number of bits of the enum variants, for the fields without a width.
+    pub fn variant_bits(mut self, bits: usize) -> Self {
+        self.variant_bits = bits;
+        self
+    }
+
+    /// The number of bits of a field.
+    pub fn get(&self, name: &str) -> Option<usize> {
+        self.fields.iter().find(|f| f.0 == name).map(|f| f.1)
+    }
+}
+
+/// Packs the value.
+pub fn to_bytes<T>(value: &T, widths: &BitWidths) -> Result<Vec<u8>, BitsError> where T: Serialize + ?Sized {
+    let mut serializer = BitSerializer {
+        bytes: Vec::new(),
+        bits: 0,
+        widths,
+        path: Vec::new()
+    };
+    value.serialize(&mut serializer)?;
+    Ok(serializer.bytes)
+}
+
+/// Unpacks a value. The bytes have to end with the value, apart from the padding of the
+/// last byte.
+pub fn from_bytes<'de, T>(bytes: &'de [u8], widths: &BitWidths) -> Result<T, BitsError> where T: Deserialize<'de> {
+    let mut deserializer = BitDeserializer {
+        bytes,
+        bits: 0,
+        widths,
+        path: Vec::new()
+    };
+    let value = T::deserialize(&mut deserializer)?;
+    if (deserializer.bits + 7) / 8 < bytes.len() {
+        return Err(BitsError::TrailingBytes);
+    }
+    Ok(value)
+}
+
+fn mask(bits: usize) -> u128 {
+    if bits >= 128 { u128::MAX } else { (1 << bits) - 1 }
+}
+
+fn field_name(path: &[&'static str]) -> String {
+    path.join(".")
+}
+
+/// Writes the values into bytes.
+pub struct BitSerializer<'w> {
+    bytes: Vec<u8>,
+    bits: usize,
+    widths: &'w BitWidths,
+    path: Vec<&'static str>
+}
+
+impl<'w> BitSerializer<'w> {
+    fn width(&self, default_bits: usize) -> usize {
+        self.widths.get(&field_name(&self.path)).unwrap_or(default_bits)
+    }
+
+    fn write_bits(&mut self, value: u128, bits: usize) {
+        for i in (0..bits).rev() {
+            if self.bits % 8 == 0 {
+                self.bytes.push(0);
+            }
+            if (value >> i) & 1 == 1 {
+                let last = self.bytes.len() - 1;
+                self.bytes[last] |= 0x80 >> (self.bits % 8);
+            }
+            self.bits += 1;
+        }
+    }
+
+    fn write_unsigned(&mut self, value: u128, native_bits: usize) -> Result<(), BitsError> {
+        let bits = self.width(native_bits);
+        if value & !mask(bits) != 0 {
+            return Err(BitsError::ValueTooWide { field: field_name(&self.path), bits });
+        }
+        self.write_bits(value, bits);
+        Ok(())
+    }
+
+    fn write_signed(&mut self, value: i128, native_bits: usize) -> Result<(), BitsError> {
+        let bits = self.width(native_bits);
+        let fits = match bits {
+            0 => value == 0,
+            1..=127 => value >= -(1 << (bits - 1)) && value < (1 << (bits - 1)),
+            _ => true
+        };
+        if !fits {
+            return Err(BitsError::ValueTooWide { field: field_name(&self.path), bits });
+        }
+        self.write_bits(value as u128 & mask(bits), bits);
+        Ok(())
+    }
+
+    fn write_length(&mut self, len: usize) -> Result<(), BitsError> {
+        let bits = self.widths.length_bits;
+        if len as u128 & !mask(bits) != 0 {
+            return Err(BitsError::ValueTooWide { field: format!("the length of {}", field_name(&self.path)), bits });
+        }
+        self.write_bits(len as u128, bits);
+        Ok(())
+    }
+
+    fn write_variant(&mut self, index: u32) -> Result<(), BitsError> {
+        let bits = self.width(self.widths.variant_bits);
+        if index as u128 & !mask(bits) != 0 {
+            return Err(BitsError::ValueTooWide { field: field_name(&self.path), bits });
+        }
+        self.write_bits(index as u128, bits);
+        Ok(())
+    }
+}
+
+impl<'a, 'w> ser::Serializer for &'a mut BitSerializer<'w> {
+    type Ok = ();
+    type Error = BitsError;
+
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    fn serialize_bool(self, v: bool) -> Result<(), BitsError> {
+        self.write_unsigned(v as u128, 1)
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), BitsError> {
+        self.write_signed(v as i128, 8)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), BitsError> {
+        self.write_signed(v as i128, 16)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), BitsError> {
+        self.write_signed(v as i128, 32)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), BitsError> {
+        self.write_signed(v as i128, 64)
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<(), BitsError> {
+        self.write_signed(v, 128)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), BitsError> {
+        self.write_unsigned(v as u128, 8)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), BitsError> {
+        self.write_unsigned(v as u128, 16)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), BitsError> {
+        self.write_unsigned(v as u128, 32)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), BitsError> {
+        self.write_unsigned(v as u128, 64)
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<(), BitsError> {
+        self.write_unsigned(v, 128)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), BitsError> {
+        self.write_bits(v.to_bits() as u128, 32);
+        Ok(())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), BitsError> {
+        self.write_bits(v.to_bits() as u128, 64);
+        Ok(())
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), BitsError> {
+        self.write_unsigned(v as u128, 32)
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), BitsError> {
+        self.serialize_bytes(v.as_bytes())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), BitsError> {
+        self.write_length(v.len())?;
+        for &b in v {
+            self.write_bits(b as u128, 8);
+        }
+        Ok(())
+    }
+
+    fn serialize_none(self) -> Result<(), BitsError> {
+        self.write_bits(0, 1);
+        Ok(())
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<(), BitsError> where T: Serialize + ?Sized {
+        self.write_bits(1, 1);
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), BitsError> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), BitsError> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(self, _name: &'static str, variant_index: u32, _variant: &'static str) -> Result<(), BitsError> {
+        self.write_variant(variant_index)
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<(), BitsError> where T: Serialize + ?Sized {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(self, _name: &'static str, variant_index: u32, _variant: &'static str, value: &T) -> Result<(), BitsError>
+        where T: Serialize + ?Sized
+    {
+        self.write_variant(variant_index)?;
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self, BitsError> {
+        let len = len.ok_or(BitsError::NotSupported("sequences without a length"))?;
+        self.write_length(len)?;
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self, BitsError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self, BitsError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(self, _name: &'static str, variant_index: u32, _variant: &'static str, _len: usize) -> Result<Self, BitsError> {
+        self.write_variant(variant_index)?;
+        Ok(self)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self, BitsError> {
+        let len = len.ok_or(BitsError::NotSupported("maps without a length"))?;
+        self.write_length(len)?;
+        Ok(self)
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self, BitsError> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(self, _name: &'static str, variant_index: u32, _variant: &'static str, _len: usize) -> Result<Self, BitsError> {
+        self.write_variant(variant_index)?;
+        Ok(self)
+    }
+}
+
+impl<'a, 'w> ser::SerializeSeq for &'a mut BitSerializer<'w> {
+    type Ok = ();
+    type Error = BitsError;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), BitsError> where T: Serialize + ?Sized {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), BitsError> {
+        Ok(())
+    }
+}
+
+impl<'a, 'w> ser::SerializeTuple for &'a mut BitSerializer<'w> {
+    type Ok = ();
+    type Error = BitsError;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), BitsError> where T: Serialize + ?Sized {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), BitsError> {
+        Ok(())
+    }
+}
+
+impl<'a, 'w> ser::SerializeTupleStruct for &'a mut BitSerializer<'w> {
+    type Ok = ();
+    type Error = BitsError;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), BitsError> where T: Serialize + ?Sized {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), BitsError> {
+        Ok(())
+    }
+}
+
+impl<'a, 'w> ser::SerializeTupleVariant for &'a mut BitSerializer<'w> {
+    type Ok = ();
+    type Error = BitsError;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), BitsError> where T: Serialize + ?Sized {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), BitsError> {
+        Ok(())
+    }
+}
+
+impl<'a, 'w> ser::SerializeMap for &'a mut BitSerializer<'w> {
+    type Ok = ();
+    type Error = BitsError;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), BitsError> where T: Serialize + ?Sized {
+        key.serialize(&mut **self)
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), BitsError> where T: Serialize + ?Sized {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), BitsError> {
+        Ok(())
+    }
+}
+
+impl<'a, 'w> ser::SerializeStruct for &'a mut BitSerializer<'w> {
+    type Ok = ();
+    type Error = BitsError;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), BitsError> where T: Serialize + ?Sized {
+        self.path.push(key);
+        let r = value.serialize(&mut **self);
+        self.path.pop();
+        r
+    }
+
+    fn end(self) -> Result<(), BitsError> {
+        Ok(())
+    }
+}
+
+impl<'a, 'w> ser::SerializeStructVariant for &'a mut BitSerializer<'w> {
+    type Ok = ();
+    type Error = BitsError;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), BitsError> where T: Serialize + ?Sized {
+        self.path.push(key);
+        let r = value.serialize(&mut **self);
+        self.path.pop();
+        r
+    }
+
+    fn end(self) -> Result<(), BitsError> {
+        Ok(())
+    }
+}
+
+/// Reads the values from bytes.
+pub struct BitDeserializer<'de, 'w> {
+    bytes: &'de [u8],
+    bits: usize,
+    widths: &'w BitWidths,
+    path: Vec<&'static str>
+}
+
+impl<'de, 'w> BitDeserializer<'de, 'w> {
+    fn width(&self, default_bits: usize) -> usize {
+        self.widths.get(&field_name(&self.path)).unwrap_or(default_bits)
+    }
+
+    fn read_bits(&mut self, bits: usize) -> Result<u128, BitsError> {
+        if self.bits + bits > self.bytes.len() * 8 {
+            return Err(BitsError::UnexpectedEnd);
+        }
+        let start = self.bits;
+        self.bits += bits;
+        Ok((start..start + bits).fold(0, |value, i| (value << 1) | ((self.bytes[i / 8] >> (7 - i % 8)) & 1) as u128))
+    }
+
+    fn read_unsigned<T>(&mut self, native_bits: usize) -> Result<T, BitsError> where T: TryFrom<u128> {
+        let bits = self.width(native_bits);
+        let value = self.read_bits(bits)?;
+        T::try_from(value).map_err(|_| BitsError::ValueTooWide { field: field_name(&self.path), bits: native_bits })
+    }
+
+    fn read_signed<T>(&mut self, native_bits: usize) -> Result<T, BitsError> where T: TryFrom<i128> {
+        let bits = self.width(native_bits);
+        let value = self.read_bits(bits)?;
+        let value = if bits > 0 && bits < 128 && (value >> (bits - 1)) & 1 == 1 {
+            (value | !mask(bits)) as i128
+        } else {
+            value as i128
+        };
+        T::try_from(value).map_err(|_| BitsError::ValueTooWide { field: field_name(&self.path), bits: native_bits })
+    }
+
+    fn read_length(&mut self) -> Result<usize, BitsError> {
+        let bits = self.widths.length_bits;
+        Ok(self.read_bits(bits)? as usize)
+    }
+
+    fn read_variant(&mut self) -> Result<u32, BitsError> {
+        let bits = self.width(self.widths.variant_bits);
+        let index = self.read_bits(bits)?;
+        u32::try_from(index).map_err(|_| BitsError::ValueTooWide { field: field_name(&self.path), bits: 32 })
+    }
+
+    fn read_bytes(&mut self) -> Result<Vec<u8>, BitsError> {
+        let len = self.read_length()?;
+        if self.bits + len * 8 > self.bytes.len() * 8 {
+            return Err(BitsError::UnexpectedEnd);
+        }
+        (0..len).map(|_| self.read_bits(8).map(|b| b as u8)).collect()
+    }
+}
+
+impl<'a, 'de, 'w> de::Deserializer<'de> for &'a mut BitDeserializer<'de, 'w> {
+    type Error = BitsError;
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value, BitsError> where V: Visitor<'de> {
+        Err(BitsError::NotSupported("values of an unknown type"))
+    }
+
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, BitsError> where V: Visitor<'de> {
+        let v: u8 = self.read_unsigned(1)?;
+        visitor.visit_bool(v != 0)
+    }
+
+    fn deserialize_i8<V>(self, visitor: V) -> Result<V::Value, BitsError> where V: Visitor<'de> {
+        visitor.visit_i8(self.read_signed(8)?)
+    }
+
+    fn deserialize_i16<V>(self, visitor: V) -> Result<V::Value, BitsError> where V: Visitor<'de> {
+        visitor.visit_i16(self.read_signed(16)?)
+    }
+
+    fn deserialize_i32<V>(self, visitor: V) -> Result<V::Value, BitsError> where V: Visitor<'de> {
+        visitor.visit_i32(self.read_signed(32)?)
+    }
+
+    fn deserialize_i64<V>(self, visitor: V) -> Result<V::Value, BitsError> where V: Visitor<'de> {
+        visitor.visit_i64(self.read_signed(64)?)
+    }
+
+    fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value, BitsError> where V: Visitor<'de> {
+        visitor.visit_i128(self.read_signed(128)?)
+    }
+
+    fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value, BitsError> where V: Visitor<'de> {
+        visitor.visit_u8(self.read_unsigned(8)?)
+    }
+
+    fn deserialize_u16<V>(self, visitor: V) -> Result<V::Value, BitsError> where V: Visitor<'de> {
+        visitor.visit_u16(self.read_unsigned(16)?)
+    }
+
+    fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value, BitsError> where V: Visitor<'de> {
+        visitor.visit_u32(self.read_unsigned(32)?)
+    }
+
+    fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value, BitsError> where V: Visitor<'de> {
+        visitor.visit_u64(self.read_unsigned(64)?)
+    }
+
+    fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value, BitsError> where V: Visitor<'de> {
+        visitor.visit_u128(self.read_unsigned(128)?)
+    }
+
+    fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value, BitsError> where V: Visitor<'de> {
+        let bits = self.read_bits(32)? as u32;
+        visitor.visit_f32(f32::from_bits(bits))
+    }
+
+    fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value, BitsError> where V: Visitor<'de> {
+        let bits = self.read_bits(64)? as u64;
+        visitor.visit_f64(f64::from_bits(bits))
+    }
+
+    fn deserialize_char<V>(self, visitor: V) -> Result<V::Value, BitsError> where V: Visitor<'de> {
+        let v: u32 = self.read_unsigned(32)?;
+        visitor.visit_char(char::from_u32(v).ok_or(BitsError::InvalidChar)?)
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, BitsError> where V: Visitor<'de> {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, BitsError> where V: Visitor<'de> {
+        let bytes = self.read_bytes()?;
+        visitor.visit_string(String::from_utf8(bytes).map_err(|_| BitsError::InvalidUtf8)?)
+    }
+
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, BitsError> where V: Visitor<'de> {
+        self.deserialize_byte_buf(visitor)
+    }
+
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, BitsError> where V: Visitor<'de> {
+        visitor.visit_byte_buf(self.read_bytes()?)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, BitsError> where V: Visitor<'de> {
+        if self.read_bits(1)? == 1 {
+            visitor.visit_some(self)
+        } else {
+            visitor.visit_none()
+        }
+    }
+
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, BitsError> where V: Visitor<'de> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value, BitsError> where V: Visitor<'de> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value, BitsError> where V: Visitor<'de> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, BitsError> where V: Visitor<'de> {
+        let len = self.read_length()?;
+        visitor.visit_seq(Elements { de: self, remaining: len })
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, BitsError> where V: Visitor<'de> {
+        visitor.visit_seq(Elements { de: self, remaining: len })
+    }
+
+    fn deserialize_tuple_struct<V>(self, _name: &'static str, len: usize, visitor: V) -> Result<V::Value, BitsError> where V: Visitor<'de> {
+        visitor.visit_seq(Elements { de: self, remaining: len })
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, BitsError> where V: Visitor<'de> {
+        let len = self.read_length()?;
+        visitor.visit_map(Elements { de: self, remaining: len })
+    }
+
+    fn deserialize_struct<V>(self, _name: &'static str, fields: &'static [&'static str], visitor: V) -> Result<V::Value, BitsError>
+        where V: Visitor<'de>
+    {
+        visitor.visit_seq(Fields { de: self, fields })
+    }
+
+    fn deserialize_enum<V>(self, _name: &'static str, _variants: &'static [&'static str], visitor: V) -> Result<V::Value, BitsError>
+        where V: Visitor<'de>
+    {
+        visitor.visit_enum(self)
+    }
+
+    fn deserialize_identifier<V>(self, _visitor: V) -> Result<V::Value, BitsError> where V: Visitor<'de> {
+        Err(BitsError::NotSupported("identifiers"))
+    }
+
+    /// The skipped values are serialized as units, like the reserved fields.
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, BitsError> where V: Visitor<'de> {
+        visitor.visit_unit()
+    }
+}
+
+/// The elements of a sequence, tuple or map.
+struct Elements<'a, 'de: 'a, 'w: 'a> {
+    de: &'a mut BitDeserializer<'de, 'w>,
+    remaining: usize
+}
+
+impl<'a, 'de, 'w> de::SeqAccess<'de> for Elements<'a, 'de, 'w> {
+    type Error = BitsError;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, BitsError> where T: DeserializeSeed<'de> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+impl<'a, 'de, 'w> de::MapAccess<'de> for Elements<'a, 'de, 'w> {
+    type Error = BitsError;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, BitsError> where K: DeserializeSeed<'de> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, BitsError> where V: DeserializeSeed<'de> {
+        seed.deserialize(&mut *self.de)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+/// The fields of a structure, in their order.
+struct Fields<'a, 'de: 'a, 'w: 'a> {
+    de: &'a mut BitDeserializer<'de, 'w>,
+    fields: &'static [&'static str]
+}
+
+impl<'a, 'de, 'w> de::SeqAccess<'de> for Fields<'a, 'de, 'w> {
+    type Error = BitsError;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, BitsError> where T: DeserializeSeed<'de> {
+        let (field, rest) = match self.fields.split_first() {
+            Some(f) => f,
+            None => return Ok(None)
+        };
+        self.fields = rest;
+
+        self.de.path.push(field);
+        let r = seed.deserialize(&mut *self.de);
+        self.de.path.pop();
+        r.map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.fields.len())
+    }
+}
+
+impl<'a, 'de, 'w> de::EnumAccess<'de> for &'a mut BitDeserializer<'de, 'w> {
+    type Error = BitsError;
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self), BitsError> where V: DeserializeSeed<'de> {
+        let index = self.read_variant()?;
+        let value = seed.deserialize(IntoDeserializer::<BitsError>::into_deserializer(index))?;
+        Ok((value, self))
+    }
+}
+
+impl<'a, 'de, 'w> de::VariantAccess<'de> for &'a mut BitDeserializer<'de, 'w> {
+    type Error = BitsError;
+
+    fn unit_variant(self) -> Result<(), BitsError> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, BitsError> where T: DeserializeSeed<'de> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value, BitsError> where V: Visitor<'de> {
+        visitor.visit_seq(Elements { de: self, remaining: len })
+    }
+
+    fn struct_variant<V>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value, BitsError> where V: Visitor<'de> {
+        visitor.visit_seq(Fields { de: self, fields })
+    }
+}
@@ -0,0 +1,83 @@
+//! Serde support for the primitive enums. The human readable formats, like JSON or TOML,
+//! store the name of the variant and also accept the primitive value when loading. The
+//! other formats store the primitive value.
+//!
+//! With the ```serde``` feature of the code generator, the primitive enums with
+//! ```#[primitive(serde)]``` and the packed structures with ```#[packed_struct(serde)]```
+//! implement ```Serialize``` and ```Deserialize``` on their own. The structures skip their
+//! reserved fields. For the types that derive their serde implementations, the enum fields
+//! can use this module:
+//!
+//! ```rust,ignore
+//! #[derive(Serialize, Deserialize)]
+//! pub struct Config {
+//!     #[serde(with = "packed_struct::serde_enum")]
+//!     mode: Mode
+//! }
+//! ```
+
+use internal_prelude::v1::*;
+
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde::de::{self, Visitor, Unexpected};
+
+use primitive_enum::*;
+
+/// Serializes the name of the variant, or the primitive value for the binary formats.
+pub fn serialize<E, S>(value: &E, serializer: S) -> Result<S::Ok, S::Error>
+    where E: PrimitiveEnumStaticStr, E::Primitive: Serialize, S: Serializer
+{
+    if serializer.is_human_readable() {
+        serializer.serialize_str(value.to_display_str())
+    } else {
+        value.to_primitive().serialize(serializer)
+    }
+}
+
+/// Deserializes the name of the variant or its primitive value.
+pub fn deserialize<'de, E, D>(deserializer: D) -> Result<E, D::Error>
+    where E: PrimitiveEnum, E::Primitive: Deserialize<'de> + TryFrom<u64> + TryFrom<i64>, D: Deserializer<'de>
+{
+    deserialize_with(deserializer, E::from_str)
+}
+
+/// Same as ```deserialize```, with the text parsed by ```parse```.
+#[doc(hidden)]
+pub fn deserialize_with<'de, E, D>(deserializer: D, parse: fn(&str) -> Option<E>) -> Result<E, D::Error>
+    where E: PrimitiveEnum, E::Primitive: Deserialize<'de> + TryFrom<u64> + TryFrom<i64>, D: Deserializer<'de>
+{
+    if deserializer.is_human_readable() {
+        deserializer.deserialize_any(PrimitiveEnumVisitor(parse))
+    } else {
+        let value = E::Primitive::deserialize(deserializer)?;
+        E::from_primitive(value).ok_or_else(|| de::Error::custom(format_args!("invalid enum value {:?}", value)))
+    }
+}
+
+struct PrimitiveEnumVisitor<E>(fn(&str) -> Option<E>);
+
+impl<'de, E> Visitor<'de> for PrimitiveEnumVisitor<E>
+    where E: PrimitiveEnum, E::Primitive: TryFrom<u64> + TryFrom<i64>
+{
+    type Value = E;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "the name or the value of a variant")
+    }
+
+    fn visit_str<Er>(self, v: &str) -> Result<E, Er> where Er: de::Error {
+        (self.0)(v).ok_or_else(|| Er::invalid_value(Unexpected::Str(v), &self))
+    }
+
+    fn visit_u64<Er>(self, v: u64) -> Result<E, Er> where Er: de::Error {
+        E::Primitive::try_from(v).ok()
+            .and_then(E::from_primitive)
+            .ok_or_else(|| Er::invalid_value(Unexpected::Unsigned(v), &self))
+    }
+
+    fn visit_i64<Er>(self, v: i64) -> Result<E, Er> where Er: de::Error {
+        E::Primitive::try_from(v).ok()
+            .and_then(E::from_primitive)
+            .ok_or_else(|| Er::invalid_value(Unexpected::Signed(v), &self))
+    }
+}
@@ -0,0 +1,18 @@
+//! Structured events with the ```tracing``` crate. With the ```tracing``` feature of the code
+//! generator, every structure records an event with its name as the message and every field
+//! as an attribute, with the value formatted by its ```Debug``` implementation. The array
+//! elements are recorded one by one, as `values[0]`, `values[1]`, and so on.
+//!
+//! ```rust,ignore
+//! use packed_struct::tracing_event::{PackedStructTracing, Level};
+//!
+//! status.trace_event(Level::DEBUG);
+//! ```
+
+pub use tracing::{event, Level};
+
+/// A structure that records its fields as a ```tracing``` event.
+pub trait PackedStructTracing {
+    /// Record an event at the level, in the module of the structure.
+    fn trace_event(&self, level: Level);
+}
@@ -27,7 +27,16 @@ impl<T, B> Display for Integer<T, B> where T: Display {
     }
 }
 
+#[cfg(feature="defmt")]
+impl<T, B> ::defmt::Format for Integer<T, B> where T: ::defmt::Format {
+    fn format(&self, f: ::defmt::Formatter) {
+        self.num.format(f)
+    }
+}
+
+#[cfg(feature="serde")]
 use serde::ser::{Serialize, Serializer};
+#[cfg(feature="serde")]
 impl<T, B> Serialize for Integer<T, B> where T: Serialize {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
         where S: Serializer
@@ -36,7 +45,9 @@ impl<T, B> Serialize for Integer<T, B> where T: Serialize {
     }
 }
 
+#[cfg(feature="serde")]
 use serde::de::{Deserialize, Deserializer};
+#[cfg(feature="serde")]
 impl<'de, T, B> Deserialize<'de> for Integer<T, B> where T: Deserialize<'de>, T: Into<Integer<T, B>> {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
         where D: Deserializer<'de>
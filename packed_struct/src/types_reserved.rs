@@ -14,7 +14,8 @@ pub trait ReservedBitValue {
     fn get_reserved_bit_value_byte() -> u8;
 }
 
-#[derive(Default, Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Default, Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature="serde", derive(Serialize, Deserialize))]
 pub struct BitOne;
 impl ReservedBitValue for BitOne {
     fn get_reserved_bit_value_byte() -> u8 {
@@ -22,7 +23,8 @@ impl ReservedBitValue for BitOne {
     }
 }
 
-#[derive(Default, Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Default, Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature="serde", derive(Serialize, Deserialize))]
 pub struct BitZero;
 impl ReservedBitValue for BitZero {
     fn get_reserved_bit_value_byte() -> u8 {
@@ -31,12 +33,29 @@ impl ReservedBitValue for BitZero {
 }
 
 /// Always packs into the associated bit value. Ignores the input when unpacking.
-#[derive(Default, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Default, Copy, Clone, PartialEq)]
 pub struct ReservedBits<V, B> {
     value: V,
     bits: PhantomData<B>
 }
 
+/// Serialized as a unit, the bits are always the same.
+#[cfg(feature="serde")]
+impl<V, B> ::serde::Serialize for ReservedBits<V, B> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: ::serde::Serializer {
+        serializer.serialize_unit()
+    }
+}
+
+/// Ignores the serialized value.
+#[cfg(feature="serde")]
+impl<'de, V, B> ::serde::Deserialize<'de> for ReservedBits<V, B> where Self: Default {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: ::serde::Deserializer<'de> {
+        <::serde::de::IgnoredAny as ::serde::Deserialize>::deserialize(deserializer)?;
+        Ok(Self::default())
+    }
+}
+
 impl<B> Debug for ReservedBits<BitZero, B> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "Reserved - always 0")
@@ -49,6 +68,13 @@ impl<B> Display for ReservedBits<BitZero, B> {
     }
 }
 
+#[cfg(feature="defmt")]
+impl<B> ::defmt::Format for ReservedBits<BitZero, B> {
+    fn format(&self, f: ::defmt::Formatter) {
+        ::defmt::write!(f, "Reserved - always 0")
+    }
+}
+
 impl<B> Debug for ReservedBits<BitOne, B> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "Reserved - always 1")
@@ -61,6 +87,13 @@ impl<B> Display for ReservedBits<BitOne, B> {
     }
 }
 
+#[cfg(feature="defmt")]
+impl<B> ::defmt::Format for ReservedBits<BitOne, B> {
+    fn format(&self, f: ::defmt::Formatter) {
+        ::defmt::write!(f, "Reserved - always 1")
+    }
+}
+
 
 
 use packing::*;
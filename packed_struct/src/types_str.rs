@@ -0,0 +1,244 @@
+//! Fixed size text fields. The text is stored as UTF-8, either padded with
+//! NUL bytes, spaces or terminated with a NUL byte.
+
+use internal_prelude::v1::*;
+
+use packing::PackingError;
+use types_bits::{NumberOfBytes, ByteArray};
+
+/// How the unused bytes of a packed text field are filled.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum StringPadding {
+    /// Trailing bytes are NUL. A text can occupy all of the bytes.
+    Nul,
+    /// Trailing bytes are spaces. A text can occupy all of the bytes.
+    Space,
+    /// The text is followed by at least one NUL byte.
+    NulTerminated
+}
+
+/// Packs the text into the output buffer, padding the unused bytes. Texts that don't
+/// fit are truncated on a character boundary.
+pub fn pack_str(s: &str, padding: StringPadding, output: &mut [u8]) {
+    let max_len = match padding {
+        StringPadding::Nul | StringPadding::Space => output.len(),
+        StringPadding::NulTerminated => output.len().saturating_sub(1)
+    };
+
+    let mut len = min(s.len(), max_len);
+    while !s.is_char_boundary(len) {
+        len -= 1;
+    }
+
+    output[..len].copy_from_slice(&s.as_bytes()[..len]);
+
+    let pad = match padding {
+        StringPadding::Space => b' ',
+        StringPadding::Nul | StringPadding::NulTerminated => 0
+    };
+    for b in output[len..].iter_mut() {
+        *b = pad;
+    }
+}
+
+/// Unpacks the text from a packed buffer, without the padding.
+pub fn unpack_str(src: &[u8], padding: StringPadding) -> Result<&str, PackingError> {
+    let text = match padding {
+        StringPadding::Nul => {
+            let len = src.iter().rposition(|&b| b != 0).map(|p| p + 1).unwrap_or(0);
+            &src[..len]
+        },
+        StringPadding::Space => {
+            let len = src.iter().rposition(|&b| b != b' ').map(|p| p + 1).unwrap_or(0);
+            &src[..len]
+        },
+        StringPadding::NulTerminated => {
+            match src.iter().position(|&b| b == 0) {
+                Some(len) => &src[..len],
+                None => return Err(PackingError::InvalidValue)
+            }
+        }
+    };
+
+    str::from_utf8(text).map_err(|_| PackingError::InvalidValue)
+}
+
+/// A text type that can be used with packed string fields.
+pub trait PackedString where Self: Sized {
+    /// The text that is to be packed.
+    fn as_packed_str(&self) -> &str;
+    /// Create from an unpacked text.
+    fn from_packed_str(s: &str) -> Result<Self, PackingError>;
+
+    /// Number of bytes of the text types with a fixed size, checked against the width of
+    /// their fields at compile time.
+    const BYTES: Option<usize> = None;
+}
+
+/// Text stored inline in a fixed number of bytes, for use without an allocator.
+pub struct FixedString<B: NumberOfBytes> {
+    bytes: B::AsBytes,
+    len: usize
+}
+
+impl<B> FixedString<B> where B: NumberOfBytes {
+    /// Copies the text. Fails if it is longer than the capacity.
+    pub fn new(s: &str) -> Result<Self, PackingError> {
+        let mut bytes = B::AsBytes::default();
+        if s.len() > bytes.len() {
+            return Err(PackingError::BufferTooSmall);
+        }
+        bytes.as_mut_bytes_slice()[..s.len()].copy_from_slice(s.as_bytes());
+        Ok(FixedString { bytes, len: s.len() })
+    }
+
+    /// The stored text.
+    pub fn as_str(&self) -> &str {
+        // only ever filled from a valid str
+        str::from_utf8(&self.bytes.as_bytes_slice()[..self.len]).unwrap_or("")
+    }
+
+    /// Length of the stored text, in bytes.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Is the stored text empty?
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Maximum length of the text, in bytes.
+    pub const CAPACITY: usize = mem::size_of::<B::AsBytes>();
+
+    /// Maximum length of the text, in bytes.
+    pub fn capacity() -> usize {
+        Self::CAPACITY
+    }
+}
+
+impl<B> Default for FixedString<B> where B: NumberOfBytes {
+    fn default() -> Self {
+        FixedString { bytes: Default::default(), len: 0 }
+    }
+}
+
+impl<B> Clone for FixedString<B> where B: NumberOfBytes, B::AsBytes: Clone {
+    fn clone(&self) -> Self {
+        FixedString { bytes: self.bytes.clone(), len: self.len }
+    }
+}
+
+impl<B> Copy for FixedString<B> where B: NumberOfBytes, B::AsBytes: Copy { }
+
+impl<B> Deref for FixedString<B> where B: NumberOfBytes {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<B> PartialEq for FixedString<B> where B: NumberOfBytes {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl<B> Debug for FixedString<B> where B: NumberOfBytes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self.as_str())
+    }
+}
+
+#[cfg(feature="defmt")]
+impl<B> ::defmt::Format for FixedString<B> where B: NumberOfBytes {
+    fn format(&self, f: ::defmt::Formatter) {
+        ::defmt::write!(f, "{=str}", self.as_str())
+    }
+}
+
+#[cfg(feature="serde")]
+impl<B> ::serde::Serialize for FixedString<B> where B: NumberOfBytes {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: ::serde::Serializer {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+/// Fails for the texts that are longer than the capacity.
+#[cfg(feature="serde")]
+impl<'de, B> ::serde::Deserialize<'de> for FixedString<B> where B: NumberOfBytes {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: ::serde::Deserializer<'de> {
+        struct FixedStringVisitor<B>(PhantomData<B>);
+
+        impl<'de, B> ::serde::de::Visitor<'de> for FixedStringVisitor<B> where B: NumberOfBytes {
+            type Value = FixedString<B>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a text of at most {} bytes", FixedString::<B>::capacity())
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<FixedString<B>, E> where E: ::serde::de::Error {
+                FixedString::new(v).map_err(|_| E::invalid_length(v.len(), &self))
+            }
+        }
+
+        deserializer.deserialize_str(FixedStringVisitor(PhantomData))
+    }
+}
+
+impl<B> Display for FixedString<B> where B: NumberOfBytes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl<B> PackedString for FixedString<B> where B: NumberOfBytes {
+    fn as_packed_str(&self) -> &str {
+        self.as_str()
+    }
+
+    fn from_packed_str(s: &str) -> Result<Self, PackingError> {
+        Self::new(s)
+    }
+
+    const BYTES: Option<usize> = Some(Self::CAPACITY);
+}
+
+#[cfg(any(feature="alloc", feature="std"))]
+impl PackedString for String {
+    fn as_packed_str(&self) -> &str {
+        self.as_str()
+    }
+
+    fn from_packed_str(s: &str) -> Result<Self, PackingError> {
+        Ok(s.into())
+    }
+}
+
+#[test]
+fn test_pack_str_padding() {
+    let mut b = [0xAA; 6];
+    pack_str("abc", StringPadding::Nul, &mut b);
+    assert_eq!(b"abc\0\0\0", &b);
+    assert_eq!("abc", unpack_str(&b, StringPadding::Nul).unwrap());
+
+    pack_str("abc", StringPadding::Space, &mut b);
+    assert_eq!(b"abc   ", &b);
+    assert_eq!("abc", unpack_str(&b, StringPadding::Space).unwrap());
+
+    pack_str("abcdefgh", StringPadding::NulTerminated, &mut b);
+    assert_eq!(b"abcde\0", &b);
+    assert_eq!("abcde", unpack_str(&b, StringPadding::NulTerminated).unwrap());
+    assert_eq!(Err(PackingError::InvalidValue), unpack_str(b"abcdef", StringPadding::NulTerminated));
+}
+
+#[test]
+fn test_pack_str_truncation() {
+    let mut b = [0; 4];
+    // the last character takes two bytes and doesn't fit
+    pack_str("abcé", StringPadding::Nul, &mut b);
+    assert_eq!(b"abc\0", &b);
+
+    assert_eq!(Err(PackingError::InvalidValue), unpack_str(&[0x61, 0xC3, 0x28, 0x00], StringPadding::Nul));
+}
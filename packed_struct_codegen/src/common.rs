@@ -10,6 +10,16 @@ pub fn collections_prefix() -> syn::Ty {
     syn::parse_type("::alloc").unwrap()
 }
 
+#[cfg(feature="std")]
+pub fn core_prefix() -> syn::Ty {
+    syn::parse_type("::std").unwrap()
+}
+
+#[cfg(not(feature="std"))]
+pub fn core_prefix() -> syn::Ty {
+    syn::parse_type("::core").unwrap()
+}
+
 #[cfg(feature="std")]
 pub fn result_type() -> syn::Ty {
     syn::parse_type("::std::result::Result").expect("result type parse error")
@@ -35,3 +45,16 @@ pub fn alloc_supported() -> bool {
 pub fn include_debug_codegen() -> bool {
     alloc_supported()    
 }
+
+
+pub fn include_serde_codegen() -> bool {
+    cfg!(feature="serde")
+}
+
+pub fn include_defmt_codegen() -> bool {
+    cfg!(feature="defmt")
+}
+
+pub fn include_tracing_codegen() -> bool {
+    cfg!(feature="tracing")
+}
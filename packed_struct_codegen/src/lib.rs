@@ -14,6 +14,9 @@ use proc_macro::TokenStream;
 mod pack;
 mod pack_codegen;
 mod pack_codegen_docs;
+mod pack_codegen_register;
+mod pack_codegen_schema;
+mod pack_codegen_serde;
 mod pack_parse;
 mod pack_parse_attributes;
 
@@ -30,47 +33,47 @@ pub fn derive_packable_bytes(input: TokenStream) -> TokenStream {
     quote!(#pack).to_string().parse().unwrap()        
 }
 
-#[proc_macro_derive(PrimitiveEnum)]
+#[proc_macro_derive(PrimitiveEnum, attributes(primitive))]
 pub fn derive_primitive_detect(input: TokenStream) -> TokenStream {
     derive_primitive(input, None)
 }
 
-#[proc_macro_derive(PrimitiveEnum_u8)]
+#[proc_macro_derive(PrimitiveEnum_u8, attributes(primitive))]
 pub fn derive_primitive_u8(input: TokenStream) -> TokenStream {
     derive_primitive(input, Some(syn::parse_type("u8").unwrap()))
 }
 
-#[proc_macro_derive(PrimitiveEnum_u16)]
+#[proc_macro_derive(PrimitiveEnum_u16, attributes(primitive))]
 pub fn derive_primitive_u16(input: TokenStream) -> TokenStream {
     derive_primitive(input, Some(syn::parse_type("u16").unwrap()))
 }
 
-#[proc_macro_derive(PrimitiveEnum_u32)]
+#[proc_macro_derive(PrimitiveEnum_u32, attributes(primitive))]
 pub fn derive_primitive_u32(input: TokenStream) -> TokenStream {
     derive_primitive(input, Some(syn::parse_type("u32").unwrap()))
 }
 
-#[proc_macro_derive(PrimitiveEnum_u64)]
+#[proc_macro_derive(PrimitiveEnum_u64, attributes(primitive))]
 pub fn derive_primitive_u64(input: TokenStream) -> TokenStream {
     derive_primitive(input, Some(syn::parse_type("u64").unwrap()))
 }
 
-#[proc_macro_derive(PrimitiveEnum_i8)]
+#[proc_macro_derive(PrimitiveEnum_i8, attributes(primitive))]
 pub fn derive_primitive_i8(input: TokenStream) -> TokenStream {
     derive_primitive(input, Some(syn::parse_type("i8").unwrap()))
 }
 
-#[proc_macro_derive(PrimitiveEnum_i16)]
+#[proc_macro_derive(PrimitiveEnum_i16, attributes(primitive))]
 pub fn derive_primitive_i16(input: TokenStream) -> TokenStream {
     derive_primitive(input, Some(syn::parse_type("i16").unwrap()))
 }
 
-#[proc_macro_derive(PrimitiveEnum_i32)]
+#[proc_macro_derive(PrimitiveEnum_i32, attributes(primitive))]
 pub fn derive_primitive_i32(input: TokenStream) -> TokenStream {
     derive_primitive(input, Some(syn::parse_type("i32").unwrap()))
 }
 
-#[proc_macro_derive(PrimitiveEnum_i64)]
+#[proc_macro_derive(PrimitiveEnum_i64, attributes(primitive))]
 pub fn derive_primitive_i64(input: TokenStream) -> TokenStream {
     derive_primitive(input, Some(syn::parse_type("i64").unwrap()))
 }
@@ -3,11 +3,22 @@ extern crate syn;
 
 use std::ops::*;
 use pack_parse::*;
+use pack_parse_attributes::StringPadding;
 
 #[derive(Debug)]
 pub struct FieldMidPositioning {
     pub bit_width: usize,
     pub bits_position: BitsPositionParsed,
+    pub array_layout: Option<ArrayLayout>
+}
+
+/// Placement of the elements within a packed array.
+#[derive(Debug, Clone, Copy)]
+pub struct ArrayLayout {
+    pub element_bit_width: usize,
+    /// Distance between the starting bits of two consecutive elements.
+    pub element_stride_bits: usize,
+    pub reverse: bool
 }
 
 #[derive(Debug)]
@@ -18,9 +29,33 @@ pub enum FieldKind {
     },
     Array {
         ident: syn::Ident,
-        size: usize,
-        elements: Vec<FieldRegular>
+        /// Sizes of each dimension, outermost first. [[u8; 3]; 16] is [16, 3].
+        dimensions: Vec<usize>,
+        /// Flattened elements, the last index changing the fastest
+        elements: Vec<FieldRegular>,
+        /// The range of the whole array, including the padding between elements.
+        bit_range: Range<usize>
+    }
+}
+
+/// Splits a flat element index into the index of each dimension.
+pub fn array_element_indices(dimensions: &[usize], index: usize) -> Vec<usize> {
+    let mut indices = vec![0; dimensions.len()];
+    let mut index = index;
+    for (i, dimension) in dimensions.iter().enumerate().rev() {
+        indices[i] = index % dimension;
+        index /= dimension;
     }
+    indices
+}
+
+/// Name of an array element, for example "palette[3][1]".
+pub fn array_element_name(ident: &syn::Ident, dimensions: &[usize], index: usize) -> String {
+    let mut name = ident.as_ref().to_string();
+    for i in array_element_indices(dimensions, index) {
+        name.push_str(&format!("[{}]", i));
+    }
+    name
 }
 
 #[derive(Debug)]
@@ -31,7 +66,15 @@ pub struct FieldRegular {
     /// The range as parsed by our parser. A single byte: 0..7
     pub bit_range: Range<usize>,
     /// The range that can be used by rust's slices. A single byte: 0..8
-    pub bit_range_rust: Range<usize>
+    pub bit_range_rust: Range<usize>,
+    /// The fixed value of the bits of the ReservedBits types
+    pub reserved: Option<ReservedValue>
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ReservedValue {
+    Zero,
+    One
 }
 
 #[derive(Debug, Clone)]
@@ -42,7 +85,19 @@ pub enum SerializationWrapper {
     EndiannesWrapper {
         endian: syn::Ty
     },
-    PrimitiveEnumWrapper
+    PrimitiveEnumWrapper {
+        /// Every value of the bits is a variant, unpacking can't fail.
+        exhaustive: bool
+    },
+    BoolWrapper {
+        primitive: syn::Ty,
+        true_value: u64,
+        false_value: u64,
+        non_zero: bool
+    },
+    StringWrapper {
+        padding: StringPadding
+    }
 }
 
 
@@ -51,7 +106,42 @@ pub struct PackStruct {
     pub ast: syn::MacroInput,    
     pub fields: Vec<FieldKind>,
     pub num_bytes: usize,
-    pub num_bits: usize
+    pub num_bits: usize,
+    /// Set for the structures that describe a register.
+    pub register: Option<PackRegister>,
+    /// Emit the runtime schema, requested with #[packed_struct(schema)]
+    pub schema: bool,
+    /// Implement proptest's Arbitrary, requested with #[packed_struct(proptest)]
+    pub proptest: bool,
+    /// Implement serde's Serialize and Deserialize, requested with #[packed_struct(serde)]
+    pub serde: bool,
+    /// Implement PackedStructDebugWrite, requested with #[packed_struct(debug_write)]
+    pub debug_write: bool
+}
+
+/// Location and access permissions of a register.
+#[derive(Debug, Clone, Copy)]
+pub struct PackRegister {
+    pub address: u64,
+    pub access: RegisterAccess
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RegisterAccess {
+    ReadOnly,
+    WriteOnly,
+    ReadWrite
+}
+
+impl RegisterAccess {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "ro" | "r" | "read-only" => Some(RegisterAccess::ReadOnly),
+            "wo" | "w" | "write-only" => Some(RegisterAccess::WriteOnly),
+            "rw" | "read-write" => Some(RegisterAccess::ReadWrite),
+            _ => None
+        }
+    }
 }
 
 
@@ -2,7 +2,11 @@ extern crate quote;
 extern crate syn;
 
 use pack::*;
+use pack_parse_attributes::StringPadding;
 use pack_codegen_docs::*;
+use pack_codegen_schema::*;
+use pack_codegen_register::*;
+use pack_codegen_serde::*;
 use pack_parse::syn_to_string;
 use common::*;
 use utils::*;
@@ -11,6 +15,84 @@ use utils::*;
 
 
 
+/// Fails to compile when the variants of an enum field don't fit into its bits, they would
+/// be truncated when packed.
+fn enum_width_checks(parsed: &PackStruct) -> quote::Tokens {
+    if !parsed.ast.generics.ty_params.is_empty() {
+        return quote! {};
+    }
+
+    let checks: Vec<_> = parsed.fields.iter().filter_map(|f| {
+        let (ident, field) = match f {
+            &FieldKind::Regular { ref ident, ref field } => (ident, field),
+            &FieldKind::Array { ref ident, ref elements, .. } => (ident, elements.first()?)
+        };
+
+        let exhaustive = field.serialization_wrappers.iter().filter_map(|w| match w {
+            &SerializationWrapper::PrimitiveEnumWrapper { exhaustive } => Some(exhaustive),
+            _ => None
+        }).next()?;
+
+        let ty = &field.ty;
+        let ty_name = type_name(&syn_to_string(ty));
+        let bit_width = field.bit_width;
+
+        // the exhaustive enums ignore the bits above their own
+        if exhaustive {
+            let message = format!("{} isn't exhaustive in the {} bits of {}::{}", ty_name, bit_width, parsed.ast.ident, ident);
+            Some(quote! {
+                const _: () = assert!(<#ty as ::packed_struct::ExhaustivePrimitiveEnum>::BITS == #bit_width, #message);
+            })
+        } else {
+            let message = format!("The variants of {} don't fit into the {} bits of {}::{}", ty_name, bit_width, parsed.ast.ident, ident);
+            Some(quote! {
+                const _: () = {
+                    #[allow(unused_imports)]
+                    use ::packed_struct::EnumBitsCheckFallback;
+                    assert!(::packed_struct::EnumBitsCheck::<#ty>::MAX_BITS <= #bit_width, #message);
+                };
+            })
+        }
+    }).collect();
+
+    quote! {
+        #(#checks)*
+    }
+}
+
+/// Fails to compile when a text type of a fixed size doesn't match the bytes of its field.
+fn str_width_checks(parsed: &PackStruct) -> quote::Tokens {
+    if !parsed.ast.generics.ty_params.is_empty() {
+        return quote! {};
+    }
+
+    let checks: Vec<_> = parsed.fields.iter().filter_map(|f| {
+        let (ident, field) = match f {
+            &FieldKind::Regular { ref ident, ref field } => (ident, field),
+            &FieldKind::Array { ref ident, ref elements, .. } => (ident, elements.first()?)
+        };
+
+        field.serialization_wrappers.iter().find(|w| match w {
+            &SerializationWrapper::StringWrapper { .. } => true,
+            _ => false
+        })?;
+
+        let ty = &field.ty;
+        let num_bytes = field.bit_width / 8;
+        let message = format!("{} doesn't match the {} bytes of {}::{}", type_name(&syn_to_string(ty)), num_bytes, parsed.ast.ident, ident);
+        Some(quote! {
+            const _: () = assert!(match <#ty as ::packed_struct::types::PackedString>::BYTES {
+                Some(bytes) => bytes == #num_bytes,
+                None => true
+            }, #message);
+        })
+    }).collect();
+
+    quote! {
+        #(#checks)*
+    }
+}
+
 pub fn derive_pack(parsed: &PackStruct) -> quote::Tokens {
 
     let (impl_generics, ty_generics, where_clause) = parsed.ast.generics.split_for_impl();
@@ -62,19 +144,19 @@ pub fn derive_pack(parsed: &PackStruct) -> quote::Tokens {
                         #ident: #ident
                     });
                 },
-                &FieldKind::Array { ref ident, ref elements, .. } => {
+                &FieldKind::Array { ref ident, ref dimensions, ref elements, .. } => {
                     let mut array_unpacked_elements = vec![];
                     for (i, field) in elements.iter().enumerate() {
-                        let src = syn::Ident::new(format!("{}[{}]", syn_to_string(ident), i));
-                        let target = syn::Ident::new(format!("{}_{}", syn_to_string(ident), i));
+                        let indices: Vec<_> = array_element_indices(dimensions, i).iter().map(|i| i.to_string()).collect();
+                        let src = syn::Ident::new(array_element_name(ident, dimensions, i));
+                        let target = syn::Ident::new(format!("{}_{}", syn_to_string(ident), indices.join("_")));
                         reg(&src, &target, field);
                         array_unpacked_elements.push(target);
                     }
 
+                    let array = nested_array(dimensions, &array_unpacked_elements);
                     unpack_struct_set.push(quote! {
-                        #ident: [
-                            #(#array_unpacked_elements),*
-                        ]
+                        #ident: #array
                     });
                 }
             }        
@@ -96,6 +178,22 @@ pub fn derive_pack(parsed: &PackStruct) -> quote::Tokens {
                 pub fn packed_struct_display_formatter<'a>(&'a self) -> ::packed_struct::debug_fmt::PackedStructDisplay<'a, Self, [u8; #num_bytes]> {
                     ::packed_struct::debug_fmt::PackedStructDisplay::new(self)
                 }
+
+                #[allow(dead_code)]
+                /// The fields whose values differ in the other value
+                pub fn diff(&self, other: &Self) -> impl Iterator<Item=::packed_struct::debug_fmt::FieldChange> {
+                    use ::packed_struct::debug_fmt::PackedStructDebug;
+
+                    let old_packed: [u8; #num_bytes] = ::packed_struct::PackedStruct::pack(self);
+                    let new_packed: [u8; #num_bytes] = ::packed_struct::PackedStruct::pack(other);
+                    ::packed_struct::debug_fmt::field_changes(&old_packed, self.debug_fields(), &new_packed, other.debug_fields()).into_iter()
+                }
+
+                #[allow(dead_code)]
+                /// Display formatter of the changes from this value to the other one
+                pub fn packed_struct_diff_formatter<'a>(&'a self, other: &'a Self) -> ::packed_struct::debug_fmt::PackedStructDiffDisplay<'a, Self> {
+                    ::packed_struct::debug_fmt::PackedStructDiffDisplay::new(self, other)
+                }
             }
 
         }
@@ -103,8 +201,22 @@ pub fn derive_pack(parsed: &PackStruct) -> quote::Tokens {
         quote! {}
     };
 
+    let enum_width_checks = enum_width_checks(parsed);
+    let str_width_checks = str_width_checks(parsed);
+    let fields_writer = struct_fields_writer(parsed);
+    let defmt_format = struct_defmt_format(parsed);
+    let tracing_event = struct_tracing_event(parsed);
+    let serde = struct_serde(parsed);
+    let schema = struct_schema(parsed);
+    let field_access = struct_field_access(parsed);
+    let arbitrary = struct_arbitrary(parsed);
+    let register = register_impl(parsed);
+
     quote! {
         #type_documentation
+        #enum_width_checks
+        #str_width_checks
+
         impl #impl_generics ::packed_struct::PackedStruct<[u8; #num_bytes]> for #name #ty_generics #where_clause {
             #[inline]
             #[allow(unused_imports, unused_parens)]
@@ -148,7 +260,7 @@ pub fn derive_pack(parsed: &PackStruct) -> quote::Tokens {
                     return Err(::packed_struct::PackingError::BufferTooSmall);
                 }
                 let packed = self.pack();                
-                &mut output[..].copy_from_slice(&packed[..]);
+                output[..].copy_from_slice(&packed[..]);
                 Ok(())
             }
 
@@ -161,7 +273,7 @@ pub fn derive_pack(parsed: &PackStruct) -> quote::Tokens {
                     return Err(::packed_struct::PackingError::BufferTooSmall);
                 }
                 let mut s = [0; #num_bytes];
-                &mut s[..].copy_from_slice(&src[..#num_bytes]);
+                s[..].copy_from_slice(&src[..#num_bytes]);
                 Self::unpack(&s)
             }
 
@@ -172,11 +284,46 @@ pub fn derive_pack(parsed: &PackStruct) -> quote::Tokens {
         }
 
         #debug_fmt
+
+        #fields_writer
+
+        #defmt_format
+
+        #tracing_event
+
+        #serde
+
+        #schema
+
+        #field_access
+
+        #arbitrary
+
+        #register
     }
 }
 
 
 
+/// Array expression of the flattened elements, nested by the dimensions.
+fn nested_array(dimensions: &[usize], elements: &[syn::Ident]) -> quote::Tokens {
+    if dimensions.len() <= 1 {
+        return quote! {
+            [
+                #(#elements),*
+            ]
+        };
+    }
+
+    let inner_size = elements.len() / dimensions[0];
+    let inner: Vec<_> = elements.chunks(inner_size).map(|c| nested_array(&dimensions[1..], c)).collect();
+    quote! {
+        [
+            #(#inner),*
+        ]
+    }
+}
+
 struct PackBitsCopy {
     pack: quote::Tokens,
     unpack: quote::Tokens
@@ -192,11 +339,11 @@ fn pack_bits(field: &FieldRegular) -> PackBitsCopy {
         
         PackBitsCopy {
             pack: quote! {
-                &mut target[#start..#end].copy_from_slice(&packed);
+                target[#start..#end].copy_from_slice(&packed);
             },
             unpack: quote! {
                 let mut b = [0; (#end - #start)];
-                &mut b[..].copy_from_slice(&src[#start..#end]);
+                b[..].copy_from_slice(&src[#start..#end]);
                 b
             }
         }
@@ -289,9 +436,24 @@ fn pack_bits(field: &FieldRegular) -> PackBitsCopy {
 fn pack_field(name: &syn::Ident, field: &FieldRegular) -> quote::Tokens {
     let mut output = quote! { (self.#name) };
 
+    if let Some(&SerializationWrapper::StringWrapper { padding }) = field.serialization_wrappers.first() {
+        let num_bytes = field.bit_width / 8;
+        let padding = string_padding_tokens(padding);
+
+        return quote! {
+            {
+                use ::packed_struct::types::*;
+
+                let mut bytes = [0 as u8; #num_bytes];
+                pack_str(PackedString::as_packed_str(& #output), #padding, &mut bytes);
+                bytes
+            }
+        };
+    }
+
     for wrapper in &field.serialization_wrappers {
         match wrapper {
-            &SerializationWrapper::PrimitiveEnumWrapper => {
+            &SerializationWrapper::PrimitiveEnumWrapper { .. } => {
                 output = quote! {
                     {
                         use ::packed_struct::PrimitiveEnum;
@@ -301,6 +463,20 @@ fn pack_field(name: &syn::Ident, field: &FieldRegular) -> quote::Tokens {
                     }
                 };
             },
+            &SerializationWrapper::StringWrapper { .. } => {
+                panic!("Text fields can't be wrapped.");
+            },
+            &SerializationWrapper::BoolWrapper { ref primitive, true_value, false_value, .. } => {
+                let true_value = syn::Lit::Int(true_value, syn::IntTy::Unsuffixed);
+                let false_value = syn::Lit::Int(false_value, syn::IntTy::Unsuffixed);
+
+                output = quote! {
+                    {
+                        let primitive_integer: #primitive = if { #output } { #true_value } else { #false_value };
+                        primitive_integer
+                    }
+                };
+            },
             &SerializationWrapper::IntegerWrapper { ref integer } => {
                 output = quote! {
                     {
@@ -355,7 +531,15 @@ fn unpack_field(field: &FieldRegular) -> quote::Tokens {
 
                 i += 1;
             }
-            (Some(&SerializationWrapper::PrimitiveEnumWrapper), _) => {
+            (Some(&SerializationWrapper::PrimitiveEnumWrapper { exhaustive: true }), _) => {
+                let ty = &field.ty;
+
+                unpack = quote! {
+                    let primitive_integer: <#ty as ::packed_struct::PrimitiveEnum>::Primitive = { #unpack };
+                    <#ty as ::packed_struct::ExhaustivePrimitiveEnum>::from_bits(primitive_integer)
+                };
+            },
+            (Some(&SerializationWrapper::PrimitiveEnumWrapper { .. }), _) => {
                 let ty = &field.ty;
                 
                 unpack = quote! {
@@ -366,6 +550,38 @@ fn unpack_field(field: &FieldRegular) -> quote::Tokens {
                     r?
                 };
             },
+            (Some(&SerializationWrapper::StringWrapper { padding }), _) => {
+                let ty = &field.ty;
+                let padding = string_padding_tokens(padding);
+
+                unpack = quote! {
+                    use ::packed_struct::types::*;
+
+                    let s = unpack_str(& #unpack, #padding)?;
+                    <#ty as PackedString>::from_packed_str(s)?
+                };
+            },
+            (Some(&SerializationWrapper::BoolWrapper { ref primitive, true_value, false_value, non_zero }), _) => {
+                let true_value = syn::Lit::Int(true_value, syn::IntTy::Unsuffixed);
+                let false_value = syn::Lit::Int(false_value, syn::IntTy::Unsuffixed);
+
+                unpack = if non_zero {
+                    quote! {
+                        let primitive_integer: #primitive = { #unpack };
+                        primitive_integer != #false_value
+                    }
+                } else {
+                    quote! {
+                        let primitive_integer: #primitive = { #unpack };
+                        let r = match primitive_integer {
+                            #true_value => Ok(true),
+                            #false_value => Ok(false),
+                            _ => Err(PackingError::InvalidValue)
+                        };
+                        r?
+                    }
+                };
+            },
             (Some(&SerializationWrapper::EndiannesWrapper { ref endian }), _) => {
                 let integer_ty = &field.ty;
 
@@ -395,4 +611,11 @@ fn unpack_field(field: &FieldRegular) -> quote::Tokens {
     }
 
     unpack
-}
\ No newline at end of file
+}
+pub fn string_padding_tokens(padding: StringPadding) -> quote::Tokens {
+    match padding {
+        StringPadding::Nul => quote! { ::packed_struct::types::StringPadding::Nul },
+        StringPadding::Space => quote! { ::packed_struct::types::StringPadding::Space },
+        StringPadding::NulTerminated => quote! { ::packed_struct::types::StringPadding::NulTerminated }
+    }
+}
@@ -37,16 +37,17 @@ pub fn struct_runtime_formatter(parsed: &PackStruct) -> quote::Tokens {
                     }
                 });
             },
-            &FieldKind::Array { ref ident, ref elements, .. } => {
+            &FieldKind::Array { ref ident, ref dimensions, ref elements, .. } => {
                 for (i, field) in elements.iter().enumerate() {
-                    let name_str = format!("{}[{}]", syn_to_string(ident), i);
+                    let name_str = array_element_name(ident, dimensions, i);
+                    let element = syn::Ident::new(name_str.clone());
                     let bits = syn::parse_expr(&format!("{}..{}", field.bit_range.start, field.bit_range.end)).unwrap();
                     
                     debug_fields.push(quote! {
                         ::packed_struct::debug_fmt::DebugBitField {
                             name: #name_str.into(),
                             bits: #bits,
-                            display_value: format!("{:?}", src.#ident[#i]).into()
+                            display_value: format!("{:?}", src.#element).into()
                         }
                     });
                 }
@@ -78,6 +79,10 @@ pub fn struct_runtime_formatter(parsed: &PackStruct) -> quote::Tokens {
             fn packed_struct_display_header() -> &'static str {
                 #display_header
             }
+
+            fn debug_fields(&self) -> #stdlib_prefix::vec::Vec<::packed_struct::debug_fmt::DebugBitField<'static>> {
+                #stdlib_prefix::vec::Vec::from(#debug_fields_fn(self))
+            }
         }
 
         #[allow(unused_imports)]
@@ -91,6 +96,139 @@ pub fn struct_runtime_formatter(parsed: &PackStruct) -> quote::Tokens {
     }
 }
 
+/// The fields table written without allocating, also in the ```no_std``` builds. Requested
+/// with #[packed_struct(debug_write)], as every field has to implement ```Debug```.
+pub fn struct_fields_writer(parsed: &PackStruct) -> quote::Tokens {
+    if !parsed.debug_write {
+        return quote! {};
+    }
+
+    let (impl_generics, ty_generics, where_clause) = parsed.ast.generics.split_for_impl();
+    let name = &parsed.ast.ident;
+    let core_prefix = core_prefix();
+    let num_bytes = parsed.num_bytes;
+
+    let mut fields = vec![];
+    {
+        let mut push_field = |name_str: String, ident: &syn::Ident, bits: &Range<usize>| {
+            let bits = syn::parse_expr(&format!("{}..{}", bits.start, bits.end)).unwrap();
+            fields.push(quote! {
+                ::packed_struct::debug_write::DebugFieldRef {
+                    name: #name_str,
+                    bits: #bits,
+                    value: &self.#ident
+                }
+            });
+        };
+
+        for field in &parsed.fields {
+            match field {
+                &FieldKind::Regular { ref ident, ref field } => {
+                    push_field(ident.as_ref().to_string(), ident, &field.bit_range);
+                },
+                &FieldKind::Array { ref ident, ref dimensions, ref elements, .. } => {
+                    for (i, field) in elements.iter().enumerate() {
+                        let name_str = array_element_name(ident, dimensions, i);
+                        let element = syn::Ident::new(name_str.clone());
+                        push_field(name_str, &element, &field.bit_range);
+                    }
+                }
+            }
+        }
+    }
+
+    let visited_fields = fields.clone();
+
+    quote! {
+        impl #impl_generics ::packed_struct::debug_write::PackedStructDebugWrite for #name #ty_generics #where_clause {
+            fn write_fields(&self, w: &mut ::packed_struct::debug_write::FieldsWriter) -> #core_prefix::fmt::Result {
+                let packed: [u8; #num_bytes] = ::packed_struct::PackedStruct::pack(self);
+                let fields = [#(#fields),*];
+                ::packed_struct::debug_write::write_fields(w, &packed, &fields)
+            }
+
+            fn visit_fields<'a>(&'a self, visitor: &mut dyn FnMut(::packed_struct::debug_write::DebugFieldRef<'a>)) {
+                for field in [#(#visited_fields),*] {
+                    visitor(field);
+                }
+            }
+        }
+    }
+}
+
+/// ```defmt::Format``` with the names and values of the fields, for the ```defmt``` feature.
+pub fn struct_defmt_format(parsed: &PackStruct) -> quote::Tokens {
+    if !include_defmt_codegen() {
+        return quote! {};
+    }
+
+    let (impl_generics, ty_generics, where_clause) = parsed.ast.generics.split_for_impl();
+    let name = &parsed.ast.ident;
+
+    let idents: Vec<_> = parsed.fields.iter().map(|field| match field {
+        &FieldKind::Regular { ref ident, .. } => ident,
+        &FieldKind::Array { ref ident, .. } => ident
+    }).collect();
+    let names: Vec<_> = idents.iter().map(|ident| format!("{}: {{}}", ident)).collect();
+    let format_str = format!("{} {{{{ {} }}}}", name, names.join(", "));
+
+    quote! {
+        impl #impl_generics defmt::Format for #name #ty_generics #where_clause {
+            fn format(&self, f: defmt::Formatter) {
+                defmt::write!(f, #format_str, #(self.#idents),*)
+            }
+        }
+    }
+}
+
+/// An event with every field as an attribute, for the ```tracing``` feature.
+pub fn struct_tracing_event(parsed: &PackStruct) -> quote::Tokens {
+    if !include_tracing_codegen() {
+        return quote! {};
+    }
+
+    let (impl_generics, ty_generics, where_clause) = parsed.ast.generics.split_for_impl();
+    let name = &parsed.ast.ident;
+    let name_str = name.as_ref().to_string();
+
+    let mut fields = vec![];
+    for field in &parsed.fields {
+        match field {
+            &FieldKind::Regular { ref ident, .. } => {
+                let name_str = ident.as_ref().to_string();
+                fields.push(quote! { #name_str = ?self.#ident });
+            },
+            &FieldKind::Array { ref ident, ref dimensions, ref elements, .. } => {
+                for i in 0..elements.len() {
+                    let name_str = array_element_name(ident, dimensions, i);
+                    let element = syn::Ident::new(name_str.clone());
+                    fields.push(quote! { #name_str = ?self.#element });
+                }
+            }
+        }
+    }
+
+    let events: Vec<_> = ["TRACE", "DEBUG", "INFO", "WARN", "ERROR"].iter().map(|level| {
+        let level = syn::Ident::from(*level);
+        let fields = &fields;
+        quote! {
+            if level == Level::#level {
+                event!(Level::#level, { #(#fields),* }, #name_str);
+            }
+        }
+    }).collect();
+
+    quote! {
+        impl #impl_generics ::packed_struct::tracing_event::PackedStructTracing for #name #ty_generics #where_clause {
+            fn trace_event(&self, level: ::packed_struct::tracing_event::Level) {
+                use ::packed_struct::tracing_event::{event, Level};
+
+                #(#events)*
+            }
+        }
+    }
+}
+
 use std::ops::Range;
 
 
@@ -127,9 +265,9 @@ pub fn type_docs(parsed: &PackStruct) -> quote::Tokens {
                 &FieldKind::Regular { ref ident, ref field } => {
                     emit_field_docs(&field.bit_range, ident.as_ref().to_string(), &field.ty);
                 },
-                &FieldKind::Array { ref ident, ref elements, .. } => {
+                &FieldKind::Array { ref ident, ref dimensions, ref elements, .. } => {
                     for (i, field) in elements.iter().enumerate() {
-                        emit_field_docs(&field.bit_range, format!("{}[{}]", syn_to_string(ident), i), &field.ty);
+                        emit_field_docs(&field.bit_range, array_element_name(ident, dimensions, i), &field.ty);
                     }
                 }
             }
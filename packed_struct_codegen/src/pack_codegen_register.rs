@@ -0,0 +1,49 @@
+extern crate quote;
+extern crate syn;
+
+use pack::*;
+
+
+/// Ties the structure to its register address, with the marker traits of the
+/// allowed accesses.
+pub fn register_impl(parsed: &PackStruct) -> quote::Tokens {
+    let register = match parsed.register {
+        Some(register) => register,
+        None => return quote! {}
+    };
+
+    let (impl_generics, ty_generics, where_clause) = parsed.ast.generics.split_for_impl();
+    let name = &parsed.ast.ident;
+    let num_bytes = parsed.num_bytes;
+    let address = register.address;
+
+    let access = match register.access {
+        RegisterAccess::ReadOnly => quote! { ::packed_struct::register::RegisterAccess::ReadOnly },
+        RegisterAccess::WriteOnly => quote! { ::packed_struct::register::RegisterAccess::WriteOnly },
+        RegisterAccess::ReadWrite => quote! { ::packed_struct::register::RegisterAccess::ReadWrite }
+    };
+
+    let readable = if register.access != RegisterAccess::WriteOnly {
+        quote! { impl #impl_generics ::packed_struct::register::ReadableRegister for #name #ty_generics #where_clause { } }
+    } else {
+        quote! {}
+    };
+
+    let writable = if register.access != RegisterAccess::ReadOnly {
+        quote! { impl #impl_generics ::packed_struct::register::WritableRegister for #name #ty_generics #where_clause { } }
+    } else {
+        quote! {}
+    };
+
+    quote! {
+        impl #impl_generics ::packed_struct::register::Register for #name #ty_generics #where_clause {
+            type Bytes = [u8; #num_bytes];
+            const ADDRESS: u64 = #address;
+            const ACCESS: ::packed_struct::register::RegisterAccess = #access;
+            const ZEROED: [u8; #num_bytes] = [0; #num_bytes];
+        }
+
+        #readable
+        #writable
+    }
+}
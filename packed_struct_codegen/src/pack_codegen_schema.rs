@@ -0,0 +1,400 @@
+extern crate quote;
+extern crate syn;
+
+use pack::*;
+use pack_parse::syn_to_string;
+use utils::*;
+use common::{result_type, core_prefix};
+use pack_codegen::string_padding_tokens;
+
+
+/// Static description of the structure's layout, for runtime reflection. Only for the
+/// structures with #[packed_struct(schema)], it requires the schemas of the nested
+/// structures and the enums.
+pub fn struct_schema(parsed: &PackStruct) -> quote::Tokens {
+    if !parsed.schema {
+        return quote! {};
+    }
+
+    let (impl_generics, ty_generics, where_clause) = parsed.ast.generics.split_for_impl();
+    let name = &parsed.ast.ident;
+    let name_str = name.as_ref().to_string();
+    let struct_doc = doc_comment(&parsed.ast.attrs);
+    let num_bytes = parsed.num_bytes;
+    let num_bits = parsed.num_bits;
+
+    let field_docs = |ident: &syn::Ident| -> String {
+        match parsed.ast.body {
+            syn::Body::Struct(syn::VariantData::Struct(ref fields)) => {
+                fields.iter()
+                    .find(|f| f.ident.as_ref() == Some(ident))
+                    .map(|f| doc_comment(&f.attrs))
+                    .unwrap_or_default()
+            },
+            _ => String::new()
+        }
+    };
+
+    let mut fields = vec![];
+    for field in &parsed.fields {
+        match field {
+            &FieldKind::Regular { ref ident, ref field } => {
+                fields.push(field_schema(ident.as_ref().to_string(), &field_docs(ident), field, parsed.num_bytes));
+            },
+            &FieldKind::Array { ref ident, ref dimensions, ref elements, .. } => {
+                let doc = field_docs(ident);
+                for (i, field) in elements.iter().enumerate() {
+                    fields.push(field_schema(array_element_name(ident, dimensions, i), &doc, field, parsed.num_bytes));
+                }
+            }
+        }
+    }
+
+    quote! {
+        impl #impl_generics ::packed_struct::schema::PackedStructSchemaInfo for #name #ty_generics #where_clause {
+            fn packed_struct_schema() -> &'static ::packed_struct::schema::PackedStructSchema {
+                static SCHEMA: ::packed_struct::schema::PackedStructSchema = ::packed_struct::schema::PackedStructSchema {
+                    name: #name_str,
+                    doc: #struct_doc,
+                    num_bytes: #num_bytes,
+                    num_bits: #num_bits,
+                    fields: &[#(#fields),*]
+                };
+
+                &SCHEMA
+            }
+        }
+    }
+}
+
+fn field_schema(name: String, doc: &str, field: &FieldRegular, num_bytes: usize) -> quote::Tokens {
+    let ty = &field.ty;
+    let ty_str = syn_to_string(ty);
+    let type_name = type_name(&ty_str);
+
+    let last_bit = num_bytes * 8 - 1;
+    let msb0_start = field.bit_range.start;
+    let msb0_end = field.bit_range.end;
+    let lsb0_start = last_bit - field.bit_range.end;
+    let lsb0_end = last_bit - field.bit_range.start;
+
+    let mut enum_schema = quote! { None };
+    let mut nested = quote! { None };
+    let mut reserved = false;
+
+    let kind = match value_kind(field) {
+        ValueKind::Str => {
+            let padding = field.serialization_wrappers.iter().filter_map(|w| match w {
+                &SerializationWrapper::StringWrapper { padding } => Some(string_padding_tokens(padding)),
+                _ => None
+            }).next().expect("Missing the string wrapper");
+            quote! { Str { padding: #padding } }
+        },
+        ValueKind::Enum => {
+            enum_schema = quote! { Some(<#ty as ::packed_struct::schema::PrimitiveEnumSchemaInfo>::SCHEMA) };
+            quote! { Enum }
+        },
+        ValueKind::Bool => {
+            let (true_bits, false_bits, nonzero) = field.serialization_wrappers.iter().filter_map(|w| match w {
+                &SerializationWrapper::BoolWrapper { true_value, false_value, non_zero, .. } => Some((true_value, false_value, non_zero)),
+                _ => None
+            }).next().unwrap_or((1, 0, false));
+            quote! { Bool { true_bits: #true_bits, false_bits: #false_bits, nonzero: #nonzero } }
+        },
+        ValueKind::Reserved => {
+            reserved = true;
+            let ones = field.reserved == Some(ReservedValue::One);
+            quote! { Reserved { ones: #ones } }
+        },
+        ValueKind::Integer { signed, .. } => quote! { Integer { signed: #signed } },
+        ValueKind::Struct => {
+            nested = quote! { Some(<#ty as ::packed_struct::schema::PackedStructSchemaInfo>::packed_struct_schema) };
+            quote! { Struct }
+        }
+    };
+
+    let wrappers = &field.serialization_wrappers;
+    let endianness = wrappers.iter().filter_map(|w| match w {
+        &SerializationWrapper::EndiannesWrapper { ref endian } if field.bit_width > 8 => {
+            if syn_to_string(endian).starts_with("Lsb") {
+                Some(quote! { Some(::packed_struct::schema::IntegerEndianness::Lsb) })
+            } else {
+                Some(quote! { Some(::packed_struct::schema::IntegerEndianness::Msb) })
+            }
+        },
+        _ => None
+    }).next().unwrap_or(quote! { None });
+
+    quote! {
+        ::packed_struct::schema::PackedFieldSchema {
+            name: #name,
+            type_name: #type_name,
+            bits_msb0: ::packed_struct::schema::BitRange { start: #msb0_start, end: #msb0_end },
+            bits_lsb0: ::packed_struct::schema::BitRange { start: #lsb0_start, end: #lsb0_end },
+            kind: ::packed_struct::schema::PackedFieldKind::#kind,
+            endianness: #endianness,
+            enum_schema: #enum_schema,
+            reserved: #reserved,
+            doc: #doc,
+            nested: #nested
+        }
+    }
+}
+
+
+/// The proptest strategy of the structure, for #[packed_struct(proptest)].
+pub fn struct_arbitrary(parsed: &PackStruct) -> quote::Tokens {
+    if !parsed.proptest {
+        return quote! {};
+    }
+
+    let (impl_generics, ty_generics, where_clause) = parsed.ast.generics.split_for_impl();
+    let name = &parsed.ast.ident;
+
+    quote! {
+        impl #impl_generics ::packed_struct::arbitrary::Arbitrary for #name #ty_generics #where_clause {
+            type Parameters = ();
+            type Strategy = ::packed_struct::arbitrary::BoxedStrategy<Self>;
+
+            fn arbitrary_with(_: ()) -> Self::Strategy {
+                ::packed_struct::arbitrary::packed_struct_strategy::<Self>()
+            }
+        }
+    }
+}
+
+
+/// Access to the fields by their names, as listed in the schema. Gated like the schema,
+/// as it requires the field access of the nested structures.
+pub fn struct_field_access(parsed: &PackStruct) -> quote::Tokens {
+    if !parsed.schema {
+        return quote! {};
+    }
+
+    let (impl_generics, ty_generics, where_clause) = parsed.ast.generics.split_for_impl();
+    let name = &parsed.ast.ident;
+    let core = core_prefix();
+
+    let mut get_fields = vec![];
+    let mut set_fields = vec![];
+    let mut nested_get = vec![];
+    let mut nested_set = vec![];
+
+    {
+        let mut field_access = |name_str: String, field_ident: syn::Ident, field: &FieldRegular| {
+            let ty = &field.ty;
+            let bit_width = field.bit_width;
+
+            let (get, set) = match value_kind(field) {
+                ValueKind::Bool => {
+                    (quote! { FieldValue::Bool(self.#field_ident) },
+                     quote! {
+                         match value {
+                             FieldValue::Bool(v) => { self.#field_ident = v; },
+                             _ => { return Err(FieldAccessError::InvalidValue); }
+                         }
+                     })
+                },
+                ValueKind::Integer { primitive, wrappers, signed } => {
+                    let (get_primitive, set_primitive) = match wrappers {
+                        0 => (quote! { self.#field_ident }, quote! { v as #primitive }),
+                        1 => (quote! { *self.#field_ident }, quote! { (v as #primitive).into() }),
+                        // the endianness wrappers hold an Integer
+                        _ => (quote! { **self.#field_ident }, quote! {
+                            {
+                                let i: <#ty as #core::ops::Deref>::Target = (v as #primitive).into();
+                                i.into()
+                            }
+                        })
+                    };
+                    let get = if signed {
+                        // sign extend the values that are narrower than their primitive
+                        let shift = 64 - bit_width;
+                        quote! { FieldValue::Signed(((#get_primitive as i64) << #shift) >> #shift) }
+                    } else {
+                        quote! { FieldValue::Unsigned(#get_primitive as u64) }
+                    };
+
+                    (get,
+                     quote! {
+                         match value {
+                             FieldValue::Unsigned(_) | FieldValue::Signed(_) => {
+                                 let v = value.to_integer().unwrap_or_default();
+                                 if !::packed_struct::schema::integer_fits(v, #bit_width, #signed) {
+                                     return Err(FieldAccessError::InvalidValue);
+                                 }
+                                 self.#field_ident = #set_primitive;
+                             },
+                             _ => { return Err(FieldAccessError::InvalidValue); }
+                         }
+                     })
+                },
+                ValueKind::Enum => {
+                    (quote! {
+                         {
+                             let value = PrimitiveEnum::to_primitive(&self.#field_ident) as i128;
+                             let variant = <#ty as ::packed_struct::schema::PrimitiveEnumSchemaInfo>::SCHEMA.variant_by_value(value);
+                             FieldValue::Enum { name: variant.map(|v| v.name), value: value }
+                         }
+                     },
+                     quote! {
+                         let v = match value {
+                             FieldValue::Enum { name: Some(name), .. } => <#ty as PrimitiveEnum>::from_str(name),
+                             FieldValue::Enum { .. } | FieldValue::Unsigned(_) | FieldValue::Signed(_) => {
+                                 let v = value.to_integer().unwrap_or_default();
+                                 let p = v as <#ty as PrimitiveEnum>::Primitive;
+                                 if p as i128 == v && ::packed_struct::schema::integer_fits(v, #bit_width, v < 0) {
+                                     <#ty as PrimitiveEnum>::from_primitive(p)
+                                 } else {
+                                     None
+                                 }
+                             },
+                             _ => None
+                         };
+                         self.#field_ident = v.ok_or(FieldAccessError::InvalidValue)?;
+                     })
+                },
+                ValueKind::Str => {
+                    let num_bytes = bit_width / 8;
+                    (quote! { FieldValue::Str(PackedString::as_packed_str(&self.#field_ident)) },
+                     quote! {
+                         match value {
+                             FieldValue::Str(s) => {
+                                 if s.len() > #num_bytes {
+                                     return Err(FieldAccessError::TooLong);
+                                 }
+                                 self.#field_ident = PackedString::from_packed_str(s).map_err(|_| FieldAccessError::InvalidValue)?;
+                             },
+                             _ => { return Err(FieldAccessError::InvalidValue); }
+                         }
+                     })
+                },
+                ValueKind::Struct => {
+                    let prefix = format!("{}.", name_str);
+                    let prefix_len = prefix.len();
+                    nested_get.push(quote! {
+                        if name.starts_with(#prefix) {
+                            return ::packed_struct::schema::PackedStructFieldAccess::get_field(&self.#field_ident, &name[#prefix_len..]);
+                        }
+                    });
+                    nested_set.push(quote! {
+                        if name.starts_with(#prefix) {
+                            return ::packed_struct::schema::PackedStructFieldAccess::set_field(&mut self.#field_ident, &name[#prefix_len..], value);
+                        }
+                    });
+
+                    (quote! { FieldValue::Struct(&self.#field_ident) },
+                     quote! { return Err(FieldAccessError::InvalidValue); })
+                },
+                ValueKind::Reserved => {
+                    return;
+                }
+            };
+
+            get_fields.push(quote! {
+                #name_str => Some(#get)
+            });
+            set_fields.push(quote! {
+                #name_str => { #set }
+            });
+        };
+
+        for field in &parsed.fields {
+            match field {
+                &FieldKind::Regular { ref ident, ref field } => {
+                    field_access(ident.as_ref().to_string(), ident.clone(), field);
+                },
+                &FieldKind::Array { ref ident, ref dimensions, ref elements, .. } => {
+                    for (i, field) in elements.iter().enumerate() {
+                        let name_str = array_element_name(ident, dimensions, i);
+                        field_access(name_str.clone(), syn::Ident::new(name_str), field);
+                    }
+                }
+            }
+        }
+    }
+
+    let result_ty = result_type();
+
+    quote! {
+        #[allow(unused_imports, unused_variables, unreachable_code)]
+        impl #impl_generics ::packed_struct::schema::PackedStructFieldAccess for #name #ty_generics #where_clause {
+            fn get_field<'a>(&'a self, name: &str) -> Option<::packed_struct::schema::FieldValue<'a>> {
+                use ::packed_struct::*;
+                use ::packed_struct::types::PackedString;
+                use ::packed_struct::schema::{FieldValue, FieldAccessError};
+
+                #(#nested_get)*
+
+                match name {
+                    #(#get_fields,)*
+                    _ => None
+                }
+            }
+
+            fn set_field(&mut self, name: &str, value: ::packed_struct::schema::FieldValue) -> #result_ty <(), ::packed_struct::schema::FieldAccessError> {
+                use ::packed_struct::*;
+                use ::packed_struct::types::PackedString;
+                use ::packed_struct::schema::{FieldValue, FieldAccessError};
+
+                #(#nested_set)*
+
+                match name {
+                    #(#set_fields,)*
+                    _ => { return Err(FieldAccessError::UnknownField); }
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+enum ValueKind {
+    Str,
+    Enum,
+    Bool,
+    Reserved,
+    /// A primitive integer, our `Integer` wrapper around one (1 wrapper), or an explicit
+    /// endianness wrapper around an `Integer` (2 wrappers)
+    Integer { primitive: syn::Ty, wrappers: usize, signed: bool },
+    Struct
+}
+
+fn value_kind(field: &FieldRegular) -> ValueKind {
+    let ty_str = syn_to_string(&field.ty);
+
+    let segment = match field.ty {
+        syn::Ty::Path(_, ref path) => path.segments.last(),
+        _ => None
+    };
+    let ty_ident = segment.map(|s| s.ident.as_ref().to_string()).unwrap_or_default();
+
+    let wrappers = &field.serialization_wrappers;
+    let is_str = wrappers.iter().any(|w| match w { &SerializationWrapper::StringWrapper { .. } => true, _ => false });
+    let is_enum = wrappers.iter().any(|w| match w { &SerializationWrapper::PrimitiveEnumWrapper { .. } => true, _ => false });
+    let int_types = ["u8", "i8", "u16", "i16", "u32", "i32", "u64", "i64"];
+
+    if is_str {
+        ValueKind::Str
+    } else if is_enum {
+        ValueKind::Enum
+    } else if ty_ident == "bool" {
+        ValueKind::Bool
+    } else if field.reserved.is_some() {
+        ValueKind::Reserved
+    } else if int_types.contains(&ty_ident.as_str()) {
+        ValueKind::Integer { primitive: field.ty.clone(), wrappers: 0, signed: ty_ident.starts_with('i') }
+    } else if ty_ident.ends_with("Integer") {
+        // the underlying primitive is the first parameter of the wrappers
+        let primitive = match segment.map(|s| &s.parameters) {
+            Some(&syn::PathParameters::AngleBracketed(ref params)) => params.types.first().cloned(),
+            _ => None
+        }.unwrap_or_else(|| panic!("Missing the primitive type of {}", ty_str));
+        let signed = syn_to_string(&primitive).starts_with('i');
+        let wrappers = if ty_ident == "Integer" { 1 } else { 2 };
+        ValueKind::Integer { primitive, wrappers, signed }
+    } else {
+        ValueKind::Struct
+    }
+}
@@ -0,0 +1,84 @@
+extern crate quote;
+extern crate syn;
+
+use pack::*;
+use common::*;
+
+
+/// Serde implementations that skip the reserved fields, requested with #[packed_struct(serde)].
+/// Deserialization goes through a structure with the other fields, the reserved ones are
+/// filled with their fixed bits.
+pub fn struct_serde(parsed: &PackStruct) -> quote::Tokens {
+    if !include_serde_codegen() || !parsed.serde {
+        return quote! {};
+    }
+
+    let (impl_generics, ty_generics, where_clause) = parsed.ast.generics.split_for_impl();
+    let mut de_generics = parsed.ast.generics.clone();
+    de_generics.lifetimes.insert(0, syn::LifetimeDef::new("'de"));
+    let (de_impl_generics, _, _) = de_generics.split_for_impl();
+
+    let name = &parsed.ast.ident;
+    let name_str = name.as_ref().to_string();
+    let result_ty = result_type();
+    let core = core_prefix();
+
+    let ast_fields = match parsed.ast.body {
+        syn::Body::Struct(syn::VariantData::Struct(ref fields)) => fields,
+        _ => panic!("#[derive(PackedStruct)] can only be used with braced structs")
+    };
+
+    let mut reserved = vec![];
+    let mut idents = vec![];
+    let mut types = vec![];
+    for field in &parsed.fields {
+        let (ident, is_reserved) = match field {
+            &FieldKind::Regular { ref ident, ref field } => (ident, field.reserved.is_some()),
+            &FieldKind::Array { ref ident, ref elements, .. } => (ident, elements.iter().all(|e| e.reserved.is_some()))
+        };
+
+        if is_reserved {
+            reserved.push(ident.clone());
+        } else {
+            let ty = ast_fields.iter().find(|f| f.ident.as_ref() == Some(ident)).map(|f| f.ty.clone()).expect("missing field");
+            idents.push(ident.clone());
+            types.push(ty);
+        }
+    }
+    let names: Vec<_> = idents.iter().map(|i| i.as_ref().to_string()).collect();
+    let num_fields = idents.len();
+
+    let idents_ser = idents.clone();
+    let idents_de = idents.clone();
+    let idents_set = idents.clone();
+
+    quote! {
+        impl #impl_generics ::packed_struct::serde::Serialize for #name #ty_generics #where_clause {
+            fn serialize<S>(&self, serializer: S) -> #result_ty<S::Ok, S::Error> where S: ::packed_struct::serde::Serializer {
+                use ::packed_struct::serde::ser::SerializeStruct;
+
+                let mut state = serializer.serialize_struct(#name_str, #num_fields)?;
+                #( state.serialize_field(#names, &self.#idents_ser)?; )*
+                state.end()
+            }
+        }
+
+        impl #de_impl_generics ::packed_struct::serde::Deserialize<'de> for #name #ty_generics #where_clause {
+            fn deserialize<D>(deserializer: D) -> #result_ty<Self, D::Error> where D: ::packed_struct::serde::Deserializer<'de> {
+                #[derive(::packed_struct::serde_derive::Deserialize)]
+                #[serde(crate = "::packed_struct::serde", rename = #name_str)]
+                struct Fields #impl_generics #where_clause {
+                    #( #idents_de: #types, )*
+                    #[serde(skip)]
+                    _marker: #core::marker::PhantomData<#name #ty_generics>
+                }
+
+                let fields = <Fields #ty_generics as ::packed_struct::serde::Deserialize>::deserialize(deserializer)?;
+                Ok(#name {
+                    #( #idents_set: fields.#idents, )*
+                    #( #reserved: Default::default() ),*
+                })
+            }
+        }
+    }
+}
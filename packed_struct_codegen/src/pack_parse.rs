@@ -16,12 +16,19 @@ pub fn parse_sub_attributes(attributes: &Vec<syn::Attribute>, main_attribute: &s
             if ident.as_ref() != main_attribute { continue; }
 
             for item in list {
-                if let &syn::NestedMetaItem::MetaItem(syn::MetaItem::NameValue(ref ident, ref lit)) = item {
-                    let n = ident.as_ref();
-                    
-                    if let &syn::Lit::Str(ref v, _) = lit {
-                        r.push((n.to_string(), v.to_string()));
-                    }
+                match item {
+                    &syn::NestedMetaItem::MetaItem(syn::MetaItem::NameValue(ref ident, ref lit)) => {
+                        let n = ident.as_ref();
+
+                        if let &syn::Lit::Str(ref v, _) = lit {
+                            r.push((n.to_string(), v.to_string()));
+                        }
+                    },
+                    // flags, like #[packed_struct(schema)]
+                    &syn::NestedMetaItem::MetaItem(syn::MetaItem::Word(ref ident)) => {
+                        r.push((ident.as_ref().to_string(), String::new()));
+                    },
+                    _ => ()
                 }
             }
         }
@@ -77,6 +84,21 @@ fn get_builtin_type_bit_width(p: &syn::PathSegment) -> Option<usize> {
         "u16" | "i16" => Some(16),
         "u32" | "i32" => Some(32),
         "u64" | "i64" => Some(64),
+        "FixedString" => {
+            match p.parameters {
+                ::syn::PathParameters::AngleBracketed(ref params) => {
+                    // only the BytesN types of the library, the others need an explicit position
+                    params.types.iter().filter_map(|t| match *t {
+                        syn::Ty::Path(None, ref path) => path.segments.last(),
+                        _ => None
+                    }).filter_map(|s| {
+                        let ident = s.ident.as_ref();
+                        if ident.starts_with("Bytes") { ident[5..].parse::<usize>().ok() } else { None }
+                    }).next().map(|bytes| bytes * 8)
+                },
+                _ => None
+            }
+        },
         "ReservedZero" | "ReservedZeroes" | "ReservedOne" | "ReservedOnes" |
         "Integer" => {
             match p.parameters {
@@ -107,41 +129,24 @@ fn get_builtin_type_bit_width(p: &syn::PathSegment) -> Option<usize> {
 fn get_field_mid_positioning(field: &syn::Field) -> FieldMidPositioning {
     
     let mut array_size = 1;
+    let mut is_array = false;
     let bit_width_builtin: Option<usize>;
 
-    let _ty = match field.ty {
+    match field.ty {
         syn::Ty::Path (None, syn::Path { ref segments, .. }) => {
             if segments.len() == 1 {                
                 let ref segment = segments[0];
 
                 bit_width_builtin = get_builtin_type_bit_width(segment);
-                segment.clone()
             } else {
                 panic!("Unsupported path type: {:#?}", field.ty);
             }
         },
-        syn::Ty::Array(ref ty, ref size) => {
-        
-            if let syn::Ty::Path (None, syn::Path { ref segments, .. }) = **ty {
-                if segments.len() == 1 {
-                    if let &syn::ConstExpr::Lit(syn::Lit::Int(size, _)) = size {
-                        let ref segment = segments[0];
-                        bit_width_builtin = get_builtin_type_bit_width(segment);
-                        array_size = size as usize;
-
-                        if size == 0 { panic!("Arrays sized 0 are not supported."); }
-                        
-                        segment.clone()
-                    } else {
-                        panic!("unsupported array size: {:?}", size);
-                    }
-                } else {
-                    panic!("Unsupported path type: {:#?}", ty);
-                }
-            } else {
-                panic!("Unsupported path type: {:#?}", ty);
-            }
-
+        syn::Ty::Array(..) => {
+            let (dimensions, segment) = parse_array_type(&field.ty);
+            bit_width_builtin = get_builtin_type_bit_width(segment);
+            array_size = dimensions.iter().product();
+            is_array = true;
         },
         _ => { panic!("Unsupported type: {:?}", field.ty); }
     };
@@ -153,23 +158,77 @@ fn get_field_mid_positioning(field: &syn::Field) -> FieldMidPositioning {
         _ => None
     }).next().unwrap_or(BitsPositionParsed::Next);
     
+    let element_stride_bits = field_attributes.iter().filter_map(|a| if let &PackFieldAttribute::ElementStrideBits(bits) = a { Some(bits) } else { None }).next();
+    let element_padding_bits = field_attributes.iter().filter_map(|a| if let &PackFieldAttribute::ElementPaddingBits(bits) = a { Some(bits) } else { None }).next();
+    let element_order = field_attributes.iter().filter_map(|a| if let &PackFieldAttribute::ElementOrder(o) = a { Some(o) } else { None }).next();
+
+    if !is_array && (element_stride_bits.is_some() || element_padding_bits.is_some() || element_order.is_some()) {
+        panic!("Element stride, padding and order attributes are only supported on arrays.");
+    }
+    if element_stride_bits.is_some() && element_padding_bits.is_some() {
+        panic!("Please specify either the element stride or the element padding, not both.");
+    }
+
+    let element_size_bits = field_attributes.iter().filter_map(|a| if let &PackFieldAttribute::ElementSizeBits(bits) = a { Some(bits) } else { None }).next();
+    let has_element_spacing = element_stride_bits.is_some() || element_padding_bits.is_some();
+    let element_padding_bits = element_padding_bits.unwrap_or(0);
+
     let bit_width = if let Some(bits) = field_attributes.iter().filter_map(|a| if let &PackFieldAttribute::SizeBits(bits) = a { Some(bits) } else { None }).next() {
         if array_size > 1 { panic!("Please use the 'element_size_bits' or 'element_size_bytes' for arrays."); }
         bits
-    } else if let Some(bits) = field_attributes.iter().filter_map(|a| if let &PackFieldAttribute::ElementSizeBits(bits) = a { Some(bits) } else { None }).next() {
-        bits * array_size
+    } else if let Some(bits) = element_size_bits {
+        element_stride_bits.unwrap_or(bits + element_padding_bits) * array_size
     } else if let BitsPositionParsed::Range(a, b) = bits_position {
         (b as isize - a as isize).abs() as usize + 1
     } else if let Some(bit_width_builtin) = bit_width_builtin {
         // todo: is it even possible to hit this branch?
-        bit_width_builtin * array_size
+        element_stride_bits.unwrap_or(bit_width_builtin + element_padding_bits) * array_size
     } else {
         panic!("Couldn't determine the width of this field: {:?}", field);
     };
 
+    let array_layout = if is_array {
+        let element_stride_bits = match element_stride_bits {
+            Some(stride) => stride,
+            None => {
+                if (bit_width % array_size) != 0 {
+                    panic!("element and array size mismatch!");
+                }
+                bit_width / array_size
+            }
+        };
+
+        let element_bit_width = match (element_size_bits, bit_width_builtin) {
+            (Some(bits), _) => bits,
+            (None, Some(bits)) if has_element_spacing => bits,
+            _ => {
+                if element_padding_bits >= element_stride_bits {
+                    panic!("The element padding of {} bits leaves no space for the elements.", element_padding_bits);
+                }
+                element_stride_bits - element_padding_bits
+            }
+        };
+
+        if element_bit_width > element_stride_bits {
+            panic!("The array elements of {} bits are wider than their stride of {} bits.", element_bit_width, element_stride_bits);
+        }
+        if (element_stride_bits * (array_size - 1)) + element_bit_width > bit_width {
+            panic!("The array elements don't fit into the {} bits of the field.", bit_width);
+        }
+
+        Some(ArrayLayout {
+            element_bit_width,
+            element_stride_bits,
+            reverse: element_order == Some(ElementOrder::Reverse)
+        })
+    } else {
+        None
+    };
+
     FieldMidPositioning {
         bit_width: bit_width,
-        bits_position: bits_position
+        bits_position: bits_position,
+        array_layout
     }
 }
 
@@ -190,34 +249,28 @@ fn parse_field(field: &syn::Field, mp: &FieldMidPositioning, bit_range: &Range<u
                 panic!("huh 1x");
             }
         },
-        syn::Ty::Array(ref ty, ref size) => {
-        
-            if let syn::Ty::Path (None, syn::Path { ref segments, .. }) = **ty {
-                if segments.len() == 1 {
-                    if let &syn::ConstExpr::Lit(syn::Lit::Int(size, _)) = size {
-                        let ty = syn::parse_type(&syn_to_string(&segments[0])).expect("error parsing path segment to ty");
-                                                
-                        let element_size_bits: usize = mp.bit_width as usize / size as usize;
-                        if (mp.bit_width % element_size_bits) != 0 {
-                            panic!("element and array size mismatch!");
-                        }
-
-                        let mut elements = vec![];
-                        for i in 0..size as usize {
-                            let s = bit_range.start + (i * element_size_bits);
-                            let element_bit_range = s..(s + element_size_bits - 1);
-                            elements.push(parse_reg_field(field, &ty, &element_bit_range, default_endianness));
-                            //panic!("field: {:#?}, mp: {:#?}, bit_range: {:#?}", field, mp, bit_range);
-                        }
-                        
-                        return FieldKind::Array {
-                            ident: field.ident.clone().expect("mah ident?"),
-                            size: size as usize,
-                            elements: elements
-                        };
-                    }
-                }
+        syn::Ty::Array(..) => {
+            let (dimensions, segment) = parse_array_type(&field.ty);
+            let ty = syn::parse_type(&syn_to_string(segment)).expect("error parsing path segment to ty");
+
+            let layout = mp.array_layout.expect("missing array layout");
+            let size = dimensions.iter().product();
+
+            // multi-dimensional arrays are laid out as a flat array, the last index changing the fastest
+            let mut elements = vec![];
+            for i in 0..size {
+                let position = if layout.reverse { size - 1 - i } else { i };
+                let s = bit_range.start + (position * layout.element_stride_bits);
+                let element_bit_range = s..(s + layout.element_bit_width - 1);
+                elements.push(parse_reg_field(field, &ty, &element_bit_range, default_endianness));
             }
+
+            return FieldKind::Array {
+                ident: field.ident.clone().expect("mah ident?"),
+                dimensions: dimensions,
+                elements: elements,
+                bit_range: bit_range.clone()
+            };
         },
         _ => {  }
     };
@@ -225,6 +278,27 @@ fn parse_field(field: &syn::Field, mp: &FieldMidPositioning, bit_range: &Range<u
     panic!("Field not supported: {:?}", field);
 }
 
+/// Dimensions of a possibly nested array type, outermost first, and the type of its elements.
+fn parse_array_type(ty: &syn::Ty) -> (Vec<usize>, &syn::PathSegment) {
+    match *ty {
+        syn::Ty::Array(ref ty, ref size) => {
+            let size = match *size {
+                syn::ConstExpr::Lit(syn::Lit::Int(size, _)) => size as usize,
+                _ => panic!("unsupported array size: {:?}", size)
+            };
+            if size == 0 { panic!("Arrays sized 0 are not supported."); }
+
+            let (mut dimensions, segment) = parse_array_type(ty);
+            dimensions.insert(0, size);
+            (dimensions, segment)
+        },
+        syn::Ty::Path (None, syn::Path { ref segments, .. }) if segments.len() == 1 => {
+            (vec![], &segments[0])
+        },
+        _ => panic!("Unsupported path type: {:#?}", ty)
+    }
+}
+
 fn parse_reg_field(field: &syn::Field, ty: &syn::Ty, bit_range: &Range<usize>, default_endianness: Option<IntegerEndianness>) -> FieldRegular {
     let mut wrappers = vec![];
 
@@ -233,13 +307,49 @@ fn parse_reg_field(field: &syn::Field, ty: &syn::Ty, bit_range: &Range<usize>, d
     let field_attributes = PackFieldAttribute::parse_all(&parse_sub_attributes(&field.attrs, "packed_field"));
 
     let is_enum_ty = field_attributes.iter().filter_map(|a| match a {
-        &PackFieldAttribute::Ty(TyKind::Enum) => Some(()),
+        &PackFieldAttribute::Ty(TyKind::Enum) | &PackFieldAttribute::Ty(TyKind::ExhaustiveEnum) => Some(()),
         _ => None
     }).next().is_some();    
 
+    let is_exhaustive_enum_ty = field_attributes.iter().any(|a| match a {
+        &PackFieldAttribute::Ty(TyKind::ExhaustiveEnum) => true,
+        _ => false
+    });
+
+    let is_str_ty = field_attributes.iter().any(|a| match a {
+        &PackFieldAttribute::Ty(TyKind::Str) => true,
+        _ => false
+    });
+
+    let string_padding = field_attributes.iter().filter_map(|a| match a {
+        &PackFieldAttribute::StringPadding(p) => Some(p),
+        _ => None
+    }).next();
+
+    if is_str_ty {
+        if (bit_width % 8) != 0 {
+            panic!("Text fields have to occupy whole bytes, {} has {} bits.", ty_str, bit_width);
+        }
+
+        wrappers.push(SerializationWrapper::StringWrapper { padding: string_padding.unwrap_or(StringPadding::Nul) });
+
+        return FieldRegular {
+            ty: ty.clone(),
+            serialization_wrappers: wrappers,
+            bit_width: bit_width,
+            bit_range: bit_range.clone(),
+            bit_range_rust: bit_range.start..(bit_range.end + 1),
+            reserved: None
+        };
+    } else if string_padding.is_some() {
+        panic!("The padding attribute is only supported on text fields, with ty=\"str\".");
+    }
+
+    let bool_wrapper = parse_bool_wrapper(&ty_str, bit_width, &field_attributes);
+
     let needs_int_wrap = {
         let int_types = ["u8", "i8", "u16", "i16", "u32", "i32", "u64", "i64"];
-        is_enum_ty || int_types.iter().any(|t| t == &ty_str)
+        is_enum_ty || bool_wrapper.is_some() || int_types.iter().any(|t| t == &ty_str)
     };
 
     let needs_endiannes_wrap = {
@@ -248,12 +358,18 @@ fn parse_reg_field(field: &syn::Field, ty: &syn::Ty, bit_range: &Range<usize>, d
     };
     
     if is_enum_ty {
-        wrappers.push(SerializationWrapper::PrimitiveEnumWrapper);
+        wrappers.push(SerializationWrapper::PrimitiveEnumWrapper { exhaustive: is_exhaustive_enum_ty });
+    }
+
+    if let Some(ref bool_wrapper) = bool_wrapper {
+        wrappers.push(bool_wrapper.clone());
     }
 
     if needs_int_wrap {
         let ty = if is_enum_ty {
             format!("<{} as PrimitiveEnum>::Primitive",syn_to_string(ty))
+        } else if let Some(SerializationWrapper::BoolWrapper { ref primitive, .. }) = bool_wrapper {
+            syn_to_string(primitive)
         } else {
             ty_str.clone()
         };
@@ -297,12 +413,97 @@ fn parse_reg_field(field: &syn::Field, ty: &syn::Ty, bit_range: &Range<usize>, d
         serialization_wrappers: wrappers,
         bit_width: bit_width,
         bit_range: bit_range.clone(),
-        bit_range_rust: bit_range.start..(bit_range.end + 1)
+        bit_range_rust: bit_range.start..(bit_range.end + 1),
+        reserved: parse_reserved_value(ty)
+    }
+}
+
+/// The value of the library's reserved types, by their exact names.
+pub fn parse_reserved_value(ty: &syn::Ty) -> Option<ReservedValue> {
+    let segment = match *ty {
+        syn::Ty::Path(None, ref path) => path.segments.last(),
+        _ => None
+    };
+    let segment = match segment {
+        Some(segment) => segment,
+        None => return None
+    };
+
+    match segment.ident.as_ref() {
+        "ReservedZero" | "ReservedZeroes" => Some(ReservedValue::Zero),
+        "ReservedOne" | "ReservedOnes" => Some(ReservedValue::One),
+        "ReservedBits" => {
+            let value = match segment.parameters {
+                syn::PathParameters::AngleBracketed(ref params) => params.types.first().map(syn_to_string),
+                _ => None
+            };
+            match value.as_ref().map(|v| v.as_str()) {
+                Some("BitOne") => Some(ReservedValue::One),
+                _ => Some(ReservedValue::Zero)
+            }
+        },
+        _ => None
     }
 }
 
 
 
+/// Booleans that occupy more than a single bit, or that have a custom polarity,
+/// are packed as an integer with a pattern for each state.
+fn parse_bool_wrapper(ty_str: &str, bit_width: usize, field_attributes: &[PackFieldAttribute]) -> Option<SerializationWrapper> {
+    let true_value = field_attributes.iter().filter_map(|a| if let &PackFieldAttribute::BoolTrue(v) = a { Some(v) } else { None }).next();
+    let false_value = field_attributes.iter().filter_map(|a| if let &PackFieldAttribute::BoolFalse(v) = a { Some(v) } else { None }).next();
+    let active = field_attributes.iter().filter_map(|a| if let &PackFieldAttribute::BoolActive(v) = a { Some(v) } else { None }).next();
+    let mode = field_attributes.iter().filter_map(|a| if let &PackFieldAttribute::BoolMode(v) = a { Some(v) } else { None }).next();
+
+    let has_bool_attributes = true_value.is_some() || false_value.is_some() || active.is_some() || mode.is_some();
+
+    if ty_str != "bool" {
+        if has_bool_attributes {
+            panic!("Boolean packing attributes are only supported on bool fields, found on {}.", ty_str);
+        }
+        return None;
+    }
+
+    if bit_width == 1 && !has_bool_attributes {
+        return None;
+    }
+
+    if bit_width > 64 {
+        panic!("Boolean fields can occupy at most 64 bits, this one has {}.", bit_width);
+    }
+
+    let all_ones = if bit_width == 64 { !0 } else { (1u64 << bit_width) - 1 };
+    let (default_true, default_false) = match active.unwrap_or(BoolActive::High) {
+        BoolActive::High => (all_ones, 0),
+        BoolActive::Low => (0, all_ones)
+    };
+
+    let true_value = true_value.unwrap_or(default_true);
+    let false_value = false_value.unwrap_or(default_false);
+
+    if true_value == false_value {
+        panic!("The true and false patterns of a boolean field have to differ, both are {:#b}.", true_value);
+    }
+    if (true_value & !all_ones) != 0 || (false_value & !all_ones) != 0 {
+        panic!("Boolean patterns {:#b} and {:#b} don't fit into {} bits.", true_value, false_value, bit_width);
+    }
+
+    let primitive = match bit_width {
+        0..=8 => "u8",
+        9..=16 => "u16",
+        17..=32 => "u32",
+        _ => "u64"
+    };
+
+    Some(SerializationWrapper::BoolWrapper {
+        primitive: syn::parse_type(primitive).unwrap(),
+        true_value,
+        false_value,
+        non_zero: mode == Some(BoolMode::NonZero)
+    })
+}
+
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum BitsPositionParsed {
     Next,
@@ -331,6 +532,8 @@ pub fn parse_num(s: &str) -> usize {
 
     if s.starts_with("0x") || s.starts_with("0X") {
         usize::from_str_radix(&s[2..], 16).expect(&format!("Invalid hex number: {:?}", s))
+    } else if s.starts_with("0b") || s.starts_with("0B") {
+        usize::from_str_radix(&s[2..].replace("_", ""), 2).expect(&format!("Invalid binary number: {:?}", s))
     } else {
         s.parse().expect(&format!("Invalid decimal number: {:?}", s))
     }
@@ -373,6 +576,46 @@ pub fn parse_struct(ast: &syn::MacroInput) -> PackStruct {
 
 
 
+    let register_address = attributes.iter().filter_map(|a| match a {
+        &PackStructAttribute::Address(address) => Some(address),
+        _ => None
+    }).next();
+
+    let register_access = attributes.iter().filter_map(|a| match a {
+        &PackStructAttribute::Access(access) => Some(access),
+        _ => None
+    }).next();
+
+    let register = match (register_address, register_access) {
+        (Some(address), access) => Some(PackRegister {
+            address: address,
+            access: access.unwrap_or(RegisterAccess::ReadWrite)
+        }),
+        (None, Some(_)) => panic!("The register access of {} also requires its address, like #[packed_struct(address=\"0x10\")].", ast.ident),
+        (None, None) => None
+    };
+
+    let proptest = attributes.iter().any(|a| match a {
+        &PackStructAttribute::Proptest => true,
+        _ => false
+    });
+
+    // the strategy is driven by the schema
+    let schema = proptest || attributes.iter().any(|a| match a {
+        &PackStructAttribute::Schema => true,
+        _ => false
+    });
+
+    let serde = attributes.iter().any(|a| match a {
+        &PackStructAttribute::Serde => true,
+        _ => false
+    });
+
+    let debug_write = attributes.iter().any(|a| match a {
+        &PackStructAttribute::DebugWrite => true,
+        _ => false
+    });
+
     let first_field_is_auto_positioned = {
         if let Some(ref field) = fields.first() {
             let mp = get_field_mid_positioning(field);
@@ -421,7 +664,7 @@ pub fn parse_struct(ast: &syn::MacroInput) -> PackStruct {
         } else {
             let last_bit = fields_parsed.iter().map(|f| match f {
                 &FieldKind::Regular { ref field, .. } => field.bit_range_rust.end,
-                &FieldKind::Array { ref elements, .. } => elements.last().unwrap().bit_range_rust.end
+                &FieldKind::Array { ref bit_range, .. } => bit_range.end + 1
             }).max().unwrap();
             last_bit
         }
@@ -451,9 +694,9 @@ pub fn parse_struct(ast: &syn::MacroInput) -> PackStruct {
                 &FieldKind::Regular { ref field, ref ident } => {
                     find_overlaps(syn_to_string(ident), &field.bit_range);
                 },
-                &FieldKind::Array { ref ident, ref elements, .. } => {
+                &FieldKind::Array { ref ident, ref dimensions, ref elements, .. } => {
                     for (i, field) in elements.iter().enumerate() {
-                        find_overlaps(format!("{}[{}]", syn_to_string(ident), i), &field.bit_range);
+                        find_overlaps(array_element_name(ident, dimensions, i), &field.bit_range);
                     }
                 }
             }
@@ -464,7 +707,12 @@ pub fn parse_struct(ast: &syn::MacroInput) -> PackStruct {
         ast: ast.clone(),
         fields: fields_parsed,
         num_bytes: num_bytes,
-        num_bits: num_bits
+        num_bits: num_bits,
+        register: register,
+        schema: schema,
+        proptest: proptest,
+        serde: serde,
+        debug_write: debug_write
     }
 }
 
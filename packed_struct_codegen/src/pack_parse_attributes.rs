@@ -1,3 +1,4 @@
+use pack::RegisterAccess;
 use pack_parse::*;
 
 #[derive(Clone, Copy)]
@@ -5,7 +6,13 @@ pub enum PackStructAttributeKind {
     SizeBytes,
     //SizeBits,
     DefaultIntEndianness,
-    BitNumbering
+    BitNumbering,
+    Address,
+    Access,
+    Schema,
+    Proptest,
+    Serde,
+    DebugWrite
 }
 
 impl PackStructAttributeKind {
@@ -16,7 +23,13 @@ impl PackStructAttributeKind {
             SizeBytes => "size_bytes",
             //SizeBits => "size_bits",
             DefaultIntEndianness => "endian",
-            BitNumbering => "bit_numbering"
+            BitNumbering => "bit_numbering",
+            Address => "address",
+            Access => "access",
+            Schema => "schema",
+            Proptest => "proptest",
+            Serde => "serde",
+            DebugWrite => "debug_write"
         }
     }
 }
@@ -25,7 +38,13 @@ pub enum PackStructAttribute {
     SizeBytes(usize),
     //SizeBits(usize),
     DefaultIntEndianness(IntegerEndianness),
-    BitNumbering(BitNumbering)
+    BitNumbering(BitNumbering),
+    Address(u64),
+    Access(RegisterAccess),
+    Schema,
+    Proptest,
+    Serde,
+    DebugWrite
 }
 
 impl PackStructAttribute {
@@ -44,6 +63,31 @@ impl PackStructAttribute {
             return Ok(PackStructAttribute::SizeBytes(b));
         }
 
+        if name == PackStructAttributeKind::Address.get_attr_name() {
+            return Ok(PackStructAttribute::Address(parse_num(val) as u64));
+        }
+
+        if name == PackStructAttributeKind::Access.get_attr_name() {
+            let a = RegisterAccess::from_str(val).expect(&format!("Invalid register access value: {}, expected \"ro\", \"wo\" or \"rw\"", val));
+            return Ok(PackStructAttribute::Access(a));
+        }
+
+        if name == PackStructAttributeKind::Schema.get_attr_name() {
+            return Ok(PackStructAttribute::Schema);
+        }
+
+        if name == PackStructAttributeKind::Proptest.get_attr_name() {
+            return Ok(PackStructAttribute::Proptest);
+        }
+
+        if name == PackStructAttributeKind::Serde.get_attr_name() {
+            return Ok(PackStructAttribute::Serde);
+        }
+
+        if name == PackStructAttributeKind::DebugWrite.get_attr_name() {
+            return Ok(PackStructAttribute::DebugWrite);
+        }
+
         /*
         if name == PackStructAttributeKind::SizeBits.get_attr_name() {
             let b = parse_num(val);
@@ -72,9 +116,19 @@ pub enum PackFieldAttributeKind {
     BytePosition,
     ElementSizeBytes,
     ElementSizeBits,
+    ElementStrideBytes,
+    ElementStrideBits,
+    ElementPaddingBytes,
+    ElementPaddingBits,
+    ElementOrder,
     SizeBytes,
     SizeBits,
-    Ty
+    Ty,
+    BoolTrue,
+    BoolFalse,
+    BoolActive,
+    BoolMode,
+    StringPadding
 }
 
 impl PackFieldAttributeKind {
@@ -89,7 +143,17 @@ impl PackFieldAttributeKind {
             SizeBits => "size_bits",
             ElementSizeBytes => "element_size_bytes",
             ElementSizeBits => "element_size_bits",
-            Ty => "ty"
+            ElementStrideBytes => "element_stride_bytes",
+            ElementStrideBits => "element_stride_bits",
+            ElementPaddingBytes => "element_padding_bytes",
+            ElementPaddingBits => "element_padding_bits",
+            ElementOrder => "element_order",
+            Ty => "ty",
+            BoolTrue => "bool_true",
+            BoolFalse => "bool_false",
+            BoolActive => "active",
+            BoolMode => "bool_mode",
+            StringPadding => "padding"
         }
     }
 }
@@ -100,11 +164,54 @@ pub enum PackFieldAttribute {
     BytePosition(BitsPositionParsed),
     SizeBits(usize),
     ElementSizeBits(usize),
-    Ty(TyKind)
+    ElementStrideBits(usize),
+    ElementPaddingBits(usize),
+    ElementOrder(ElementOrder),
+    Ty(TyKind),
+    BoolTrue(u64),
+    BoolFalse(u64),
+    BoolActive(BoolActive),
+    BoolMode(BoolMode),
+    StringPadding(StringPadding)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+/// Order of the elements of a packed array.
+pub enum ElementOrder {
+    /// The first element occupies the lowest bits.
+    Forward,
+    /// The first element occupies the highest bits.
+    Reverse
 }
 
 pub enum TyKind {
-    Enum
+    Enum,
+    ExhaustiveEnum,
+    Str
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+/// Layout of the unused bytes in a text field.
+pub enum StringPadding {
+    Nul,
+    Space,
+    NulTerminated
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+/// Polarity of a packed boolean.
+pub enum BoolActive {
+    High,
+    Low
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+/// How strictly are the unpacked boolean bits matched.
+pub enum BoolMode {
+    /// Only the true and false patterns are accepted.
+    Strict,
+    /// Anything but the false pattern unpacks as true.
+    NonZero
 }
 
 impl PackFieldAttribute {
@@ -143,9 +250,74 @@ impl PackFieldAttribute {
             return Ok(PackFieldAttribute::ElementSizeBits(b));
         }
 
+        if name == PackFieldAttributeKind::ElementStrideBytes.get_attr_name() {
+            let b = parse_num(val);
+            return Ok(PackFieldAttribute::ElementStrideBits(b * 8));
+        }
+
+        if name == PackFieldAttributeKind::ElementStrideBits.get_attr_name() {
+            let b = parse_num(val);
+            return Ok(PackFieldAttribute::ElementStrideBits(b));
+        }
+
+        if name == PackFieldAttributeKind::ElementPaddingBytes.get_attr_name() {
+            let b = parse_num(val);
+            return Ok(PackFieldAttribute::ElementPaddingBits(b * 8));
+        }
+
+        if name == PackFieldAttributeKind::ElementPaddingBits.get_attr_name() {
+            let b = parse_num(val);
+            return Ok(PackFieldAttribute::ElementPaddingBits(b));
+        }
+
+        if name == PackFieldAttributeKind::ElementOrder.get_attr_name() {
+            match val.to_lowercase().as_str() {
+                "forward" => { return Ok(PackFieldAttribute::ElementOrder(ElementOrder::Forward)); },
+                "reverse" => { return Ok(PackFieldAttribute::ElementOrder(ElementOrder::Reverse)); },
+                _ => panic!("Invalid element order value: {}. Supported values: forward, reverse.", val)
+            }
+        }
+
+        if name == PackFieldAttributeKind::BoolTrue.get_attr_name() {
+            let b = parse_num(val);
+            return Ok(PackFieldAttribute::BoolTrue(b as u64));
+        }
+
+        if name == PackFieldAttributeKind::BoolFalse.get_attr_name() {
+            let b = parse_num(val);
+            return Ok(PackFieldAttribute::BoolFalse(b as u64));
+        }
+
+        if name == PackFieldAttributeKind::BoolActive.get_attr_name() {
+            match val.to_lowercase().as_str() {
+                "high" => { return Ok(PackFieldAttribute::BoolActive(BoolActive::High)); },
+                "low" => { return Ok(PackFieldAttribute::BoolActive(BoolActive::Low)); },
+                _ => panic!("Invalid boolean active value: {}. Supported values: high, low.", val)
+            }
+        }
+
+        if name == PackFieldAttributeKind::BoolMode.get_attr_name() {
+            match val.to_lowercase().as_str() {
+                "strict" => { return Ok(PackFieldAttribute::BoolMode(BoolMode::Strict)); },
+                "nonzero" => { return Ok(PackFieldAttribute::BoolMode(BoolMode::NonZero)); },
+                _ => panic!("Invalid boolean mode value: {}. Supported values: strict, nonzero.", val)
+            }
+        }
+
+        if name == PackFieldAttributeKind::StringPadding.get_attr_name() {
+            match val.to_lowercase().as_str() {
+                "nul" => { return Ok(PackFieldAttribute::StringPadding(StringPadding::Nul)); },
+                "space" => { return Ok(PackFieldAttribute::StringPadding(StringPadding::Space)); },
+                "nul_terminated" => { return Ok(PackFieldAttribute::StringPadding(StringPadding::NulTerminated)); },
+                _ => panic!("Invalid string padding value: {}. Supported values: nul, space, nul_terminated.", val)
+            }
+        }
+
         if name == PackFieldAttributeKind::Ty.get_attr_name() {
             match val {
                 "enum" => { return Ok(PackFieldAttribute::Ty(TyKind::Enum)); },
+                "exhaustive_enum" => { return Ok(PackFieldAttribute::Ty(TyKind::ExhaustiveEnum)); },
+                "str" => { return Ok(PackFieldAttribute::Ty(TyKind::Str)); },
                 _ => ()
             }
         }
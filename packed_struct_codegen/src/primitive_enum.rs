@@ -1,6 +1,8 @@
 extern crate quote;
 extern crate syn;
 
+use std::convert::TryFrom;
+
 use utils::*;
 use common::collections_prefix;
 
@@ -12,37 +14,23 @@ pub fn derive(ast: &syn::DeriveInput, mut prim_type: Option<syn::Ty>) -> quote::
     let v = get_unitary_enum(ast);
     //panic!("v: {:?}", v);
 
-    let from_primitive_match: Vec<_> = v.iter().map(|x| {
-        let d = x.discriminant;
-        let d = syn::Lit::Int(d, syn::IntTy::Unsuffixed);
-        let negative = if x.negative {
-            quote! { - }
-        } else {
-            quote! {}
-        };
-
-        let n = &x.variant.ident;
-        quote! {
-            #negative #d => Some(#name::#n)
-    }}).collect();
-
     let to_display_str: Vec<_> = v.iter().map(|x| {
         let n = &x.variant.ident;
-        let d = n.as_ref().to_string();
+        let d = &x.name;
         quote! {
             #name::#n => (#d)
     }}).collect();
 
     let from_str: Vec<_> = v.iter().map(|x| {
         let n = &x.variant.ident;
-        let d = n.as_ref().to_string();
+        let d = &x.name;
         quote! {
             #d => Some(#name::#n)
     }}).collect();
 
     let from_str_lower: Vec<_> = v.iter().map(|x| {
         let n = &x.variant.ident;
-        let d = n.as_ref().to_string().to_lowercase();
+        let d = x.name.to_lowercase();
         quote! {
             #d => Some(#name::#n)
     }}).collect();
@@ -53,39 +41,43 @@ pub fn derive(ast: &syn::DeriveInput, mut prim_type: Option<syn::Ty>) -> quote::
     }).collect();
     let all_variants_len = all_variants.len();
 
+    let schema_variants: Vec<_> = v.iter().map(|x| {
+        let n = &x.variant.ident;
+        let d = n.as_ref().to_string();
+        let doc = doc_comment(&x.variant.attrs);
+        quote! {
+            ::packed_struct::schema::PrimitiveEnumVariantSchema {
+                name: #d,
+                value: #name::#n as i128,
+                doc: #doc
+            }
+        }
+    }).collect();
+
+    let attr_type = primitive_type_attribute(&ast.attrs);
+    if let (&Some(ref derived), &Some(ref attr)) = (&prim_type, &attr_type) {
+        if derived != attr {
+            panic!("The primitive type of the enum {} is declared both as {} and {}.", name,
+                ::pack_parse::syn_to_string(derived), ::pack_parse::syn_to_string(attr));
+        }
+    }
+    prim_type = prim_type.or(attr_type);
+
     if prim_type.is_none() {
-        let min_ty: Vec<_> = v.iter().map(|d| {
+        let mut min_ty: Vec<_> = v.iter().map(|d| {
             if d.int_ty != syn::IntTy::Isize && d.int_ty != syn::IntTy::Usize && d.int_ty != syn::IntTy::Unsuffixed {
                 d.int_ty
             } else {
-                if d.negative {
-                    let n = d.discriminant as i64;
-                    if n < <i32>::min_value() as i64 {
-                        syn::IntTy::I64
-                    } else {
-                        let n = -n;
-                        if n < <i16>::min_value() as i64 {
-                            syn::IntTy::I32
-                        } else if n < <i8>::min_value() as i64 {
-                            syn::IntTy::I16
-                        } else {
-                            syn::IntTy::I8
-                        }
-                    }
-                } else {
-                    let n = d.discriminant as u64;
-                    if n > <u32>::max_value() as u64 {
-                        syn::IntTy::U64
-                    } else if n > <u16>::max_value() as u64 {
-                        syn::IntTy::U32
-                    } else if n > <u8>::max_value() as u64 {
-                        syn::IntTy::U16
-                    } else {
-                        syn::IntTy::U8
-                    }
+                match d.discriminant {
+                    Some(n) => smallest_int_ty(n),
+                    None => panic!("The primitive type of the enum {} can't be detected from the discriminant of {}. \
+                        Declare it with #[repr(u8)] or #[primitive(u8)].", name, d.variant.ident)
                 }
             }
         }).collect();
+        min_ty.extend(v.iter().flat_map(|d| d.other_values()).map(|(lo, hi)| {
+            if lo < 0 { smallest_int_ty(lo.min(-hi - 1)) } else { smallest_int_ty(hi) }
+        }));
 
         // first mention, higher priority
         let priority = [
@@ -122,6 +114,39 @@ pub fn derive(ast: &syn::DeriveInput, mut prim_type: Option<syn::Ty>) -> quote::
     }    
 
     let prim_type = prim_type.expect("Unable to detect the primitive type for this enum.");
+    let name_str = name.as_ref().to_string();
+    let prim_type_str = ::pack_parse::syn_to_string(&prim_type);
+
+    // the discriminants that aren't known, like constants, are compared at runtime
+    let mut value_patterns: Vec<_> = v.iter().map(|x| {
+        let pattern = match x.discriminant {
+            Some(d) => int_pattern(d),
+            None => {
+                let n = &x.variant.ident;
+                quote! { v if v == #name::#n as #prim_type }
+            }
+        };
+        (pattern, &x.variant.ident)
+    }).collect();
+
+    // the aliases and the ranges only map to the variant, it packs into its discriminant
+    check_overlapping_values(&v);
+    for x in &v {
+        for (lo, hi) in x.other_values() {
+            let pattern = if lo == hi {
+                int_pattern(lo)
+            } else {
+                let lo = int_pattern(lo);
+                let hi = int_pattern(hi);
+                quote! { #lo ..= #hi }
+            };
+            value_patterns.push((pattern, &x.variant.ident));
+        }
+    }
+
+    let from_primitive_match: Vec<_> = value_patterns.iter().map(|&(ref pattern, n)| quote! {
+        #pattern => Some(#name::#n)
+    }).collect();
 
     let all_variants_const_ident = syn::Ident::from(format!("{}_ALL", to_snake_case(name.as_ref()).to_uppercase() ));
     
@@ -167,6 +192,115 @@ pub fn derive(ast: &syn::DeriveInput, mut prim_type: Option<syn::Ty>) -> quote::
         });
     };
 
+    if let Some(bits) = exhaustive_bits(ast, &v) {
+        let mask = syn::Lit::Int(((1u128 << bits) - 1) as u64, syn::IntTy::Unsuffixed);
+        let first = &v[0].variant.ident;
+        let from_bits_match: Vec<_> = value_patterns.iter().map(|&(ref pattern, n)| quote! {
+            #pattern => #name::#n
+        }).collect();
+
+        str_format.append(quote! {
+            impl ::packed_struct::ExhaustivePrimitiveEnum for #name {
+                const BITS: usize = #bits;
+
+                #[inline]
+                #[allow(unreachable_patterns)]
+                fn from_bits(bits: #prim_type) -> Self {
+                    match bits & #mask {
+                        #(#from_bits_match),* ,
+                        _ => #name::#first
+                    }
+                }
+            }
+        });
+    }
+
+    let core = ::common::core_prefix();
+    let result_ty = ::common::result_type();
+
+    if ::common::include_serde_codegen() && has_trait_attribute(&ast.attrs, "serde") {
+        str_format.append(quote! {
+            impl ::packed_struct::serde::Serialize for #name {
+                fn serialize<S>(&self, serializer: S) -> #result_ty<S::Ok, S::Error> where S: ::packed_struct::serde::Serializer {
+                    ::packed_struct::serde_enum::serialize(self, serializer)
+                }
+            }
+
+            impl<'de> ::packed_struct::serde::Deserialize<'de> for #name {
+                fn deserialize<D>(deserializer: D) -> #result_ty<Self, D::Error> where D: ::packed_struct::serde::Deserializer<'de> {
+                    ::packed_struct::serde_enum::deserialize(deserializer)
+                }
+            }
+        });
+    }
+
+    if has_trait_attribute(&ast.attrs, "display") {
+        str_format.append(quote! {
+            impl #core::fmt::Display for #name {
+                fn fmt(&self, f: &mut #core::fmt::Formatter) -> #core::fmt::Result {
+                    f.pad(::packed_struct::PrimitiveEnumStaticStr::to_display_str(self))
+                }
+            }
+        });
+    }
+
+    // an exact match first, the names that only differ in case could be different variants
+    if has_trait_attribute(&ast.attrs, "from_str") {
+        str_format.append(quote! {
+            impl #core::str::FromStr for #name {
+                type Err = ::packed_struct::ParseEnumError;
+
+                fn from_str(s: &str) -> #result_ty<Self, Self::Err> {
+                    <Self as ::packed_struct::PrimitiveEnum>::from_str(s)
+                        .or_else(|| <Self as ::packed_struct::PrimitiveEnumStaticStr>::from_str_ignore_case(s))
+                        .ok_or(::packed_struct::ParseEnumError { enum_name: #name_str })
+                }
+            }
+        });
+    }
+
+    if has_trait_attribute(&ast.attrs, "try_from") {
+        str_format.append(quote! {
+            impl #core::convert::TryFrom<#prim_type> for #name {
+                type Error = ::packed_struct::TryFromPrimitiveError<#prim_type>;
+
+                fn try_from(value: #prim_type) -> #result_ty<Self, Self::Error> {
+                    <Self as ::packed_struct::PrimitiveEnum>::from_primitive(value)
+                        .ok_or(::packed_struct::TryFromPrimitiveError { enum_name: #name_str, value: value })
+                }
+            }
+        });
+    }
+
+    if has_trait_attribute(&ast.attrs, "into") {
+        str_format.append(quote! {
+            impl #core::convert::From<#name> for #prim_type {
+                fn from(value: #name) -> #prim_type {
+                    ::packed_struct::PrimitiveEnum::to_primitive(&value)
+                }
+            }
+        });
+    }
+
+    if ::common::include_defmt_codegen() {
+        let defmt_variants: Vec<_> = v.iter().map(|x| {
+            let n = &x.variant.ident;
+            let d = &x.name;
+            quote! {
+                #name::#n => defmt::write!(f, #d)
+        }}).collect();
+
+        str_format.append(quote! {
+            impl defmt::Format for #name {
+                fn format(&self, f: defmt::Formatter) {
+                    match *self {
+                        #(#defmt_variants),*
+                    }
+                }
+            }
+        });
+    }
+
 
     quote! {
 
@@ -205,16 +339,65 @@ pub fn derive(ast: &syn::DeriveInput, mut prim_type: Option<syn::Ty>) -> quote::
             }
         }
 
+        impl ::packed_struct::PrimitiveEnumBounds for #name {
+            const MIN: #prim_type = {
+                let mut min = #all_variants_const_ident[0] as #prim_type;
+                let mut i = 1;
+                while i < #all_variants_len {
+                    if (#all_variants_const_ident[i] as #prim_type) < min {
+                        min = #all_variants_const_ident[i] as #prim_type;
+                    }
+                    i += 1;
+                }
+                min
+            };
+
+            const MAX: #prim_type = {
+                let mut max = #all_variants_const_ident[0] as #prim_type;
+                let mut i = 1;
+                while i < #all_variants_len {
+                    if (#all_variants_const_ident[i] as #prim_type) > max {
+                        max = #all_variants_const_ident[i] as #prim_type;
+                    }
+                    i += 1;
+                }
+                max
+            };
+
+            const MAX_BITS: usize = ::packed_struct::primitive_bits(Self::MIN as i128, Self::MAX as i128);
+        }
+
         #str_format
+
+        impl ::packed_struct::schema::PrimitiveEnumSchemaInfo for #name {
+            const SCHEMA: &'static ::packed_struct::schema::PrimitiveEnumSchema = &::packed_struct::schema::PrimitiveEnumSchema {
+                name: #name_str,
+                primitive: #prim_type_str,
+                variants: &[#(#schema_variants),*]
+            };
+        }
     }
 }
 
 #[derive(Debug)]
 struct Variant {
     variant: syn::Variant,
-    discriminant: u64,
-    negative: bool,
-    int_ty: syn::IntTy
+    /// The value of the discriminant, if it can be calculated from the literals.
+    discriminant: Option<i128>,
+    int_ty: syn::IntTy,
+    /// The name for display and parsing.
+    name: String,
+    /// Other values that unpack into this variant.
+    aliases: Vec<i128>,
+    /// Inclusive ranges of values that unpack into this variant.
+    ranges: Vec<(i128, i128)>
+}
+
+impl Variant {
+    /// The aliases and the ranges, as inclusive ranges.
+    fn other_values(&self) -> Vec<(i128, i128)> {
+        self.aliases.iter().map(|&a| (a, a)).chain(self.ranges.iter().cloned()).collect()
+    }
 }
 
 
@@ -223,47 +406,30 @@ fn get_unitary_enum(input: &syn::DeriveInput) -> Vec<Variant> {
         syn::Body::Enum(ref variants) => {
             let mut r = Vec::new();
 
-            let mut d = 0;
-            let mut neg = false;
+            let mut next = Some(0);
 
             for variant in variants {
                 if variant.data != syn::VariantData::Unit {
                     break;
                 }
 
-                let (discriminant, negative, int_ty) = match variant.discriminant {
-                    Some(syn::ConstExpr::Lit(syn::Lit::Int(v, int_ty))) => { (v, false, int_ty) },
-                    Some(syn::ConstExpr::Unary(syn::UnOp::Neg, ref v)) => {
-                        match **v {
-                            syn::ConstExpr::Lit(syn::Lit::Int(v, int_ty)) => {
-                                (v, true, int_ty)
-                            },
-                            ref p @ _ => {
-                                panic!("Unsupported negated enum const expr: {:?}", p);
-                            }
-                        }
-                    }
-                    Some(ref p @ _) => {
-                        panic!("Unsupported enum const expr: {:?}", p);
-                    },
-                    None => {
-                        if neg {
-                            (d-1, if d-1 == 0 { false } else { true }, syn::IntTy::Unsuffixed)
-                        } else {
-                            (d+1, false, syn::IntTy::Unsuffixed)
-                        }
-                    }
+                let (discriminant, int_ty) = match variant.discriminant {
+                    Some(ref expr) => (eval_const_expr(expr), literal_int_ty(expr)),
+                    None => (next, syn::IntTy::Unsuffixed)
                 };
 
-                r.push(Variant {
+                let mut v = Variant {
                     variant: variant.clone(),
                     discriminant: discriminant,
-                    negative: negative,
-                    int_ty: int_ty
-                });
+                    int_ty: int_ty,
+                    name: variant.ident.as_ref().to_string(),
+                    aliases: vec![],
+                    ranges: vec![]
+                };
+                parse_variant_attributes(&mut v);
+                r.push(v);
 
-                d = discriminant;                
-                neg = negative;                
+                next = discriminant.and_then(|d| d.checked_add(1));
             }
             return r;
         },
@@ -271,4 +437,248 @@ fn get_unitary_enum(input: &syn::DeriveInput) -> Vec<Variant> {
     }
 
     panic!("Enum's variants must be unitary.");
-}
\ No newline at end of file
+}
+
+/// Calculates the discriminants made of integer literals and operators. Constants and
+/// other expressions are left to the compiler.
+fn eval_const_expr(expr: &syn::ConstExpr) -> Option<i128> {
+    match *expr {
+        syn::ConstExpr::Lit(syn::Lit::Int(v, _)) => Some(v as i128),
+        syn::ConstExpr::Unary(syn::UnOp::Neg, ref v) => eval_const_expr(v).map(|v| -v),
+        syn::ConstExpr::Paren(ref v) => eval_const_expr(v),
+        syn::ConstExpr::Binary(op, ref a, ref b) => {
+            let a = eval_const_expr(a)?;
+            let b = eval_const_expr(b)?;
+            match op {
+                syn::BinOp::Add => a.checked_add(b),
+                syn::BinOp::Sub => a.checked_sub(b),
+                syn::BinOp::Mul => a.checked_mul(b),
+                syn::BinOp::Div => a.checked_div(b),
+                syn::BinOp::Rem => a.checked_rem(b),
+                syn::BinOp::Shl => u32::try_from(b).ok().and_then(|b| a.checked_shl(b)),
+                syn::BinOp::Shr => u32::try_from(b).ok().and_then(|b| a.checked_shr(b)),
+                syn::BinOp::BitAnd => Some(a & b),
+                syn::BinOp::BitOr => Some(a | b),
+                syn::BinOp::BitXor => Some(a ^ b),
+                _ => None
+            }
+        },
+        _ => None
+    }
+}
+
+/// The type suffix of a literal discriminant, like ```5u16```.
+fn literal_int_ty(expr: &syn::ConstExpr) -> syn::IntTy {
+    match *expr {
+        syn::ConstExpr::Lit(syn::Lit::Int(_, int_ty)) => int_ty,
+        syn::ConstExpr::Unary(syn::UnOp::Neg, ref v) => literal_int_ty(v),
+        _ => syn::IntTy::Unsuffixed
+    }
+}
+
+fn smallest_int_ty(n: i128) -> syn::IntTy {
+    if n < 0 {
+        if n >= i8::MIN as i128 {
+            syn::IntTy::I8
+        } else if n >= i16::MIN as i128 {
+            syn::IntTy::I16
+        } else if n >= i32::MIN as i128 {
+            syn::IntTy::I32
+        } else {
+            syn::IntTy::I64
+        }
+    } else if n <= u8::MAX as i128 {
+        syn::IntTy::U8
+    } else if n <= u16::MAX as i128 {
+        syn::IntTy::U16
+    } else if n <= u32::MAX as i128 {
+        syn::IntTy::U32
+    } else {
+        syn::IntTy::U64
+    }
+}
+
+/// The traits that the enum's ```#[primitive(..)]``` attribute can ask for. ```serde```
+/// also needs the ```serde``` feature.
+const TRAITS: [&str; 5] = ["display", "from_str", "try_from", "into", "serde"];
+
+fn has_trait_attribute(attrs: &[syn::Attribute], name: &str) -> bool {
+    attrs.iter().any(|attr| match attr.value {
+        syn::MetaItem::List(ref ident, ref items) if ident.as_ref() == "primitive" => {
+            items.iter().any(|item| match *item {
+                syn::NestedMetaItem::MetaItem(syn::MetaItem::Word(ref w)) => w.as_ref() == name,
+                _ => false
+            })
+        },
+        _ => false
+    })
+}
+
+/// The primitive type from ```#[primitive(u16)]``` or ```#[repr(u16)]```, the former
+/// taking precedence.
+fn primitive_type_attribute(attrs: &[syn::Attribute]) -> Option<syn::Ty> {
+    const TYPES: [&str; 8] = ["u8", "u16", "u32", "u64", "i8", "i16", "i32", "i64"];
+
+    let find = |attr_name: &str, strict: bool| {
+        for attr in attrs {
+            if let syn::MetaItem::List(ref ident, ref items) = attr.value {
+                if ident.as_ref() != attr_name {
+                    continue;
+                }
+
+                for item in items {
+                    if let syn::NestedMetaItem::MetaItem(syn::MetaItem::Word(ref ty)) = *item {
+                        if TYPES.contains(&ty.as_ref()) {
+                            return Some(syn::parse_type(ty.as_ref()).unwrap());
+                        } else if strict && !TRAITS.contains(&ty.as_ref()) {
+                            panic!("Unsupported primitive type {}, supported are {}.", ty, TYPES.join(", "));
+                        }
+                    }
+                }
+            }
+        }
+        None
+    };
+
+    find("primitive", true).or_else(|| find("repr", false))
+}
+
+/// Reads ```#[primitive(name = "low-power", alias = 3, range = "8..=15")]```. The aliases
+/// and ranges can be repeated.
+fn parse_variant_attributes(v: &mut Variant) {
+    for attr in &v.variant.attrs {
+        let items = match attr.value {
+            syn::MetaItem::List(ref ident, ref items) if ident.as_ref() == "primitive" => items,
+            _ => continue
+        };
+
+        for item in items {
+            let (key, lit) = match *item {
+                syn::NestedMetaItem::MetaItem(syn::MetaItem::NameValue(ref key, ref lit)) => (key.as_ref(), lit),
+                _ => panic!("Unsupported attribute of the variant {}: {:?}", v.variant.ident, item)
+            };
+
+            let value = match *lit {
+                syn::Lit::Str(ref s, _) => s.clone(),
+                syn::Lit::Int(n, _) => n.to_string(),
+                _ => panic!("Unsupported value of {} for the variant {}", key, v.variant.ident)
+            };
+
+            match key {
+                "name" => v.name = value,
+                "alias" => v.aliases.push(parse_int(&value)),
+                "range" => v.ranges.push(parse_range(&value)),
+                _ => panic!("Unknown attribute {} of the variant {}", key, v.variant.ident)
+            }
+        }
+    }
+}
+
+/// Inclusive ```8..=15``` or exclusive ```8..16``` range of values.
+fn parse_range(s: &str) -> (i128, i128) {
+    let (lo, hi) = if let Some(p) = s.find("..=") {
+        (parse_int(&s[..p]), parse_int(&s[p + 3..]))
+    } else if let Some(p) = s.find("..") {
+        (parse_int(&s[..p]), parse_int(&s[p + 2..]) - 1)
+    } else {
+        panic!("Invalid range: {:?}", s);
+    };
+
+    if lo > hi {
+        panic!("Empty range: {:?}", s);
+    }
+    (lo, hi)
+}
+
+fn parse_int(s: &str) -> i128 {
+    let s = s.trim();
+    if s.starts_with('-') {
+        -parse_int(&s[1..])
+    } else {
+        ::pack_parse::parse_num(s) as i128
+    }
+}
+
+fn int_pattern(n: i128) -> quote::Tokens {
+    let lit = syn::Lit::Int(n.unsigned_abs() as u64, syn::IntTy::Unsuffixed);
+    if n < 0 {
+        quote! { -#lit }
+    } else {
+        quote! { #lit }
+    }
+}
+
+/// A value can only belong to a single variant.
+fn check_overlapping_values(variants: &[Variant]) {
+    let values: Vec<_> = variants.iter().flat_map(|v| {
+        let ident = &v.variant.ident;
+        v.discriminant.map(|d| (d, d)).into_iter().chain(v.other_values()).map(move |r| (r, ident))
+    }).collect();
+
+    for (i, &((lo, hi), a)) in values.iter().enumerate() {
+        for &((other_lo, other_hi), b) in &values[i + 1..] {
+            if lo <= other_hi && other_lo <= hi {
+                panic!("The values {}..={} of {} overlap with the values {}..={} of {}", lo, hi, a, other_lo, other_hi, b);
+            }
+        }
+    }
+}
+
+/// The number of bits in which every value is a variant, from ```#[primitive(bits = 2)]```.
+/// Panics when the variants don't cover every value of the declared bits.
+fn exhaustive_bits(ast: &syn::DeriveInput, variants: &[Variant]) -> Option<usize> {
+    let name = &ast.ident;
+    let declared = ast.attrs.iter().filter_map(|attr| match attr.value {
+        syn::MetaItem::List(ref ident, ref items) if ident.as_ref() == "primitive" => Some(items),
+        _ => None
+    }).flat_map(|items| items.iter()).filter_map(|item| match *item {
+        syn::NestedMetaItem::MetaItem(syn::MetaItem::NameValue(ref key, ref lit)) if key.as_ref() == "bits" => match *lit {
+            syn::Lit::Int(n, _) => Some(n as usize),
+            syn::Lit::Str(ref s, _) => Some(::pack_parse::parse_num(s)),
+            _ => panic!("Invalid number of bits of the enum {}", name)
+        },
+        _ => None
+    }).next();
+
+    // only declared widths, an enum that happens to cover its bits isn't exhaustive by accident
+    let bits = match declared {
+        Some(bits) => bits,
+        None => return None
+    };
+
+    let not_exhaustive = |reason: &str| {
+        panic!("The enum {} isn't exhaustive: {}", name, reason)
+    };
+
+    let mut values = vec![];
+    for v in variants {
+        match v.discriminant {
+            Some(d) => values.push((d, d)),
+            None => not_exhaustive("the discriminants have to be literals")
+        }
+        values.extend(v.other_values());
+    }
+    values.sort();
+
+    let min = values.first().map(|v| v.0).unwrap_or(0);
+    let max = values.iter().map(|v| v.1).max().unwrap_or(0);
+    if bits == 0 || bits > 64 {
+        not_exhaustive("unsupported number of bits");
+    }
+    if min < 0 || max >= (1 << bits) {
+        not_exhaustive("some of the values don't fit into the bits");
+    }
+
+    let mut next = 0;
+    for &(lo, hi) in &values {
+        if lo != next {
+            not_exhaustive(&format!("the value {} isn't a variant", next));
+        }
+        next = hi + 1;
+    }
+    if next != (1 << bits) {
+        not_exhaustive(&format!("the value {} isn't a variant", next));
+    }
+
+    Some(bits)
+}
@@ -73,3 +73,45 @@ pub fn to_snake_case(mut str: &str) -> String {
     }
     words.join("_")
 }
+
+
+/// The doc comment from the attributes, each line trimmed of the comment markers.
+pub fn doc_comment(attrs: &[::syn::Attribute]) -> String {
+    let mut lines = vec![];
+    for attr in attrs {
+        if let ::syn::MetaItem::NameValue(ref ident, ::syn::Lit::Str(ref s, _)) = attr.value {
+            if ident.as_ref() != "doc" {
+                continue;
+            }
+
+            let line = if attr.is_sugared_doc {
+                if s.starts_with("/**") {
+                    s.trim_start_matches("/**").trim_end_matches("*/").trim()
+                } else {
+                    let line = s.trim_start_matches("///");
+                    if line.starts_with(' ') { &line[1..] } else { line }
+                }
+            } else {
+                s.as_str()
+            };
+            lines.push(line.trim_end().to_string());
+        }
+    }
+    lines.join("\n")
+}
+
+/// Type name as it would be written by hand, without the spacing between the tokens.
+pub fn type_name(ty: &str) -> String {
+    let mut name = String::new();
+    let mut prev = "";
+    for token in ty.split_whitespace() {
+        let is_word = |t: &str| t.chars().last().map(|c| c.is_alphanumeric() || c == '_').unwrap_or(false);
+        let starts_word = token.chars().next().map(|c| c.is_alphanumeric() || c == '_' || c == '\'').unwrap_or(false);
+        if prev == "," || prev == ";" || (is_word(prev) && starts_word) {
+            name.push(' ');
+        }
+        name.push_str(token);
+        prev = token;
+    }
+    name
+}
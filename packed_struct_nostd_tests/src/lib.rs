@@ -4,12 +4,14 @@
 extern crate packed_struct;
 #[macro_use]
 extern crate packed_struct_codegen;
+#[cfg(feature="defmt")]
+extern crate defmt;
 
 use packed_struct::prelude::*;
 
 /// Control register, address 0xA0.
 #[derive(PackedStruct, Debug, Copy, Clone, PartialEq)]
-#[packed_struct(bit_numbering="msb0")]
+#[packed_struct(bit_numbering="msb0", debug_write)]
 pub struct ControlRegister {
     /// Sensor's power mode
     #[packed_field(bits="0:1", ty="enum")]
@@ -32,6 +34,7 @@ pub struct ControlRegister {
 }
 
 #[derive(PrimitiveEnum, Debug, Copy, Clone, PartialEq)]
+#[primitive(display, from_str, try_from, into)]
 pub enum PowerMode {
     /// The sensor is turned off
     Off = 0,
@@ -43,6 +46,26 @@ pub enum PowerMode {
     On = 3
 }
 
+/// Flags without a ```Debug``` implementation.
+#[derive(PackedStruct, Copy, Clone, PartialEq)]
+#[packed_struct(bit_numbering="msb0")]
+pub struct Flags {
+    #[packed_field(bits="0")]
+    pub ready: bool,
+    #[packed_field(bits="1:7")]
+    pub level: Integer<u8, packed_bits::Bits7>
+}
+
+/// A structure with a field that isn't ```Debug```, without the fields table.
+#[derive(PackedStruct, Copy, Clone, PartialEq)]
+#[packed_struct(bit_numbering="msb0")]
+pub struct Status {
+    #[packed_field(bytes="0")]
+    pub flags: Flags,
+    #[packed_field(bytes="1")]
+    pub count: u8
+}
+
 
 #[cfg(test)]
 mod tests {
@@ -67,4 +90,94 @@ mod tests {
         let unpacked = ControlRegister::unpack(&[0x8B, 0xE7, 0x21, 0xFA]).unwrap();
         assert_eq!(unpacked, reg);
     }
+
+    /// A writer into a fixed buffer, like a UART log would use.
+    struct BufferWriter {
+        buffer: [u8; 2048],
+        len: usize
+    }
+
+    impl ::core::fmt::Write for BufferWriter {
+        fn write_str(&mut self, s: &str) -> ::core::fmt::Result {
+            let end = self.len + s.len();
+            if end > self.buffer.len() {
+                return Err(::core::fmt::Error);
+            }
+            self.buffer[self.len..end].copy_from_slice(s.as_bytes());
+            self.len = end;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn nostd_without_debug() {
+        use packed_struct::prelude::*;
+
+        use Flags;
+        use Status;
+
+        let status = Status { flags: Flags { ready: true, level: 3.into() }, count: 7 };
+        let packed = status.pack();
+        assert_eq!([0x83, 0x07], packed);
+        assert!(Status::unpack(&packed).unwrap() == status);
+    }
+
+    #[test]
+    fn nostd_write_fields() {
+        use packed_struct::debug_write::PackedStructDebugWrite;
+        use core::fmt::Write;
+
+        use ControlRegister;
+        use PowerMode;
+
+        let reg = ControlRegister {
+            power_mode: PowerMode::LowPower,
+            voltage_milli_volts: 11.into(),
+            standby_led_enabled: true,
+            gain_stages: [true, true, false, false],
+            _reserved: Default::default(),
+            sensor_value: -1503
+        };
+
+        let mut out = BufferWriter { buffer: [0; 2048], len: 0 };
+        reg.write_fields(&mut out).unwrap();
+        write!(out, "{}", reg.fields_display()).unwrap();
+
+        let text = ::core::str::from_utf8(&out.buffer[..out.len]).unwrap();
+        let (first, second) = text.split_at(text.len() / 2);
+        assert_eq!(first, second);
+        assert!(first.starts_with("          power_mode | bits   0:1   | 0b10               | LowPower\r\n"));
+        assert!(first.contains("      gain_stages[2] | bits  11:11  | 0b0                | false\r\n"));
+        assert!(first.ends_with("        sensor_value | bits  16:31  | 0b0010000111111010 | -1503\r\n"));
+    }
+
+    #[cfg(feature="defmt")]
+    fn assert_format<T: ::defmt::Format>() {}
+
+    #[test]
+    #[cfg(feature="defmt")]
+    fn nostd_defmt_format() {
+        use packed_struct::prelude::*;
+
+        use ControlRegister;
+        use PowerMode;
+
+        assert_format::<ControlRegister>();
+        assert_format::<PowerMode>();
+        assert_format::<EnumCatchAll<PowerMode>>();
+        assert_format::<Integer<u8, packed_bits::Bits5>>();
+        assert_format::<ReservedOnes<packed_bits::Bits3>>();
+    }
+
+    #[test]
+    fn nostd_std_traits() {
+        use core::convert::TryFrom;
+
+        use PowerMode;
+
+        assert_eq!(Ok(PowerMode::LowPower), "lowpower".parse::<PowerMode>());
+        assert_eq!(Ok(PowerMode::On), PowerMode::try_from(3));
+        assert!(PowerMode::try_from(4).is_err());
+        assert_eq!(2u8, PowerMode::LowPower.into());
+    }
 }
@@ -0,0 +1,301 @@
+//! Emits the Rust source of the packed structures and enums.
+
+use std::fmt::Write;
+
+use super::*;
+
+/// Generates the Rust source for a parsed device.
+pub fn generate_device(device: &Device) -> String {
+    let mut out = String::new();
+
+    writeln!(out, "// Generated by packed_struct_svd from the description of the {} device, do not edit.", device.name).unwrap();
+
+    for block in &device.blocks {
+        writeln!(out).unwrap();
+        write_doc(&mut out, "", &block.description);
+        writeln!(out, "#[allow(dead_code, non_camel_case_types)]").unwrap();
+        writeln!(out, "pub mod {} {{", identifier(&snake_case(&block.name))).unwrap();
+        writeln!(out, "    #[allow(unused_imports)]").unwrap();
+        writeln!(out, "    use ::packed_struct::prelude::*;").unwrap();
+        writeln!(out).unwrap();
+        writeln!(out, "    /// Base address of the peripheral.").unwrap();
+        writeln!(out, "    pub const BASE_ADDRESS: u64 = 0x{:X};", block.base_address).unwrap();
+
+        // the names of the prelude types used by the fields can't be shadowed
+        let mut type_names: Vec<String> = PRELUDE_TYPES.iter().map(|t| t.to_string()).collect();
+        for register in &block.registers {
+            let name = unique(type_name(&register.name, "R"), &mut type_names);
+            writeln!(out).unwrap();
+            write_register(&mut out, block, register, &name, &mut type_names);
+        }
+
+        writeln!(out, "}}").unwrap();
+    }
+
+    out
+}
+
+fn write_register(out: &mut String, block: &RegisterBlock, register: &Register, name: &str, type_names: &mut Vec<String>) {
+    let num_bytes = (register.size_bits + 7) / 8;
+    let num_bits = num_bytes * 8;
+
+    let mut fields: Vec<&Field> = register.fields.iter().collect();
+    fields.sort_by_key(|f| f.bit_offset);
+
+    let mut field_lines = String::new();
+    let mut enums = String::new();
+    let mut field_names = Vec::new();
+    let mut next_bit = 0;
+
+    let whole_register = Field {
+        name: "value".to_string(),
+        description: String::new(),
+        bit_offset: 0,
+        bit_width: num_bits,
+        access: register.access,
+        enumerated_values: Vec::new()
+    };
+    if fields.is_empty() {
+        fields.push(&whole_register);
+    }
+
+    for field in fields {
+        let end = field.bit_offset + field.bit_width;
+        if field.bit_width == 0 || field.bit_offset < next_bit || end > num_bits {
+            writeln!(field_lines, "        // Skipped the field {}, it overlaps another field or doesn't fit the register.", field.name).unwrap();
+            continue;
+        }
+
+        if field.bit_offset > next_bit {
+            write_reserved(&mut field_lines, next_bit, field.bit_offset);
+        }
+        next_bit = end;
+
+        let ident = unique(identifier(&snake_case(&field.name)), &mut field_names);
+        let bits = format!("{}:{}", end - 1, field.bit_offset);
+
+        write_doc(&mut field_lines, "        ", &field.description);
+        match write_enum(&mut enums, register, name, field, type_names) {
+            Some(ty) => {
+                writeln!(field_lines, "        #[packed_field(bits=\"{}\", ty=\"enum\")]", bits).unwrap();
+                writeln!(field_lines, "        pub {}: {},", ident, ty).unwrap();
+            },
+            None => {
+                writeln!(field_lines, "        #[packed_field(bits=\"{}\")]", bits).unwrap();
+                writeln!(field_lines, "        pub {}: {},", ident, integer_type(field.bit_width)).unwrap();
+            }
+        }
+    }
+
+    if num_bits > next_bit {
+        write_reserved(&mut field_lines, next_bit, num_bits);
+    }
+
+    out.push_str(&enums);
+
+    write_doc(out, "    ", &register.description);
+    if !register.description.is_empty() {
+        writeln!(out, "    ///").unwrap();
+    }
+    write!(out, "    /// Offset 0x{:X}", register.address_offset).unwrap();
+    if let Some(reset) = register.reset_value {
+        write!(out, ", reset value 0x{:0width$X}", reset, width = num_bytes * 2).unwrap();
+    }
+    if let Some(access) = register.access {
+        write!(out, ", {}", access_name(access)).unwrap();
+    }
+    writeln!(out, ".").unwrap();
+    writeln!(out, "    #[derive(PackedStruct, Debug, Copy, Clone, PartialEq)]").unwrap();
+    writeln!(out, "    #[packed_struct(bit_numbering=\"lsb0\", size_bytes=\"{}\", endian=\"msb\", address=\"0x{:X}\", access=\"{}\", schema)]",
+        num_bytes, block.base_address.wrapping_add(register.address_offset), register_access(register.access)).unwrap();
+    writeln!(out, "    pub struct {} {{", name).unwrap();
+    out.push_str(&field_lines);
+    writeln!(out, "    }}").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "    impl {} {{", name).unwrap();
+    writeln!(out, "        /// Offset from the base address of the peripheral.").unwrap();
+    writeln!(out, "        pub const OFFSET: u64 = 0x{:X};", register.address_offset).unwrap();
+    if let Some(reset) = register.reset_value {
+        writeln!(out, "        /// Value of the register after a reset.").unwrap();
+        writeln!(out, "        pub const RESET_VALUE: u64 = 0x{:X};", reset).unwrap();
+    }
+    writeln!(out, "    }}").unwrap();
+}
+
+/// Writes the enum of a field, returning the type of the field. Values that don't cover
+/// every bit pattern of the field are wrapped into an ```EnumCatchAll```.
+fn write_enum(out: &mut String, register: &Register, register_type: &str, field: &Field, type_names: &mut Vec<String>) -> Option<String> {
+    let max = if field.bit_width >= 64 { u64::MAX } else { (1 << field.bit_width) - 1 };
+
+    let mut variants: Vec<(String, &EnumeratedValue)> = Vec::new();
+    let mut names = Vec::new();
+    for value in &field.enumerated_values {
+        if value.value > max || variants.iter().any(|v| v.1.value == value.value) {
+            continue;
+        }
+        variants.push((unique(type_name(&value.name, "V"), &mut names), value));
+    }
+
+    if variants.is_empty() || field.bit_width > 64 {
+        return None;
+    }
+
+    let name = unique(format!("{}{}", register_type, type_name(&field.name, "")), type_names);
+    let primitive = integer_primitive(field.bit_width);
+
+    writeln!(out, "    /// Values of the {} field of the {} register.", field.name, register.name).unwrap();
+    writeln!(out, "    #[derive(PrimitiveEnum_{}, Debug, Copy, Clone, PartialEq)]", primitive).unwrap();
+    writeln!(out, "    pub enum {} {{", name).unwrap();
+    for &(ref variant, value) in &variants {
+        write_doc(out, "        ", &value.description);
+        writeln!(out, "        {} = {},", variant, value.value).unwrap();
+    }
+    writeln!(out, "    }}").unwrap();
+    writeln!(out).unwrap();
+
+    let exhaustive = field.bit_width < 64 && variants.len() as u64 == max + 1;
+    if exhaustive {
+        Some(name)
+    } else {
+        Some(format!("EnumCatchAll<{}>", name))
+    }
+}
+
+fn write_reserved(out: &mut String, from: usize, to: usize) {
+    writeln!(out, "        #[packed_field(bits=\"{}:{}\")]", to - 1, from).unwrap();
+    writeln!(out, "        pub _reserved_{}: ReservedZero<packed_bits::Bits{}>,", from, to - from).unwrap();
+}
+
+fn write_doc(out: &mut String, indent: &str, doc: &str) {
+    for line in doc.lines().filter(|l| !l.trim().is_empty()) {
+        writeln!(out, "{}/// {}", indent, line.trim()).unwrap();
+    }
+}
+
+fn access_name(access: Access) -> &'static str {
+    match access {
+        Access::ReadOnly => "read-only",
+        Access::WriteOnly => "write-only",
+        Access::ReadWrite => "read-write",
+        Access::WriteOnce => "write-once",
+        Access::ReadWriteOnce => "read-write-once"
+    }
+}
+
+/// The access attribute of the register. The registers that can only be written once
+/// are still writable.
+fn register_access(access: Option<Access>) -> &'static str {
+    match access {
+        Some(Access::ReadOnly) => "ro",
+        Some(Access::WriteOnly) | Some(Access::WriteOnce) => "wo",
+        _ => "rw"
+    }
+}
+
+/// The smallest primitive that holds the bits.
+fn integer_primitive(bits: usize) -> &'static str {
+    match bits {
+        0..=8 => "u8",
+        9..=16 => "u16",
+        17..=32 => "u32",
+        _ => "u64"
+    }
+}
+
+fn integer_type(bits: usize) -> String {
+    match bits {
+        1 => "bool".to_string(),
+        8 | 16 | 32 | 64 => integer_primitive(bits).to_string(),
+        _ => format!("Integer<{}, packed_bits::Bits{}>", integer_primitive(bits), bits)
+    }
+}
+
+/// Converts the names like `CTRL_REG`, `ctrlReg` or `USBStatus` into `ctrl_reg`, `ctrl_reg`
+/// and `usb_status`.
+fn snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut s = String::new();
+
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            s.push('_');
+            continue;
+        }
+
+        if c.is_uppercase() && i > 0 {
+            let prev = chars[i - 1];
+            let next_lower = chars.get(i + 1).map(|n| n.is_lowercase()).unwrap_or(false);
+            if prev.is_lowercase() || prev.is_numeric() && next_lower || prev.is_uppercase() && next_lower {
+                s.push('_');
+            }
+        }
+        s.extend(c.to_lowercase());
+    }
+
+    let parts: Vec<_> = s.split('_').filter(|p| !p.is_empty()).collect();
+    parts.join("_")
+}
+
+/// A camel case type name, with the prefix added if the name starts with a digit.
+fn type_name(name: &str, prefix: &str) -> String {
+    let mut s: String = snake_case(name).split('_').map(|part| {
+        let mut chars = part.chars();
+        match chars.next() {
+            Some(c) => c.to_uppercase().chain(chars).collect(),
+            None => String::new()
+        }
+    }).collect();
+
+    if s.is_empty() || s.starts_with(|c: char| c.is_numeric()) {
+        s.insert_str(0, prefix);
+    }
+    if s == "Self" {
+        s.push('_');
+    }
+    s
+}
+
+const PRELUDE_TYPES: &[&str] = &["Integer", "ReservedZero", "EnumCatchAll"];
+
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use",
+    "where", "while", "abstract", "become", "box", "do", "final", "macro", "override", "priv",
+    "try", "typeof", "unsized", "virtual", "yield"
+];
+
+/// A snake case identifier, with the keywords and the leading digits escaped.
+fn identifier(name: &str) -> String {
+    if name.is_empty() || name.starts_with(|c: char| c.is_numeric()) {
+        format!("_{}", name)
+    } else if KEYWORDS.contains(&name) {
+        format!("{}_", name)
+    } else {
+        name.to_string()
+    }
+}
+
+/// Appends a number to the names that were already used.
+fn unique(name: String, used: &mut Vec<String>) -> String {
+    let mut candidate = name.clone();
+    let mut n = 2;
+    while used.contains(&candidate) {
+        candidate = format!("{}{}", name, n);
+        n += 1;
+    }
+    used.push(candidate.clone());
+    candidate
+}
+
+#[test]
+fn test_names() {
+    assert_eq!("ctrl_reg", snake_case("CTRL_REG"));
+    assert_eq!("ctrl_reg", snake_case("ctrlReg"));
+    assert_eq!("usb_status", snake_case("USBStatus"));
+    assert_eq!("timer0", snake_case("TIMER0"));
+    assert_eq!("CtrlReg", type_name("CTRL_REG", "R"));
+    assert_eq!("V3v3", type_name("3V3", "V"));
+    assert_eq!("type_", identifier("type"));
+    assert_eq!("_1st", identifier("1st"));
+}
@@ -0,0 +1,167 @@
+//! Generates `packed_struct` definitions from register description files
+//! ===========================================================================
+//!
+//! Reads a CMSIS-SVD or an IP-XACT style XML register description and emits a Rust
+//! source file with a ```#[derive(PackedStruct)]``` structure for every register and a
+//! ```PrimitiveEnum``` for every set of enumerated field values. Meant to be used from
+//! a build script.
+//!
+//! ## build.rs
+//!
+//! ```rust,no_run
+//! extern crate packed_struct_svd;
+//!
+//! fn main() {
+//!     let out = ::std::env::var("OUT_DIR").unwrap();
+//!     packed_struct_svd::generate_file("registers.svd", format!("{}/registers.rs", out)).unwrap();
+//! }
+//! ```
+//!
+//! ## Including the generated code
+//!
+//! ```rust,ignore
+//! extern crate packed_struct;
+//! #[macro_use] extern crate packed_struct_codegen;
+//!
+//! include!(concat!(env!("OUT_DIR"), "/registers.rs"));
+//! ```
+//!
+//! Every peripheral (SVD) or address block (IP-XACT) is placed into its own module. The
+//! registers use the ```lsb0``` bit numbering, so the bit ranges match the description
+//! file. The bits that aren't covered by any of the fields become reserved fields. The
+//! packed bytes hold the register's value in the MSB first order. The structures carry
+//! the absolute address and the access of their register, so they can be used with a
+//! ```RegisterInterface```.
+//!
+//! Clusters are flattened into their peripheral and peripherals can be derived from
+//! other peripherals. Register arrays (`dim`) are expanded into one register per element.
+
+extern crate roxmltree;
+
+mod codegen;
+mod parse;
+
+pub use codegen::generate_device;
+pub use parse::parse;
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// A parsed register description.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Device {
+    pub name: String,
+    pub blocks: Vec<RegisterBlock>
+}
+
+/// A peripheral, or an address block, with its registers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegisterBlock {
+    pub name: String,
+    pub description: String,
+    pub base_address: u64,
+    pub registers: Vec<Register>
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Register {
+    pub name: String,
+    pub description: String,
+    /// Offset from the base address of the block.
+    pub address_offset: u64,
+    pub size_bits: usize,
+    pub access: Option<Access>,
+    pub reset_value: Option<u64>,
+    pub fields: Vec<Field>
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    pub name: String,
+    pub description: String,
+    /// Position of the least significant bit.
+    pub bit_offset: usize,
+    pub bit_width: usize,
+    pub access: Option<Access>,
+    pub enumerated_values: Vec<EnumeratedValue>
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumeratedValue {
+    pub name: String,
+    pub description: String,
+    pub value: u64
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Access {
+    ReadOnly,
+    WriteOnly,
+    ReadWrite,
+    WriteOnce,
+    ReadWriteOnce
+}
+
+impl Access {
+    /// Parses the access values used by both formats.
+    pub fn from_name(s: &str) -> Option<Self> {
+        match s.trim() {
+            "read-only" => Some(Access::ReadOnly),
+            "write-only" => Some(Access::WriteOnly),
+            "read-write" => Some(Access::ReadWrite),
+            "writeOnce" => Some(Access::WriteOnce),
+            "read-writeOnce" => Some(Access::ReadWriteOnce),
+            _ => None
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    /// The file isn't well formed XML.
+    Xml(String),
+    /// A required element is missing.
+    Missing { element: String, context: String },
+    InvalidNumber(String),
+    Io(io::Error)
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Xml(ref e) => write!(f, "XML error: {}", e),
+            Error::Missing { ref element, ref context } => write!(f, "Missing <{}> in {}", element, context),
+            Error::InvalidNumber(ref n) => write!(f, "Invalid number: {:?}", n),
+            Error::Io(ref e) => write!(f, "I/O error: {}", e)
+        }
+    }
+}
+
+impl ::std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+/// Generates the Rust source for a register description.
+pub fn generate(xml: &str) -> Result<String, Error> {
+    let device = parse(xml)?;
+    Ok(generate_device(&device))
+}
+
+/// Generates the Rust source for a register description file. The output file is only
+/// written when its contents change, to avoid needless rebuilds.
+pub fn generate_file<P: AsRef<Path>, Q: AsRef<Path>>(input: P, output: Q) -> Result<(), Error> {
+    let xml = fs::read_to_string(input)?;
+    let source = generate(&xml)?;
+
+    if fs::read_to_string(output.as_ref()).ok().as_ref() != Some(&source) {
+        fs::write(output, source)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,308 @@
+//! Reads the device model from CMSIS-SVD and IP-XACT files. The elements are matched by
+//! their local names, so both the plain SVD and the namespaced IP-XACT documents work.
+
+use roxmltree::{Document, Node};
+
+use super::*;
+
+/// Parses a CMSIS-SVD or an IP-XACT register description.
+pub fn parse(xml: &str) -> Result<Device, Error> {
+    let doc = Document::parse(xml).map_err(|e| Error::Xml(e.to_string()))?;
+    let root = doc.root_element();
+
+    let name = child_text(root, "name").unwrap_or_else(|| "device".to_string());
+
+    let block_nodes: Vec<_> = root.descendants()
+        .filter(|n| is_element(*n, "peripheral") || is_element(*n, "addressBlock"))
+        .collect();
+
+    let mut blocks = Vec::new();
+    for node in &block_nodes {
+        let block_name = required_text(*node, "name", "a peripheral")?;
+        let mut description = description(*node);
+        let base_address = match child_text(*node, "baseAddress") {
+            Some(ref a) => parse_number(a)?,
+            None => 0
+        };
+
+        let mut registers = Vec::new();
+        collect_registers(*node, 0, &mut registers)?;
+
+        if let Some(derived_from) = node.attribute("derivedFrom") {
+            let source = block_nodes.iter().find(|n| child_text(**n, "name").as_deref() == Some(derived_from));
+            match source {
+                Some(source) => {
+                    if registers.is_empty() {
+                        collect_registers(*source, 0, &mut registers)?;
+                    }
+                    if description.is_empty() {
+                        description = self::description(*source);
+                    }
+                },
+                None => {
+                    return Err(Error::Missing { element: format!("peripheral {}", derived_from), context: format!("the peripheral {}", block_name) });
+                }
+            }
+        }
+
+        blocks.push(RegisterBlock {
+            name: block_name,
+            description,
+            base_address,
+            registers
+        });
+    }
+
+    Ok(Device {
+        name,
+        blocks
+    })
+}
+
+/// Collects the registers of a block, flattening the clusters and register files.
+fn collect_registers(node: Node, offset: u64, registers: &mut Vec<Register>) -> Result<(), Error> {
+    for child in node.children().filter(|n| n.is_element()) {
+        match child.tag_name().name() {
+            "registers" => {
+                collect_registers(child, offset, registers)?;
+            },
+            "cluster" | "registerFile" => {
+                let cluster_offset = match child_text(child, "addressOffset") {
+                    Some(ref o) => parse_number(o)?,
+                    None => 0
+                };
+                collect_registers(child, offset + cluster_offset, registers)?;
+            },
+            "register" => {
+                parse_register(child, offset, registers)?;
+            },
+            _ => ()
+        }
+    }
+
+    Ok(())
+}
+
+/// Parses a register, expanding it into several when it is an array.
+fn parse_register(node: Node, offset: u64, registers: &mut Vec<Register>) -> Result<(), Error> {
+    let name = required_text(node, "name", "a register")?;
+    let context = format!("the register {}", name);
+    let address_offset = offset + parse_number(&required_text(node, "addressOffset", &context)?)?;
+
+    let size_bits = match inherited_text(node, "size") {
+        Some(ref s) => parse_number(s)? as usize,
+        None => 32
+    };
+    let access = inherited_text(node, "access").and_then(|a| Access::from_name(&a));
+    let reset_value = match inherited_text(node, "resetValue").or_else(|| child(node, "reset").and_then(|r| child_text(r, "value"))) {
+        Some(ref v) => Some(parse_number(v)?),
+        None => None
+    };
+
+    let mut fields = Vec::new();
+    for field_node in node.children().filter(|n| is_element(*n, "fields")).flat_map(|n| n.children()).chain(node.children()) {
+        if is_element(field_node, "field") {
+            fields.push(parse_field(field_node, access, &context)?);
+        }
+    }
+
+    let register = Register {
+        name,
+        description: description(node),
+        address_offset,
+        size_bits,
+        access,
+        reset_value,
+        fields
+    };
+
+    match child_text(node, "dim") {
+        Some(ref dim) => {
+            let dim = parse_number(dim)? as usize;
+            let increment = parse_number(&required_text(node, "dimIncrement", &context)?)?;
+            let indices = match child_text(node, "dimIndex") {
+                Some(ref i) => dim_indices(i)?,
+                None => (0..dim).map(|i| i.to_string()).collect()
+            };
+
+            for (i, index) in indices.iter().take(dim).enumerate() {
+                let mut element = register.clone();
+                element.name = if register.name.contains("[%s]") {
+                    register.name.replace("[%s]", &format!("_{}", index))
+                } else {
+                    register.name.replace("%s", index)
+                };
+                element.address_offset += i as u64 * increment;
+                registers.push(element);
+            }
+        },
+        None => {
+            registers.push(register);
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_field(node: Node, register_access: Option<Access>, context: &str) -> Result<Field, Error> {
+    let name = required_text(node, "name", context)?;
+    let context = format!("the field {} of {}", name, context);
+
+    let (bit_offset, bit_width) = if let Some(offset) = child_text(node, "bitOffset") {
+        let width = match child_text(node, "bitWidth") {
+            Some(ref w) => parse_number(w)?,
+            None => 1
+        };
+        (parse_number(&offset)?, width)
+    } else if let (Some(lsb), Some(msb)) = (child_text(node, "lsb"), child_text(node, "msb")) {
+        bit_span(parse_number(&lsb)?, parse_number(&msb)?)?
+    } else if let Some(range) = child_text(node, "bitRange") {
+        let inner = range.trim_start_matches('[').trim_end_matches(']');
+        let mut parts = inner.split(':');
+        match (parts.next(), parts.next()) {
+            (Some(msb), Some(lsb)) => {
+                bit_span(parse_number(lsb)?, parse_number(msb)?)?
+            },
+            _ => return Err(Error::InvalidNumber(range.clone()))
+        }
+    } else {
+        return Err(Error::Missing { element: "bitOffset".to_string(), context });
+    };
+
+    let mut enumerated_values = Vec::new();
+    if let Some(values) = child(node, "enumeratedValues") {
+        for value in values.children().filter(|n| is_element(*n, "enumeratedValue")) {
+            let value_name = required_text(value, "name", &context)?;
+            let parsed = match child_text(value, "value") {
+                Some(ref v) => parse_enum_value(v)?,
+                None => None
+            };
+
+            if let Some(parsed) = parsed {
+                enumerated_values.push(EnumeratedValue {
+                    name: value_name,
+                    description: description(value),
+                    value: parsed
+                });
+            }
+        }
+    }
+
+    Ok(Field {
+        name,
+        description: description(node),
+        bit_offset: bit_offset as usize,
+        bit_width: bit_width as usize,
+        access: child_text(node, "access").and_then(|a| Access::from_name(&a)).or(register_access),
+        enumerated_values
+    })
+}
+
+/// The offset and the width of the bits between the two positions.
+fn bit_span(lsb: u64, msb: u64) -> Result<(u64, u64), Error> {
+    if msb < lsb {
+        return Err(Error::InvalidNumber(format!("[{}:{}]", msb, lsb)));
+    }
+
+    Ok((lsb, msb + 1 - lsb))
+}
+
+fn is_element(node: Node, name: &str) -> bool {
+    node.is_element() && node.tag_name().name() == name
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|n| is_element(*n, name))
+}
+
+fn child_text(node: Node, name: &str) -> Option<String> {
+    child(node, name).and_then(|n| n.text()).map(|t| t.trim().to_string())
+}
+
+fn required_text(node: Node, name: &str, context: &str) -> Result<String, Error> {
+    child_text(node, name).ok_or_else(|| Error::Missing { element: name.to_string(), context: context.to_string() })
+}
+
+/// The register properties are inherited from the enclosing elements.
+fn inherited_text(node: Node, name: &str) -> Option<String> {
+    node.ancestors().filter(|n| n.is_element()).filter_map(|n| child_text(n, name)).next()
+}
+
+/// The description, with the line breaks and the indentation collapsed.
+fn description(node: Node) -> String {
+    child_text(node, "description").map(|d| d.split_whitespace().collect::<Vec<_>>().join(" ")).unwrap_or_default()
+}
+
+/// Parses the numbers in the decimal, `0x` hexadecimal, `0b` or `#` binary or the Verilog
+/// style `32'h1F` formats.
+pub fn parse_number(s: &str) -> Result<u64, Error> {
+    let s = s.trim();
+    let invalid = || Error::InvalidNumber(s.to_string());
+
+    let (digits, radix) = if let Some(hex) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        (hex, 16)
+    } else if let Some(bin) = s.strip_prefix("0b").or_else(|| s.strip_prefix("0B")).or_else(|| s.strip_prefix('#')) {
+        (bin, 2)
+    } else if let Some(pos) = s.find('\'') {
+        let radix = match s[pos + 1..].chars().next() {
+            Some('h') | Some('H') => 16,
+            Some('b') | Some('B') => 2,
+            Some('o') | Some('O') => 8,
+            Some('d') | Some('D') => 10,
+            _ => return Err(invalid())
+        };
+        (&s[pos + 2..], radix)
+    } else {
+        (s, 10)
+    };
+
+    u64::from_str_radix(&digits.replace('_', ""), radix).map_err(|_| invalid())
+}
+
+/// Enumerated values can use `x` for the bits that don't matter. These can't be
+/// represented as a single discriminant and are skipped.
+fn parse_enum_value(s: &str) -> Result<Option<u64>, Error> {
+    let s = s.trim();
+    if (s.starts_with('#') || s.starts_with("0b") || s.starts_with("0B")) && s[1..].contains(['x', 'X']) {
+        return Ok(None);
+    }
+
+    parse_number(s).map(Some)
+}
+
+/// The `dimIndex` is either a comma separated list or a range, like `3-6` or `A-D`.
+fn dim_indices(s: &str) -> Result<Vec<String>, Error> {
+    if s.contains(',') {
+        return Ok(s.split(',').map(|i| i.trim().to_string()).collect());
+    }
+
+    let mut parts = s.split('-');
+    match (parts.next(), parts.next()) {
+        (Some(from), Some(to)) => {
+            let (from, to) = (from.trim(), to.trim());
+            if let (Ok(from), Ok(to)) = (from.parse::<u64>(), to.parse::<u64>()) {
+                Ok((from..to + 1).map(|i| i.to_string()).collect())
+            } else if from.len() == 1 && to.len() == 1 {
+                let (from, to) = (from.as_bytes()[0], to.as_bytes()[0]);
+                Ok((from..to + 1).map(|c| (c as char).to_string()).collect())
+            } else {
+                Err(Error::InvalidNumber(s.to_string()))
+            }
+        },
+        _ => Ok(vec![s.trim().to_string()])
+    }
+}
+
+#[test]
+fn test_parse_number() {
+    assert_eq!(26, parse_number("26").unwrap());
+    assert_eq!(0x4001_0000, parse_number(" 0x40010000 ").unwrap());
+    assert_eq!(5, parse_number("#101").unwrap());
+    assert_eq!(5, parse_number("0b101").unwrap());
+    assert_eq!(0x1F, parse_number("32'h1F").unwrap());
+    assert!(parse_number("0xZZ").is_err());
+    assert_eq!(None, parse_enum_value("#1x0").unwrap());
+    assert_eq!(vec!["A", "B", "C"], dim_indices("A-C").unwrap());
+    assert_eq!(vec!["1", "2"], dim_indices("1-2").unwrap());
+    assert_eq!(vec!["x", "y"], dim_indices("x, y").unwrap());
+}
@@ -0,0 +1,96 @@
+extern crate packed_struct_svd;
+
+use packed_struct_svd::*;
+
+const TIMER_SVD: &str = include_str!("fixtures/timer.svd");
+const UART_IPXACT: &str = include_str!("fixtures/uart.xml");
+
+#[test]
+fn test_parse_svd() {
+    let device = parse(TIMER_SVD).unwrap();
+    assert_eq!("TESTCHIP", device.name);
+    assert_eq!(2, device.blocks.len());
+
+    let timer = &device.blocks[0];
+    assert_eq!("General purpose timer", timer.description);
+    assert_eq!(0x4001_0000, timer.base_address);
+
+    let names: Vec<_> = timer.registers.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(vec!["CR", "SR", "CNT", "CCR0", "CCR1"], names);
+
+    let cr = &timer.registers[0];
+    assert_eq!(32, cr.size_bits);
+    assert_eq!(Some(Access::ReadWrite), cr.access);
+    assert_eq!(Some(0x10), cr.reset_value);
+    assert_eq!(4, cr.fields.len());
+    assert_eq!((4, 2), (cr.fields[1].bit_offset, cr.fields[1].bit_width));
+    assert_eq!((6, 1), (cr.fields[2].bit_offset, cr.fields[2].bit_width));
+    // the value with the don't care bits is skipped
+    assert_eq!(2, cr.fields[1].enumerated_values.len());
+    assert_eq!(1, cr.fields[1].enumerated_values[1].value);
+
+    let sr = &timer.registers[1];
+    assert_eq!(8, sr.size_bits);
+    assert_eq!(Some(Access::ReadOnly), sr.access);
+
+    assert_eq!(0x14, timer.registers[4].address_offset);
+
+    let derived = &device.blocks[1];
+    assert_eq!("TIMER1", derived.name);
+    assert_eq!(0x4001_1000, derived.base_address);
+    assert_eq!(timer.registers, derived.registers);
+}
+
+#[test]
+fn test_generate_svd() {
+    let source = generate(TIMER_SVD).unwrap();
+
+    assert!(source.contains("pub mod timer0 {\n    #[allow(unused_imports)]\n    use ::packed_struct::prelude::*;\n\n    /// Base address of the peripheral.\n    pub const BASE_ADDRESS: u64 = 0x40010000;\n"));
+    assert!(source.contains("    #[derive(PrimitiveEnum_u8, Debug, Copy, Clone, PartialEq)]\n    pub enum CrMode {\n        /// Count continuously\n        Continuous = 0,\n        OneShot = 1,\n    }\n"));
+    assert!(source.contains("    /// Control register\n    ///\n    /// Offset 0x0, reset value 0x00000010, read-write.\n"));
+    assert!(source.contains("    #[packed_struct(bit_numbering=\"lsb0\", size_bytes=\"4\", endian=\"msb\", address=\"0x40010000\", access=\"rw\", schema)]\n    pub struct Cr {\n        /// Enable the timer\n        #[packed_field(bits=\"0:0\")]\n        pub en: bool,\n"));
+    assert!(source.contains("        #[packed_field(bits=\"3:1\")]\n        pub _reserved_1: ReservedZero<packed_bits::Bits3>,\n"));
+    assert!(source.contains("        #[packed_field(bits=\"5:4\", ty=\"enum\")]\n        pub mode: EnumCatchAll<CrMode>,\n"));
+    // every value of the field is covered, no catch-all is needed
+    assert!(source.contains("        #[packed_field(bits=\"6:6\", ty=\"enum\")]\n        pub dir: CrDir,\n"));
+    assert!(source.contains("        pub prescaler: Integer<u16, packed_bits::Bits12>,\n"));
+    assert!(source.contains("        pub type_: Integer<u8, packed_bits::Bits4>,\n"));
+    assert!(source.contains("    pub struct Cnt {\n        #[packed_field(bits=\"15:0\")]\n        pub value: u16,\n    }\n"));
+    assert!(source.contains("size_bytes=\"1\", endian=\"msb\", address=\"0x40010004\", access=\"ro\", schema)]\n    pub struct Sr {"));
+    assert!(source.contains("address=\"0x40010014\", access=\"rw\", schema)]\n    pub struct Ccr1 {"));
+    assert!(source.contains("pub mod timer1 {"));
+    assert!(source.contains("address=\"0x40011004\", access=\"ro\", schema)]\n    pub struct Sr {"));
+}
+
+#[test]
+fn test_generate_ipxact() {
+    let device = parse(UART_IPXACT).unwrap();
+    assert_eq!("uart", device.name);
+    assert_eq!(1, device.blocks.len());
+    assert_eq!(0x1000, device.blocks[0].base_address);
+
+    let source = generate_device(&device);
+    assert!(source.contains("/// UART registers\n#[allow(dead_code, non_camel_case_types)]\npub mod uart_regs {\n    #[allow(unused_imports)]\n"));
+    assert!(source.contains("    /// Offset 0x4, read-write.\n"));
+    assert!(source.contains("    #[packed_struct(bit_numbering=\"lsb0\", size_bytes=\"2\", endian=\"msb\", address=\"0x1004\", access=\"rw\", schema)]\n    pub struct Lcr {\n"));
+    assert!(source.contains("        #[packed_field(bits=\"4:3\", ty=\"enum\")]\n        pub parity: LcrParity,\n"));
+    assert!(source.contains("        #[packed_field(bits=\"15:8\")]\n        pub divisor: u8,\n"));
+}
+
+#[test]
+fn test_errors() {
+    match parse("<device><peripherals>") {
+        Err(Error::Xml(_)) => (),
+        other => panic!("Unexpected result: {:?}", other)
+    }
+
+    match parse("<device><peripheral><name>P</name><register><name>R</name></register></peripheral></device>") {
+        Err(Error::Missing { ref element, .. }) if element == "addressOffset" => (),
+        other => panic!("Unexpected result: {:?}", other)
+    }
+
+    match parse("<device><peripheral><name>P</name><baseAddress>0xG</baseAddress></peripheral></device>") {
+        Err(Error::InvalidNumber(ref n)) if n == "0xG" => (),
+        other => panic!("Unexpected result: {:?}", other)
+    }
+}
@@ -0,0 +1,12 @@
+extern crate packed_struct_svd;
+
+use std::env;
+
+fn main() {
+    let svd = "../packed_struct_svd/tests/fixtures/timer.svd";
+    println!("cargo:rerun-if-changed={}", svd);
+
+    let out = env::var("OUT_DIR").unwrap();
+    let timer_svd = format!("{}/timer_svd.rs", out);
+    packed_struct_svd::generate_file(svd, &timer_svd).unwrap();
+}
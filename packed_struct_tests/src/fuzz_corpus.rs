@@ -0,0 +1,180 @@
+//! The structures that are fuzzed, one for each kind of field that the generated unpacking
+//! handles. New kinds of fields should add a structure here and list it in ```fuzz_structs!```.
+
+#![allow(dead_code)]
+
+use packed_struct::prelude::*;
+
+#[derive(PrimitiveEnum_u8, Debug, Copy, Clone, PartialEq)]
+pub enum Mode {
+    Idle = 0,
+    Run = 1,
+    Sleep = 2
+}
+
+#[derive(PrimitiveEnum, Debug, Copy, Clone, PartialEq)]
+pub enum Trim {
+    Down = -2,
+    Zero = 0,
+    Up = 2
+}
+
+#[derive(PrimitiveEnum_u8, Debug, Copy, Clone, PartialEq)]
+#[primitive(bits = 2)]
+pub enum Direction {
+    North = 0,
+    East = 1,
+    South = 2,
+    West = 3
+}
+
+#[derive(PrimitiveEnum_u8, Debug, Copy, Clone, PartialEq)]
+#[primitive(bits = 3)]
+pub enum Gain {
+    Off = 0,
+    Low = 1,
+    #[primitive(range = "3..=5")]
+    Medium = 2,
+    #[primitive(alias = 7)]
+    High = 6
+}
+
+#[derive(PackedStruct, Debug, Copy, Clone, PartialEq)]
+#[packed_struct(bit_numbering="msb0")]
+pub struct Integers {
+    #[packed_field(bits="0:3")]
+    pub small: Integer<u8, packed_bits::Bits4>,
+    #[packed_field(bits="4:15", endian="msb")]
+    pub signed: Integer<i16, packed_bits::Bits12>,
+    #[packed_field(bytes="2:3", endian="lsb")]
+    pub little: u16,
+    #[packed_field(bytes="4:11", endian="msb")]
+    pub wide: i64,
+    #[packed_field(bits="96..", endian="msb")]
+    pub fifty: Integer<u64, packed_bits::Bits50>
+}
+
+#[derive(PackedStruct, Debug, Copy, Clone, PartialEq)]
+#[packed_struct(size_bytes="2", bit_numbering="lsb0")]
+pub struct Lsb0Fields {
+    #[packed_field(bits="0:3")]
+    pub low: Integer<u8, packed_bits::Bits4>,
+    #[packed_field(bits="15:4", endian="msb")]
+    pub high: Integer<u16, packed_bits::Bits12>
+}
+
+#[derive(PackedStruct, Debug, Copy, Clone, PartialEq)]
+#[packed_struct(bit_numbering="msb0", endian="msb")]
+pub struct Bools {
+    #[packed_field(bits="0")]
+    pub plain: bool,
+    #[packed_field(bits="1", active="low")]
+    pub active_low: bool,
+    #[packed_field(bits="2:3")]
+    pub two_bits: bool,
+    #[packed_field(bits="4:5", bool_true="0b10", bool_false="0b01")]
+    pub patterns: bool,
+    #[packed_field(bits="6:7", bool_mode="nonzero")]
+    pub lenient: bool,
+    #[packed_field(bits="8:23", bool_true="0xA5A5", bool_false="0x0000")]
+    pub unlocked: bool
+}
+
+#[derive(PackedStruct, Debug, Copy, Clone, PartialEq)]
+#[packed_struct(bit_numbering="msb0")]
+pub struct Flags {
+    #[packed_field(bits="0:3")]
+    pub _zero: ReservedZero<packed_bits::Bits4>,
+    #[packed_field(bits="4")]
+    pub flag: bool,
+    #[packed_field(bits="5:7")]
+    pub _one: ReservedOne<packed_bits::Bits3>
+}
+
+#[derive(PackedStruct, Debug, Copy, Clone, PartialEq)]
+#[packed_struct(bit_numbering="msb0")]
+pub struct Enums {
+    #[packed_field(bits="0:1", ty="enum")]
+    pub mode: Mode,
+    #[packed_field(bits="2:5", ty="enum")]
+    pub other: EnumCatchAll<Mode>,
+    #[packed_field(bits="8:15", ty="enum")]
+    pub trim: Trim,
+    #[packed_field(bits="16:17", ty="exhaustive_enum")]
+    pub direction: Direction,
+    #[packed_field(bits="18:20", ty="exhaustive_enum")]
+    pub gain: Gain
+}
+
+#[derive(PackedStruct, Debug, Clone, PartialEq)]
+#[packed_struct(bit_numbering="msb0")]
+pub struct Texts {
+    #[packed_field(bytes="0:7", ty="str", padding="nul")]
+    pub model: String,
+    #[packed_field(bytes="8:11", ty="str", padding="space")]
+    pub serial: FixedString<packed_bits::Bytes4>,
+    #[packed_field(bytes="12:15", ty="str", padding="nul_terminated")]
+    pub revision: FixedString<packed_bits::Bytes4>,
+    #[packed_field(bits="132..", ty="str")]
+    pub unaligned: FixedString<packed_bits::Bytes2>
+}
+
+#[derive(PackedStruct, Default, Debug, Copy, Clone, PartialEq)]
+#[packed_struct(bit_numbering="msb0")]
+pub struct Channel {
+    #[packed_field(bits="0")]
+    pub enabled: bool,
+    #[packed_field(bits="1:7")]
+    pub gain: Integer<u8, packed_bits::Bits7>,
+    #[packed_field(bits="8:15")]
+    pub offset: u8
+}
+
+#[derive(PackedStruct, Debug, PartialEq)]
+#[packed_struct(bit_numbering="msb0")]
+pub struct Arrays {
+    #[packed_field(bytes="0:11", element_size_bytes="2", element_padding_bytes="1")]
+    pub channels: [Channel; 4],
+    #[packed_field(bits="96..", element_stride_bits="6")]
+    pub strided: [Integer<u8, packed_bits::Bits4>; 4],
+    #[packed_field(bits="120:123", element_order="reverse", element_size_bits="1")]
+    pub reversed: [bool; 4],
+    #[packed_field(bits="124..", element_size_bits="2")]
+    pub cube: [[[Integer<u8, packed_bits::Bits2>; 2]; 2]; 2],
+    #[packed_field(bytes="18:23")]
+    pub palette: [[u8; 3]; 2]
+}
+
+#[derive(PackedStruct, Debug, Copy, Clone, PartialEq)]
+#[packed_struct(bit_numbering="msb0")]
+pub struct Nested {
+    #[packed_field(bytes="0")]
+    pub flags: Flags,
+    #[packed_field(bytes="1:2")]
+    pub channel: Channel,
+    #[packed_field(bytes="3:5")]
+    pub enums: Enums
+}
+
+/// The registers generated from the SVD file of the tests.
+pub mod svd {
+    include!(concat!(env!("OUT_DIR"), "/timer_svd.rs"));
+}
+
+macro_rules! fuzz_structs {
+    ($($s: path),*) => {
+        /// Unpacks the bytes into every structure.
+        pub fn fuzz_unpack(data: &[u8]) {
+            $(::packed_struct::fuzz::fuzz_unpack::<$s>(data);)*
+        }
+
+        /// Takes the packed bytes of every structure from the input and unpacks them.
+        pub fn fuzz_packed_bytes(u: &mut ::arbitrary::Unstructured) -> ::arbitrary::Result<()> {
+            $(::packed_struct::fuzz::fuzz_packed_bytes::<$s>(u)?;)*
+            Ok(())
+        }
+    };
+}
+
+fuzz_structs!(Integers, Lsb0Fields, Bools, Flags, Enums, Texts, Channel, Arrays, Nested,
+              svd::timer0::Cr, svd::timer0::Sr, svd::timer0::Cnt);
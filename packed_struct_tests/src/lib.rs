@@ -0,0 +1,9 @@
+//! The structures of the tests that are fuzzed for their unpacking. See the ```fuzz```
+//! directory of the repository.
+
+extern crate arbitrary;
+extern crate packed_struct;
+#[macro_use]
+extern crate packed_struct_codegen;
+
+pub mod fuzz_corpus;
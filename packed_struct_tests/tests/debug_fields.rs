@@ -3,6 +3,7 @@ extern crate packed_struct;
 extern crate packed_struct_codegen;
 
 use packed_struct::prelude::*;
+use packed_struct::schema::BitRange;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PrimitiveEnum_u8)]	
 #[repr(u8)]
@@ -50,4 +51,108 @@ fn test_debug_reg() {
 
     println!("{}", r);
     
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_diff_reg() {
+    let before = ControlRegister4 {
+        output_data_rate: DataRate::Rate_6_25Hz,
+        x_axis_enabled: false,
+        y_axis_enabled: true,
+        z_axis_enabled: true
+    };
+    let after = ControlRegister4 {
+        output_data_rate: DataRate::Rate_100Hz,
+        x_axis_enabled: true,
+        ..before
+    };
+
+    let changes: Vec<_> = before.diff(&after).collect();
+    assert_eq!(2, changes.len());
+    assert_eq!("output_data_rate", changes[0].name);
+    assert_eq!(BitRange { start: 0, end: 3 }, changes[0].bits);
+    assert_eq!("Rate_6_25Hz", changes[0].old_value);
+    assert_eq!("Rate_100Hz", changes[0].new_value);
+    assert_eq!("x_axis_enabled", changes[1].name);
+    assert_eq!("true", changes[1].new_value);
+
+    assert_eq!(0, before.diff(&before).count());
+
+    let rendered = format!("{}", before.packed_struct_diff_formatter(&after));
+    println!("{}", rendered);
+    assert!(rendered.contains("*  output_data_rate | bits   0:3   | 0b0110 | \"Rate_6_25Hz\" -> \"Rate_100Hz\"\r\n                    |              |    ^   |"));
+    assert!(rendered.contains("     y_axis_enabled | bits   6:6   | 0b1    | \"true\"\r\n"));
+}
+
+#[test]
+fn test_diff_options() {
+    use packed_struct::debug_fmt::*;
+
+    let before = ControlRegister4 {
+        output_data_rate: DataRate::Rate_6_25Hz,
+        x_axis_enabled: false,
+        y_axis_enabled: true,
+        z_axis_enabled: true
+    };
+    let after = ControlRegister4 {
+        output_data_rate: DataRate::Rate_100Hz,
+        ..before
+    };
+
+    let options = DisplayOptions::new()
+        .line_ending("\n")
+        .bit_numbering(DisplayBitNumbering::Lsb0)
+        .field_value_format("y_axis_enabled", FieldValueFormat::Decimal);
+    let text = format!("{}", before.packed_struct_diff_formatter(&after).with_options(options));
+    assert!(text.starts_with("ControlRegister4 (1 byte)\n\n"));
+    assert!(text.contains("*  output_data_rate | bits   4:7   | 0b0110 | \"Rate_6_25Hz\" -> \"Rate_100Hz\"\n                    |              |    ^   |\n"));
+    assert!(text.contains("     y_axis_enabled | bits   1:1   | 1      | \"true\"\n"));
+    assert!(!text.contains('\r'));
+
+    let mut display = before.packed_struct_diff_formatter(&after).with_options(DisplayOptions::new().markdown());
+    display.header = false;
+    assert_eq!("| | Field | Bits | Raw | Value |\r\n\
+                |-|-------|------|-----|-------|\r\n\
+                | * | output_data_rate | 0:3 | `0b0110` | Rate_6_25Hz -> Rate_100Hz |\r\n\
+                |   | x_axis_enabled | 5 | `0b0` | false |\r\n\
+                |   | y_axis_enabled | 6 | `0b1` | true |\r\n\
+                |   | z_axis_enabled | 7 | `0b1` | true |\r\n", format!("{}", display));
+}
+
+#[test]
+fn test_display_options() {
+    use packed_struct::debug_fmt::*;
+
+    let r = ControlRegister4 {
+        output_data_rate: DataRate::Rate_1600Hz,
+        x_axis_enabled: false,
+        y_axis_enabled: true,
+        z_axis_enabled: true
+    };
+
+    let options = DisplayOptions::new()
+        .line_ending("\n")
+        .bit_numbering(DisplayBitNumbering::Lsb0)
+        .field_value_format("output_data_rate", FieldValueFormat::Hex);
+    let text = format!("{}", r.packed_struct_display_formatter().with_options(options));
+    assert!(text.starts_with("ControlRegister4 (1 byte)\n\nDecimal\n[147]\n\nHex\n[0x93]\n"));
+    assert!(text.contains(" output_data_rate | bits   4:7   | 0x9 | \"Rate_1600Hz\"\n"));
+    assert!(text.contains("   z_axis_enabled | bits   0:0   | 0b1 | \"true\"\n"));
+    assert!(!text.contains('\r'));
+
+    let mut display = r.packed_struct_display_formatter().with_options(DisplayOptions::new().markdown().group_nibbles(true));
+    display.raw_decimal = false;
+    display.raw_binary = false;
+    let markdown = format!("{}", display);
+    assert_eq!("### ControlRegister4 (1 byte)\r\n\r\n\
+                Hex: `[0x93]`\r\n\r\n\
+                | Field | Bits | Raw | Value |\r\n\
+                |-------|------|-----|-------|\r\n\
+                | output_data_rate | 0:3 | `0b1001` | Rate_1600Hz |\r\n\
+                | x_axis_enabled | 5 | `0b0` | false |\r\n\
+                | y_axis_enabled | 6 | `0b1` | true |\r\n\
+                | z_axis_enabled | 7 | `0b1` | true |\r\n", markdown);
+
+    let colored = format!("{}", r.packed_struct_display_formatter().with_options(DisplayOptions::new().ansi_colors(true)));
+    assert!(colored.contains("\x1b[1m output_data_rate\x1b[0m | bits   0:3   | \x1b[33m0b1001\x1b[0m | \x1b[32m\"Rate_1600Hz\"\x1b[0m\r\n"));
+}
@@ -36,3 +36,29 @@ fn test_debug_jedec_id() {
 
     println!("{}", id);
 }
+
+#[derive(PackedStruct, Debug, Copy, Clone, Default)]
+#[packed_struct(endian="msb")]
+pub struct Serial {
+    pub flags: u8,
+    pub serial: u64
+}
+
+#[test]
+fn test_debug_wide_fields() {
+    use packed_struct::debug_fmt::*;
+
+    let serial = Serial { flags: 0x0F, serial: 0x0123_4567_89AB_CDEF };
+
+    let plain = format!("{}", serial);
+    assert!(plain.contains(" serial | \"81985529216486895\"\r\n"));
+
+    let options = DisplayOptions::new().wide_fields(true).value_format(FieldValueFormat::Hex).group_nibbles(true);
+    let wide = format!("{}", serial.packed_struct_display_formatter().with_options(options));
+    assert!(wide.contains(" serial | bits   8:71  | 0x0123_4567_89AB_CDEF | \"81985529216486895\"\r\n"));
+    assert!(wide.contains("  flags | bits   0:7   | 0x0F                  | \"15\"\r\n"));
+
+    let options = DisplayOptions::new().wide_fields(true).group_nibbles(true);
+    let binary = format!("{}", serial.packed_struct_display_formatter().with_options(options));
+    assert!(binary.contains("  flags | bits   0:7   | 0b0000_1111 "));
+}
@@ -0,0 +1,71 @@
+extern crate packed_struct;
+#[macro_use]
+extern crate packed_struct_codegen;
+
+use packed_struct::prelude::*;
+use packed_struct::export::CHeader;
+
+mod fixtures;
+use fixtures::*;
+
+/// Control register of the device, small enough for an integer
+#[derive(PackedStruct, Debug, Copy, Clone, PartialEq)]
+#[packed_struct(bit_numbering="msb0", schema)]
+pub struct Config {
+    #[packed_field(bits="0:2", ty="enum")]
+    pub mode: PowerMode,
+    #[packed_field(bytes="1:2", endian="lsb")]
+    pub counter: u16,
+    #[packed_field(bytes="3")]
+    pub status: Status
+}
+
+#[derive(PackedStruct, Debug, Clone, PartialEq)]
+#[packed_struct(bit_numbering="msb0", schema)]
+pub struct Identification {
+    #[packed_field(bytes="0:15", ty="str")]
+    pub name: String,
+    #[packed_field(bits="132:143", endian="msb")]
+    pub id: Integer<u16, packed_bits::Bits12>
+}
+
+#[test]
+fn test_c_header_register() {
+    let header = CHeader::new("CONFIG_H")
+        .add_struct(Config::packed_struct_schema())
+        .to_string();
+
+    assert!(header.starts_with("/* Generated by packed_struct, do not edit. */\n#ifndef CONFIG_H\n#define CONFIG_H\n"));
+    assert!(header.ends_with("#endif /* CONFIG_H */\n"));
+
+    let enum_typedef = "typedef enum {\n    POWER_MODE_OFF = 0, /* Everything is off */\n    POWER_MODE_SLEEP = 1,\n    POWER_MODE_ACTIVE = 5 /* Fully powered */\n} power_mode_t;\n";
+    assert!(header.contains(enum_typedef));
+
+    // the nested structure is declared before it is used
+    let status = header.find("#define STATUS_SIZE 1\n").unwrap();
+    let config = header.find("#define CONFIG_SIZE 4\n").unwrap();
+    assert!(status < config);
+
+    assert!(header.contains("_Static_assert(CONFIG_MODE_OFFSET + CONFIG_MODE_BYTES <= CONFIG_SIZE && CONFIG_MODE_WIDTH <= CONFIG_MODE_BYTES * 8, \"mode has to fit into config_t\");"));
+    assert!(header.contains("/* mode: PowerMode, bits 0:2 (MSB0) */\n#define CONFIG_MODE_OFFSET 0\n#define CONFIG_MODE_BYTES 4\n#define CONFIG_MODE_WIDTH 3\n#define CONFIG_MODE_SHIFT 29\n#define CONFIG_MODE_MASK 0xE0000000ULL\n"));
+    assert!(header.contains("/* counter: u16, bits 8:23 (MSB0), stored as little endian */"));
+    assert!(header.contains("#define CONFIG_COUNTER_MASK 0x00FFFF00ULL\n"));
+    // the little endian counter is swapped by its accessors
+    assert!(header.contains("static inline uint64_t packed_struct_swap_bytes(uint64_t x, unsigned bytes) {"));
+    assert!(header.contains("#define CONFIG_COUNTER_GET(v) packed_struct_swap_bytes(((uint64_t)(v) & CONFIG_COUNTER_MASK) >> CONFIG_COUNTER_SHIFT, 2)\n"));
+    assert!(header.contains("#define CONFIG_COUNTER_SET(v, x) (((uint64_t)(v) & ~CONFIG_COUNTER_MASK) | ((packed_struct_swap_bytes((uint64_t)(x), 2) << CONFIG_COUNTER_SHIFT) & CONFIG_COUNTER_MASK))\n"));
+    assert!(header.contains("#define STATUS_RESERVED_MASK 0x70ULL\n"));
+    assert!(header.contains("#define STATUS_LEVEL_GET(v) (((uint64_t)(v) & STATUS_LEVEL_MASK) >> STATUS_LEVEL_SHIFT)\n"));
+}
+
+#[test]
+fn test_c_header_large_struct() {
+    let header = CHeader::new("IDENTIFICATION_H")
+        .add_struct(Identification::packed_struct_schema())
+        .to_string();
+
+    // text fields that don't fit into an integer only get their position
+    assert!(header.contains("#define IDENTIFICATION_NAME_OFFSET 0\n#define IDENTIFICATION_NAME_BYTES 16\n#define IDENTIFICATION_NAME_WIDTH 128\n_Static_assert(IDENTIFICATION_NAME_OFFSET + IDENTIFICATION_NAME_BYTES <= IDENTIFICATION_SIZE && IDENTIFICATION_NAME_WIDTH <= IDENTIFICATION_NAME_BYTES * 8, \"name has to fit into identification_t\");\n\n"));
+    assert!(!header.contains("packed_struct_swap_bytes"));
+    assert!(header.contains("#define IDENTIFICATION_ID_OFFSET 16\n#define IDENTIFICATION_ID_BYTES 2\n#define IDENTIFICATION_ID_WIDTH 12\n#define IDENTIFICATION_ID_SHIFT 0\n#define IDENTIFICATION_ID_MASK 0x0FFFULL\n"));
+}
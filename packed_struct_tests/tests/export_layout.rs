@@ -0,0 +1,135 @@
+extern crate packed_struct;
+#[macro_use]
+extern crate packed_struct_codegen;
+extern crate serde_json;
+extern crate yaml_rust;
+
+use packed_struct::prelude::*;
+use packed_struct::export::*;
+
+use yaml_rust::YamlLoader;
+
+mod fixtures;
+use fixtures::*;
+
+#[derive(PackedStruct, Debug, Clone, PartialEq)]
+#[packed_struct(bit_numbering="msb0", schema)]
+pub struct Record {
+    #[packed_field(bytes="0:3", ty="str", padding="space")]
+    pub name: String,
+    #[packed_field(bytes="4:7", ty="str", padding="nul_terminated")]
+    pub version: String,
+    #[packed_field(bytes="8:12", endian="msb")]
+    pub timestamp: Integer<u64, packed_bits::Bits40>,
+    #[packed_field(bits="108:127", endian="msb")]
+    pub offset: Integer<i32, packed_bits::Bits20>
+}
+
+#[test]
+fn test_json_layout() {
+    let json = JsonLayout::new().add_struct(Control::packed_struct_schema()).to_string();
+    let layout: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+    let structs = layout["structs"].as_array().unwrap();
+    assert_eq!(2, structs.len());
+    assert_eq!("Status", structs[0]["name"]);
+
+    let control = &structs[1];
+    assert_eq!("Control", control["name"]);
+    assert_eq!("Control register of the \"device\"", control["doc"]);
+    assert_eq!(10, control["num_bytes"]);
+
+    let fields = control["fields"].as_array().unwrap();
+    assert_eq!(7, fields.len());
+    assert_eq!("mode", fields[0]["name"]);
+    assert_eq!("enum", fields[0]["kind"]);
+    assert_eq!("PowerMode", fields[0]["enum"]);
+    assert_eq!("The power mode\nof the device", fields[0]["doc"]);
+    assert_eq!(serde_json::json!([0, 2]), fields[0]["bits_msb0"]);
+    assert_eq!(serde_json::json!([77, 79]), fields[0]["bits_lsb0"]);
+    assert_eq!("enum", fields[1]["kind"]);
+    assert_eq!("lsb", fields[2]["endianness"]);
+    assert_eq!("Status", fields[3]["struct"]);
+    assert_eq!("history[1]", fields[5]["name"]);
+    assert_eq!("str", fields[6]["kind"]);
+
+    let enums = layout["enums"].as_array().unwrap();
+    assert_eq!(1, enums.len());
+    assert_eq!("u8", enums[0]["primitive"]);
+    assert_eq!(5, enums[0]["variants"][2]["value"]);
+    assert_eq!("Everything is off", enums[0]["variants"][0]["doc"]);
+}
+
+#[test]
+fn test_kaitai_struct() {
+    let ksy = KaitaiStruct::new(Control::packed_struct_schema()).to_string();
+    let doc = &YamlLoader::load_from_str(&ksy).unwrap()[0];
+
+    assert_eq!("control", doc["meta"]["id"].as_str().unwrap());
+    assert_eq!("be", doc["meta"]["bit-endian"].as_str().unwrap());
+    assert_eq!("Control register of the \"device\"", doc["doc"].as_str().unwrap());
+
+    let seq = doc["seq"].as_vec().unwrap();
+    let ids: Vec<_> = seq.iter().map(|s| s["id"].as_str().unwrap()).collect();
+    assert_eq!(vec!["mode", "other", "counter", "status", "history_0", "history_1", "label"], ids);
+
+    assert_eq!("b3", seq[0]["type"].as_str().unwrap());
+    assert_eq!("power_mode", seq[0]["enum"].as_str().unwrap());
+    assert_eq!("b5", seq[1]["type"].as_str().unwrap());
+    assert_eq!("power_mode", seq[1]["enum"].as_str().unwrap());
+    assert_eq!("u2le", seq[2]["type"].as_str().unwrap());
+    assert_eq!("status", seq[3]["type"].as_str().unwrap());
+    assert_eq!("u1", seq[4]["type"].as_str().unwrap());
+    assert_eq!("str", seq[6]["type"].as_str().unwrap());
+    assert_eq!(4, seq[6]["size"].as_i64().unwrap());
+    assert_eq!(0, seq[6]["pad-right"].as_i64().unwrap());
+    assert!(seq[6]["terminator"].is_badvalue());
+
+    let status = doc["types"]["status"]["seq"].as_vec().unwrap();
+    assert_eq!(3, status.len());
+    assert_eq!("b1", status[0]["type"].as_str().unwrap());
+    assert_eq!("reserved", status[1]["id"].as_str().unwrap());
+
+    assert_eq!("active", doc["enums"]["power_mode"][5].as_str().unwrap());
+}
+
+#[test]
+fn test_kaitai_struct_padding() {
+    let ksy = KaitaiStruct::new(Record::packed_struct_schema()).to_string();
+    let doc = &YamlLoader::load_from_str(&ksy).unwrap()[0];
+    let seq = doc["seq"].as_vec().unwrap();
+
+    assert_eq!(0x20, seq[0]["pad-right"].as_i64().unwrap());
+    assert!(seq[0]["terminator"].is_badvalue());
+    assert_eq!(0, seq[1]["terminator"].as_i64().unwrap());
+    assert!(seq[1]["pad-right"].is_badvalue());
+}
+
+#[test]
+fn test_wireshark_dissector() {
+    let lua = WiresharkDissector::new(Control::packed_struct_schema()).to_string();
+
+    assert!(lua.contains("local control_proto = Proto(\"control\", \"Control\")\n"));
+    assert!(lua.contains("local power_mode_values = {\n    [0] = \"Off\",\n    [1] = \"Sleep\",\n    [5] = \"Active\",\n}\n"));
+    assert!(lua.contains("fields.mode = ProtoField.uint8(\"control.mode\", \"mode\", base.DEC, power_mode_values, 0xE0, \"The power mode of the device\")\n"));
+    assert!(lua.contains("fields.other = ProtoField.uint8(\"control.other\", \"other\", base.DEC, power_mode_values, 0x1F, nil)\n"));
+    assert!(lua.contains("fields.counter = ProtoField.uint16(\"control.counter\", \"counter\", base.HEX, nil, nil, nil)\n"));
+    assert!(lua.contains("fields.status__ready = ProtoField.bool(\"control.status.ready\", \"ready\", 8, nil, 0x80, nil)\n"));
+    assert!(lua.contains("fields.status__level = ProtoField.int8(\"control.status.level\", \"level\", base.DEC, nil, 0xF, nil)\n"));
+    assert!(lua.contains("fields.label = ProtoField.string(\"control.label\", \"label\", base.ASCII, nil)\n"));
+
+    assert!(lua.contains("    if buffer:len() < 10 then return 0 end\n"));
+    assert!(lua.contains("    subtree:add_le(fields.counter, buffer(1, 2))\n"));
+    assert!(lua.contains("    local status_tree = subtree:add(buffer(3, 1), \"status\")\n    status_tree:add(fields.status__ready, buffer(3, 1))\n"));
+    assert!(lua.contains("    subtree:add(fields.history_1, buffer(5, 1))\n"));
+    assert!(lua.contains("    return 10\nend\n"));
+}
+
+#[test]
+fn test_wireshark_dissector_widths() {
+    let lua = WiresharkDissector::new(Record::packed_struct_schema()).to_string();
+
+    assert!(lua.contains("fields.timestamp = ProtoField.new(\"timestamp\", \"record.timestamp\", ftypes.UINT40, nil, base.HEX, nil, nil)\n"));
+    // the 20 bits span three bytes
+    assert!(lua.contains("fields.offset = ProtoField.int24(\"record.offset\", \"offset\", base.DEC, nil, 0xFFFFF, nil)\n"));
+}
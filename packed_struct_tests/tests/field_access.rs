@@ -0,0 +1,96 @@
+extern crate packed_struct;
+#[macro_use]
+extern crate packed_struct_codegen;
+
+use packed_struct::prelude::*;
+
+mod fixtures;
+use fixtures::*;
+
+#[test]
+fn test_get_field() {
+    let c = control();
+
+    assert_eq!(Some(FieldValue::Enum { name: Some("Sleep"), value: 1 }), c.get_field("mode"));
+    assert_eq!(Some(FieldValue::Enum { name: None, value: 9 }), c.get_field("other"));
+    assert_eq!(Some(FieldValue::Unsigned(1000)), c.get_field("counter"));
+    assert_eq!(Some(FieldValue::Unsigned(2)), c.get_field("history[1]"));
+    assert_eq!(Some(FieldValue::Str("ab")), c.get_field("label"));
+    assert_eq!(Some(FieldValue::Bool(true)), c.get_field("status.ready"));
+    assert_eq!(Some(FieldValue::Signed(-3)), c.get_field("status.level"));
+    assert_eq!("Sleep", c.get_field("mode").unwrap().to_string());
+
+    match c.get_field("status") {
+        Some(FieldValue::Struct(s)) => assert_eq!(Some(FieldValue::Bool(true)), s.get_field("ready")),
+        v => panic!("Unexpected value {:?}", v)
+    }
+
+    assert_eq!(None, c.get_field("status._reserved"));
+    assert_eq!(None, c.get_field("history[2]"));
+    assert_eq!(None, c.get_field("missing"));
+}
+
+#[test]
+fn test_set_field() {
+    let mut c = control();
+
+    c.set_field("mode", FieldValue::Enum { name: Some("Active"), value: 0 }).unwrap();
+    assert_eq!(PowerMode::Active, c.mode);
+    c.set_field("mode", FieldValue::Unsigned(0)).unwrap();
+    assert_eq!(PowerMode::Off, c.mode);
+    c.set_field("other", FieldValue::Enum { name: None, value: 31 }).unwrap();
+    assert_eq!(EnumCatchAll::CatchAll(31), c.other);
+    c.set_field("counter", FieldValue::Unsigned(0xBEEF)).unwrap();
+    assert_eq!(0xBEEF, c.counter);
+    c.set_field("history[0]", FieldValue::Unsigned(7)).unwrap();
+    assert_eq!([7, 2], c.history);
+    c.set_field("label", FieldValue::Str("wxyz")).unwrap();
+    assert_eq!("wxyz", c.label.as_str());
+    c.set_field("status.ready", FieldValue::Bool(false)).unwrap();
+    c.set_field("status.level", FieldValue::Signed(7)).unwrap();
+    assert_eq!(Status { ready: false, _reserved: Default::default(), level: 7.into() }, c.status);
+
+    let unpacked = Control::unpack(&c.pack()).unwrap();
+    assert_eq!(c, unpacked);
+}
+
+#[test]
+fn test_set_field_errors() {
+    let mut c = control();
+
+    assert_eq!(Err(FieldAccessError::UnknownField), c.set_field("missing", FieldValue::Bool(true)));
+    assert_eq!(Err(FieldAccessError::UnknownField), c.set_field("status._reserved", FieldValue::Unsigned(0)));
+    assert_eq!(Err(FieldAccessError::InvalidValue), c.set_field("mode", FieldValue::Enum { name: Some("Turbo"), value: 0 }));
+    assert_eq!(Err(FieldAccessError::InvalidValue), c.set_field("mode", FieldValue::Unsigned(2)));
+    assert_eq!(Err(FieldAccessError::InvalidValue), c.set_field("other", FieldValue::Unsigned(32)));
+    assert_eq!(Err(FieldAccessError::InvalidValue), c.set_field("counter", FieldValue::Unsigned(0x10000)));
+    assert_eq!(Err(FieldAccessError::InvalidValue), c.set_field("counter", FieldValue::Signed(-1)));
+    assert_eq!(Err(FieldAccessError::InvalidValue), c.set_field("status.level", FieldValue::Signed(8)));
+    assert_eq!(Err(FieldAccessError::InvalidValue), c.set_field("status.ready", FieldValue::Unsigned(1)));
+    assert_eq!(Err(FieldAccessError::TooLong), c.set_field("label", FieldValue::Str("too long")));
+    assert_eq!(control(), c);
+}
+
+#[derive(PackedStruct, Debug)]
+#[packed_struct(bit_numbering="msb0", schema)]
+pub struct Explicit {
+    #[packed_field(bytes="0:1")]
+    msb: MsbInteger<u16, packed_bits::Bits16, Integer<u16, packed_bits::Bits16>>,
+    #[packed_field(bytes="2:3")]
+    lsb: LsbInteger<i16, packed_bits::Bits16, Integer<i16, packed_bits::Bits16>>
+}
+
+#[test]
+fn test_field_access_explicit_endianness() {
+    let msb: Integer<u16, packed_bits::Bits16> = 0x1234.into();
+    let lsb: Integer<i16, packed_bits::Bits16> = (-2).into();
+    let mut e = Explicit { msb: msb.into(), lsb: lsb.into() };
+    assert_eq!(Some(FieldValue::Unsigned(0x1234)), e.get_field("msb"));
+    assert_eq!(Some(FieldValue::Signed(-2)), e.get_field("lsb"));
+
+    e.set_field("msb", FieldValue::Unsigned(0xBEEF)).unwrap();
+    e.set_field("lsb", FieldValue::Signed(-300)).unwrap();
+    assert_eq!([0xBE, 0xEF, 0xD4, 0xFE], e.pack());
+    assert_eq!(Err(FieldAccessError::InvalidValue), e.set_field("msb", FieldValue::Signed(-1)));
+    assert_eq!(Err(FieldAccessError::InvalidValue), e.set_field("lsb", FieldValue::Signed(0x8000)));
+}
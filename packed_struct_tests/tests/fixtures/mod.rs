@@ -0,0 +1,56 @@
+//! The structures shared by the tests of the schema and of the features that are built on it.
+
+#![allow(dead_code)]
+
+use packed_struct::prelude::*;
+
+#[derive(PrimitiveEnum_u8, PartialEq, Debug, Clone, Copy)]
+pub enum PowerMode {
+    /// Everything is off
+    Off = 0,
+    Sleep = 1,
+    /// Fully powered
+    Active = 5
+}
+
+#[derive(PackedStruct, Debug, Copy, Clone, PartialEq)]
+#[packed_struct(bit_numbering="msb0", schema)]
+pub struct Status {
+    #[packed_field(bits="0")]
+    pub ready: bool,
+    #[packed_field(bits="1:3")]
+    pub _reserved: ReservedZero<packed_bits::Bits3>,
+    #[packed_field(bits="4:7")]
+    pub level: Integer<i8, packed_bits::Bits4>
+}
+
+/// Control register of the "device"
+#[derive(PackedStruct, Debug, Copy, Clone, PartialEq)]
+#[packed_struct(bit_numbering="msb0", schema, debug_write)]
+pub struct Control {
+    /// The power mode
+    /// of the device
+    #[packed_field(bits="0:2", ty="enum")]
+    pub mode: PowerMode,
+    #[packed_field(bits="3:7", ty="enum")]
+    pub other: EnumCatchAll<PowerMode>,
+    #[packed_field(bytes="1:2", endian="lsb")]
+    pub counter: u16,
+    #[packed_field(bytes="3")]
+    pub status: Status,
+    #[packed_field(bytes="4:5")]
+    pub history: [u8; 2],
+    #[packed_field(bytes="6:9", ty="str")]
+    pub label: FixedString<packed_bits::Bytes4>
+}
+
+pub fn control() -> Control {
+    Control {
+        mode: PowerMode::Sleep,
+        other: EnumCatchAll::CatchAll(9),
+        counter: 1000,
+        status: Status { ready: true, _reserved: Default::default(), level: (-3).into() },
+        history: [1, 2],
+        label: FixedString::new("ab").unwrap()
+    }
+}
@@ -0,0 +1,37 @@
+extern crate arbitrary;
+extern crate packed_struct_tests;
+
+mod common;
+use common::*;
+
+use arbitrary::Unstructured;
+use packed_struct_tests::fuzz_corpus;
+
+fn random_bytes(rnd: &mut Rnd, len: usize) -> Vec<u8> {
+    (0..len).map(|_| (rnd.next() >> 16) as u8).collect()
+}
+
+#[test]
+fn test_unpack_random_bytes() {
+    let mut rnd = Rnd::new(1);
+
+    fuzz_corpus::fuzz_unpack(&[]);
+    for _ in 0..1600 {
+        let len = rnd.next_num(128) as usize;
+        fuzz_corpus::fuzz_unpack(&random_bytes(&mut rnd, len));
+    }
+    fuzz_corpus::fuzz_unpack(&[0xFF; 128]);
+    fuzz_corpus::fuzz_unpack(&[0x00; 128]);
+}
+
+#[test]
+fn test_unpack_random_packed_bytes() {
+    let mut rnd = Rnd::new(2);
+
+    for _ in 0..200 {
+        let len = rnd.next_num_range(256, 4096) as usize;
+        let data = random_bytes(&mut rnd, len);
+        let mut u = Unstructured::new(&data);
+        fuzz_corpus::fuzz_packed_bytes(&mut u).unwrap();
+    }
+}
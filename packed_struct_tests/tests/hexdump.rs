@@ -0,0 +1,147 @@
+extern crate packed_struct;
+#[macro_use]
+extern crate packed_struct_codegen;
+
+use packed_struct::prelude::*;
+use packed_struct::hexdump::*;
+use packed_struct::schema::BitRange;
+
+#[derive(PrimitiveEnum_u8, Copy, Clone, Debug, PartialEq)]
+pub enum Mode {
+    Idle = 0,
+    Run = 1,
+    Sleep = 2
+}
+
+#[derive(PrimitiveEnum, Copy, Clone, Debug, PartialEq)]
+pub enum Trim {
+    Down = -2,
+    Zero = 0,
+    Up = 2
+}
+
+#[derive(PackedStruct, Debug, PartialEq)]
+#[packed_struct(bit_numbering="msb0", endian="msb", schema)]
+pub struct Settings {
+    #[packed_field(bits="0", active="low")]
+    enabled: bool,
+    #[packed_field(bits="1:3", ty="enum")]
+    trim: Trim,
+    #[packed_field(bits="4:7")]
+    _reserved: ReservedZero<packed_bits::Bits4>,
+    #[packed_field(bits="8:23", bool_true="0xA5A5", bool_false="0x0000")]
+    unlocked: bool,
+    #[packed_field(bytes="3:6", ty="str", padding="space")]
+    model: String,
+    #[packed_field(bytes="7:9", ty="str", padding="nul_terminated")]
+    revision: String
+}
+
+#[derive(PackedStruct, Debug, PartialEq)]
+#[packed_struct(bit_numbering="msb0", schema)]
+pub struct Flags {
+    #[packed_field(bits="0")]
+    ready: bool,
+    #[packed_field(bits="1:3")]
+    _reserved: ReservedOne<packed_bits::Bits3>,
+    #[packed_field(bits="4:7")]
+    level: Integer<i8, packed_bits::Bits4>
+}
+
+#[derive(PackedStruct, Debug, PartialEq)]
+#[packed_struct(bit_numbering="msb0", schema)]
+pub struct Frame {
+    #[packed_field(bits="0:7", ty="enum")]
+    mode: Mode,
+    #[packed_field(bytes="1")]
+    flags: Flags,
+    #[packed_field(bytes="2:3", endian="lsb")]
+    counter: u16,
+    #[packed_field(bytes="4:7", ty="str")]
+    label: String
+}
+
+#[test]
+fn test_decode_valid_bytes() {
+    let frame = Frame {
+        mode: Mode::Sleep,
+        flags: Flags { ready: true, _reserved: Default::default(), level: (-3).into() },
+        counter: 0x1234,
+        label: "ab".into()
+    };
+    let packed = frame.pack();
+    let fields = decode_fields(Frame::packed_struct_schema(), &packed);
+
+    let values: Vec<_> = fields.iter().map(|f| (f.name.as_str(), f.value.clone().unwrap())).collect();
+    assert_eq!(vec![
+        ("mode", "Sleep".to_string()),
+        ("flags.ready", "true".to_string()),
+        ("flags._reserved", "0b111".to_string()),
+        ("flags.level", "-3".to_string()),
+        ("counter", "4660".to_string()),
+        ("label", "\"ab\"".to_string())
+    ], values);
+    assert_eq!(BitRange { start: 12, end: 15 }, fields[3].bits);
+}
+
+#[test]
+fn test_decode_invalid_bytes() {
+    let bytes = [9, 0b1010_0000, 0x34, 0x12, 0xFF, 0xFE, 0, 0];
+    assert!(Frame::unpack_from_slice(&bytes).is_err());
+
+    let fields = decode_fields(Frame::packed_struct_schema(), &bytes);
+    assert_eq!(Err(FieldDecodeError::InvalidEnumValue(9)), fields[0].value);
+    assert_eq!(Err(FieldDecodeError::InvalidReservedBits { expected: 0b111, actual: 0b010 }), fields[2].value);
+    assert_eq!(Ok("4660".to_string()), fields[4].value);
+    assert_eq!(Err(FieldDecodeError::InvalidUtf8), fields[5].value);
+
+    let dump = annotated_hexdump::<Frame>(&bytes).to_string();
+    println!("{}", dump);
+    assert!(dump.starts_with("Frame (8 of 8 bytes)\r\n"));
+    assert!(dump.contains("0000  09  00001001  mode [0:7] = invalid enum value 9\r\n"));
+    assert!(dump.contains("0001  A0  10100000  flags.ready [8] = true, flags._reserved [9:11] = 0b010, expected 0b111, flags.level [12:15] = 0\r\n"));
+    assert!(dump.contains("0005  FE  11111110  label [32:63] = invalid UTF-8\r\n"));
+}
+
+#[test]
+fn test_hexdump_truncated_and_trailing() {
+    let dump = annotated_hexdump::<Frame>(&[1, 0x70, 0x01]).to_string();
+    assert!(dump.starts_with("Frame (3 of 8 bytes)\r\n"));
+    assert!(dump.contains("0002  01  00000001  counter [16:31] = truncated\r\n"));
+    assert!(dump.contains("0003  --  --------  counter [16:31] = truncated\r\n"));
+
+    let dump = annotated_hexdump::<Flags>(&[0x70, 0xAA]).to_string();
+    assert!(dump.contains("0001  AA  10101010  (trailing)\r\n"));
+}
+
+#[test]
+fn test_decode_field_attributes() {
+    let settings = Settings {
+        enabled: true,
+        trim: Trim::Down,
+        _reserved: Default::default(),
+        unlocked: true,
+        model: "ab".into(),
+        revision: "c".into()
+    };
+    let packed = settings.pack();
+    let fields = decode_fields(Settings::packed_struct_schema(), &packed);
+    let values: Vec<_> = fields.iter().map(|f| (f.name.as_str(), f.value.clone().unwrap())).collect();
+    assert_eq!(vec![
+        ("enabled", "true".to_string()),
+        ("trim", "Down".to_string()),
+        ("_reserved", "0b0000".to_string()),
+        ("unlocked", "true".to_string()),
+        ("model", "\"ab\"".to_string()),
+        ("revision", "\"c\"".to_string())
+    ], values);
+
+    // an active high bit, an unknown negative trim, a broken pattern and a missing terminator
+    let bytes = [0b1101_0000, 0xA5, 0x00, b'a', b' ', b' ', b' ', b'x', b'y', b'z'];
+    let fields = decode_fields(Settings::packed_struct_schema(), &bytes);
+    assert_eq!(Ok("false".to_string()), fields[0].value);
+    assert_eq!(Err(FieldDecodeError::InvalidEnumValue(-3)), fields[1].value);
+    assert_eq!(Err(FieldDecodeError::InvalidBoolPattern(0xA500)), fields[3].value);
+    assert_eq!(Ok("\"a\"".to_string()), fields[4].value);
+    assert_eq!(Err(FieldDecodeError::MissingTerminator), fields[5].value);
+}
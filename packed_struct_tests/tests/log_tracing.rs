@@ -0,0 +1,147 @@
+extern crate packed_struct;
+#[macro_use]
+extern crate packed_struct_codegen;
+extern crate defmt;
+extern crate log;
+extern crate tracing;
+
+use packed_struct::prelude::*;
+use packed_struct::log_kv::*;
+use packed_struct::tracing_event::PackedStructTracing;
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+mod fixtures;
+use fixtures::*;
+
+fn expected_fields() -> Vec<(String, String)> {
+    [("mode", "Sleep"), ("other", "CatchAll(9)"), ("counter", "1000"), ("status", "Status { ready: true, _reserved: Reserved - always 0, level: 13 }"),
+     ("history[0]", "1"), ("history[1]", "2"), ("label", "\"ab\"")]
+        .iter().map(|&(k, v)| (k.to_string(), v.to_string())).collect()
+}
+
+struct CollectPairs(Vec<(String, String)>);
+
+impl<'kvs> log::kv::VisitSource<'kvs> for CollectPairs {
+    fn visit_pair(&mut self, key: log::kv::Key<'kvs>, value: log::kv::Value<'kvs>) -> Result<(), log::kv::Error> {
+        self.0.push((key.to_string(), value.to_string()));
+        Ok(())
+    }
+}
+
+struct TestLogger {
+    records: Mutex<Vec<(String, Vec<(String, String)>)>>
+}
+
+impl log::Log for TestLogger {
+    fn enabled(&self, _: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        let mut pairs = CollectPairs(vec![]);
+        record.key_values().visit(&mut pairs).unwrap();
+        self.records.lock().unwrap().push((record.args().to_string(), pairs.0));
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: TestLogger = TestLogger { records: Mutex::new(Vec::new()) };
+
+#[test]
+fn test_log_fields() {
+    use log::kv::Source;
+
+    let control = control();
+    let fields = LogFields::new(&control);
+    assert_eq!(7, fields.count());
+
+    let mut pairs = CollectPairs(vec![]);
+    fields.visit(&mut pairs).unwrap();
+    assert_eq!(expected_fields(), pairs.0);
+
+    log::set_logger(&LOGGER).unwrap();
+    log::set_max_level(log::LevelFilter::Info);
+    log_fields(module_path!(), Level::Debug, &control);
+    log_fields(module_path!(), Level::Info, &control);
+
+    let records = LOGGER.records.lock().unwrap();
+    assert_eq!(vec![("Control".to_string(), expected_fields())], *records);
+}
+
+#[derive(Clone, Default)]
+struct CollectEvents {
+    events: Arc<Mutex<Vec<(tracing::Level, Vec<(String, String)>)>>>
+}
+
+struct CollectFields<'a>(&'a mut Vec<(String, String)>);
+
+impl<'a> tracing::field::Visit for CollectFields<'a> {
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn fmt::Debug) {
+        self.0.push((field.name().to_string(), format!("{:?}", value)));
+    }
+}
+
+impl tracing::Subscriber for CollectEvents {
+    fn enabled(&self, _: &tracing::Metadata) -> bool {
+        true
+    }
+
+    fn new_span(&self, _: &tracing::span::Attributes) -> tracing::span::Id {
+        tracing::span::Id::from_u64(1)
+    }
+
+    fn record(&self, _: &tracing::span::Id, _: &tracing::span::Record) {}
+
+    fn record_follows_from(&self, _: &tracing::span::Id, _: &tracing::span::Id) {}
+
+    fn event(&self, event: &tracing::Event) {
+        let mut fields = vec![];
+        event.record(&mut CollectFields(&mut fields));
+        self.events.lock().unwrap().push((*event.metadata().level(), fields));
+    }
+
+    fn enter(&self, _: &tracing::span::Id) {}
+
+    fn exit(&self, _: &tracing::span::Id) {}
+}
+
+#[test]
+fn test_trace_event() {
+    let events = CollectEvents::default();
+
+    tracing::subscriber::with_default(events.clone(), || {
+        control().trace_event(tracing::Level::WARN);
+    });
+
+    let mut expected = expected_fields();
+    expected.insert(0, ("message".to_string(), "Control".to_string()));
+    let recorded = events.events.lock().unwrap();
+    assert_eq!(vec![(tracing::Level::WARN, expected)], *recorded);
+}
+
+fn assert_format<T: defmt::Format>() {}
+
+#[test]
+fn test_defmt_format() {
+    assert_format::<Integer<u8, packed_bits::Bits4>>();
+    assert_format::<EnumCatchAll<EnumCatchAllFormat>>();
+    assert_format::<ReservedZero<packed_bits::Bits4>>();
+    assert_format::<ReservedOne<packed_bits::Bits4>>();
+    assert_format::<FixedString<packed_bits::Bytes4>>();
+}
+
+/// The derives only implement ```defmt::Format``` for the enums with the ```defmt``` feature
+/// of the code generator.
+#[derive(PrimitiveEnum_u8, Copy, Clone, Debug, PartialEq)]
+pub enum EnumCatchAllFormat {
+    A = 1
+}
+
+impl defmt::Format for EnumCatchAllFormat {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "A")
+    }
+}
@@ -0,0 +1,77 @@
+extern crate packed_struct;
+#[macro_use]
+extern crate packed_struct_codegen;
+
+use packed_struct::prelude::*;
+
+#[derive(PackedStruct, Debug, PartialEq)]
+#[packed_struct(bit_numbering="msb0")]
+pub struct Palette {
+    #[packed_field(bytes="0:11")]
+    pub palette: [[u8; 3]; 4]
+}
+
+#[test]
+fn test_nested_byte_arrays() {
+    let p = Palette {
+        palette: [
+            [0x10, 0x11, 0x12],
+            [0x20, 0x21, 0x22],
+            [0x30, 0x31, 0x32],
+            [0x40, 0x41, 0x42]
+        ]
+    };
+
+    let packed = p.pack();
+    assert_eq!([0x10, 0x11, 0x12, 0x20, 0x21, 0x22, 0x30, 0x31, 0x32, 0x40, 0x41, 0x42], packed);
+    assert_eq!(p, Palette::unpack(&packed).unwrap());
+
+    let fields = debug_fields_palette(&p);
+    assert_eq!("palette[3][1]", fields[10].name);
+    assert_eq!(80..87, fields[10].bits);
+    assert_eq!("65", fields[10].display_value);
+}
+
+#[derive(PackedStruct, Debug, PartialEq)]
+#[packed_struct(bit_numbering="msb0")]
+pub struct LedMatrix {
+    #[packed_field(bits="0:31")]
+    pub leds: [[bool; 8]; 4],
+    #[packed_field(bits="32:39")]
+    pub brightness: u8
+}
+
+#[test]
+fn test_nested_bool_arrays() {
+    let mut m = LedMatrix {
+        leds: [[false; 8]; 4],
+        brightness: 0x7F
+    };
+    m.leds[0][0] = true;
+    m.leds[1][7] = true;
+    m.leds[3][4] = true;
+
+    let packed = m.pack();
+    assert_eq!([0b1000_0000, 0b0000_0001, 0, 0b0000_1000, 0x7F], packed);
+    assert_eq!(m, LedMatrix::unpack(&packed).unwrap());
+}
+
+#[derive(PackedStruct, Debug, PartialEq)]
+#[packed_struct(bit_numbering="msb0")]
+pub struct Cube {
+    #[packed_field(bits="0..", element_size_bits="2")]
+    pub cells: [[[Integer<u8, packed_bits::Bits2>; 2]; 2]; 2]
+}
+
+#[test]
+fn test_three_dimensions() {
+    let mut c = Cube {
+        cells: Default::default()
+    };
+    c.cells[0][0][1] = 1.into();
+    c.cells[1][1][0] = 3.into();
+
+    let packed = c.pack();
+    assert_eq!([0b00_01_00_00, 0b00_00_11_00], packed);
+    assert_eq!(c, Cube::unpack(&packed).unwrap());
+}
@@ -0,0 +1,92 @@
+extern crate packed_struct;
+#[macro_use]
+extern crate packed_struct_codegen;
+
+use packed_struct::prelude::*;
+
+#[derive(PackedStruct, Default, Debug, Copy, Clone, PartialEq)]
+#[packed_struct(bit_numbering="msb0")]
+pub struct ChannelConfig {
+    #[packed_field(bits="0")]
+    pub enabled: bool,
+    #[packed_field(bits="1:7")]
+    pub gain: Integer<u8, packed_bits::Bits7>,
+    #[packed_field(bits="8:15")]
+    pub offset: u8
+}
+
+#[derive(PackedStruct, Debug, PartialEq)]
+#[packed_struct(bit_numbering="msb0")]
+pub struct ChannelTable {
+    #[packed_field(bytes="0:11", element_size_bytes="2", element_padding_bytes="1")]
+    pub channels: [ChannelConfig; 4]
+}
+
+#[test]
+fn test_array_element_padding() {
+    let table = ChannelTable {
+        channels: [
+            ChannelConfig { enabled: true, gain: 1.into(), offset: 0x10 },
+            ChannelConfig { enabled: false, gain: 2.into(), offset: 0x20 },
+            ChannelConfig { enabled: true, gain: 3.into(), offset: 0x30 },
+            ChannelConfig { enabled: false, gain: 4.into(), offset: 0x40 },
+        ]
+    };
+
+    let packed = table.pack();
+    assert_eq!([
+        0x81, 0x10, 0x00,
+        0x02, 0x20, 0x00,
+        0x83, 0x30, 0x00,
+        0x04, 0x40, 0x00
+    ], packed);
+
+    // the padding is ignored when unpacking
+    let mut with_padding = packed;
+    with_padding[2] = 0xFF;
+    with_padding[11] = 0xFF;
+    assert_eq!(table, ChannelTable::unpack(&with_padding).unwrap());
+}
+
+#[derive(PackedStruct, Debug, PartialEq)]
+#[packed_struct(bit_numbering="msb0")]
+pub struct StrideTable {
+    #[packed_field(bits="0..", element_stride_bits="6")]
+    pub values: [Integer<u8, packed_bits::Bits4>; 4],
+    pub trailer: u8
+}
+
+#[test]
+fn test_array_element_stride() {
+    assert_eq!(4, StrideTable::packed_bytes());
+
+    let table = StrideTable {
+        values: [0xF.into(), 0x1.into(), 0xF.into(), 0x1.into()],
+        trailer: 0xAB
+    };
+
+    let packed = table.pack();
+    assert_eq!([0b1111_00_00, 0b01_00_1111, 0b00_0001_00, 0xAB], packed);
+    assert_eq!(table, StrideTable::unpack(&packed).unwrap());
+}
+
+#[derive(PackedStruct, Debug, PartialEq)]
+#[packed_struct(bit_numbering="msb0")]
+pub struct ReversedTable {
+    #[packed_field(bytes="0:3", element_order="reverse")]
+    pub bytes: [u8; 4],
+    #[packed_field(bits="32:35", element_order="reverse", element_size_bits="1")]
+    pub flags: [bool; 4]
+}
+
+#[test]
+fn test_array_element_order() {
+    let table = ReversedTable {
+        bytes: [1, 2, 3, 4],
+        flags: [true, false, false, false]
+    };
+
+    let packed = table.pack();
+    assert_eq!([4, 3, 2, 1, 0b0001_0000], packed);
+    assert_eq!(table, ReversedTable::unpack(&packed).unwrap());
+}
@@ -0,0 +1,95 @@
+extern crate packed_struct;
+#[macro_use]
+extern crate packed_struct_codegen;
+
+use packed_struct::prelude::*;
+
+#[derive(PackedStruct, Debug, Copy, Clone, PartialEq)]
+#[packed_struct(bit_numbering="msb0")]
+pub struct BoolFlags {
+    #[packed_field(bits="0")]
+    pub plain: bool,
+    #[packed_field(bits="1", active="low")]
+    pub active_low: bool,
+    #[packed_field(bits="2:3")]
+    pub two_bits: bool,
+    #[packed_field(bits="4:5", bool_true="0b10", bool_false="0b01")]
+    pub patterns: bool,
+    #[packed_field(bits="6:7", bool_mode="nonzero")]
+    pub lenient: bool
+}
+
+#[test]
+fn test_bool_flags() {
+    let f = BoolFlags {
+        plain: true,
+        active_low: true,
+        two_bits: true,
+        patterns: true,
+        lenient: false
+    };
+
+    let packed = f.pack();
+    assert_eq!([0b1_0_11_10_00], packed);
+    assert_eq!(f, BoolFlags::unpack(&packed).unwrap());
+
+    let f = BoolFlags {
+        plain: false,
+        active_low: false,
+        two_bits: false,
+        patterns: false,
+        lenient: true
+    };
+
+    let packed = f.pack();
+    assert_eq!([0b0_1_00_01_11], packed);
+    assert_eq!(f, BoolFlags::unpack(&packed).unwrap());
+}
+
+#[test]
+fn test_bool_invalid_patterns() {
+    // two_bits only accepts 0b00 and 0b11
+    assert_eq!(Err(PackingError::InvalidValue), BoolFlags::unpack(&[0b0_0_01_01_00]));
+    // patterns only accepts 0b10 and 0b01
+    assert_eq!(Err(PackingError::InvalidValue), BoolFlags::unpack(&[0b0_0_00_11_00]));
+
+    // anything but zero is true in the lenient mode
+    for &v in &[0b01, 0b10, 0b11] {
+        let unpacked = BoolFlags::unpack(&[0b0_0_00_01_00 | v]).unwrap();
+        assert_eq!(true, unpacked.lenient);
+    }
+}
+
+#[derive(PackedStruct, Debug, Copy, Clone, PartialEq)]
+#[packed_struct(bit_numbering="msb0", endian="msb")]
+pub struct WideBool {
+    #[packed_field(bits="0:15", bool_true="0xA5A5", bool_false="0x0000")]
+    pub unlocked: bool,
+    #[packed_field(bits="16:19", active="low", bool_mode="nonzero")]
+    pub ready: bool
+}
+
+#[test]
+fn test_bool_wide() {
+    let w = WideBool {
+        unlocked: true,
+        ready: true
+    };
+
+    let packed = w.pack();
+    assert_eq!([0xA5, 0xA5, 0x00], packed);
+    assert_eq!(w, WideBool::unpack(&packed).unwrap());
+
+    let w = WideBool {
+        unlocked: false,
+        ready: false
+    };
+
+    let packed = w.pack();
+    assert_eq!([0x00, 0x00, 0xF0], packed);
+    assert_eq!(w, WideBool::unpack(&packed).unwrap());
+
+    // active low and lenient: anything but all ones is true
+    let unpacked = WideBool::unpack(&[0x00, 0x00, 0x70]).unwrap();
+    assert_eq!(true, unpacked.ready);
+}
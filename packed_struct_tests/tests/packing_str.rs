@@ -0,0 +1,105 @@
+extern crate packed_struct;
+#[macro_use]
+extern crate packed_struct_codegen;
+
+use packed_struct::prelude::*;
+
+#[derive(PackedStruct, Debug, Clone, PartialEq)]
+#[packed_struct(bit_numbering="msb0")]
+pub struct DeviceIdentification {
+    #[packed_field(bytes="0:3", endian="msb")]
+    pub vendor_id: u32,
+    #[packed_field(bytes="4:19", ty="str", padding="nul")]
+    pub model: String,
+    #[packed_field(bytes="20:27", ty="str", padding="space")]
+    pub serial: FixedString<packed_bits::Bytes8>,
+    #[packed_field(bytes="28:31", ty="str", padding="nul_terminated")]
+    pub revision: FixedString<packed_bits::Bytes4>
+}
+
+#[test]
+fn test_device_identification() {
+    let id = DeviceIdentification {
+        vendor_id: 0x1234,
+        model: "Sensor 3000".into(),
+        serial: FixedString::new("AB12").unwrap(),
+        revision: FixedString::new("r2").unwrap()
+    };
+
+    let packed = id.pack();
+    assert_eq!(&[0x00, 0x00, 0x12, 0x34], &packed[0..4]);
+    assert_eq!(b"Sensor 3000\0\0\0\0\0", &packed[4..20]);
+    assert_eq!(b"AB12    ", &packed[20..28]);
+    assert_eq!(b"r2\0\0", &packed[28..32]);
+
+    let unpacked = DeviceIdentification::unpack(&packed).unwrap();
+    assert_eq!(id, unpacked);
+    assert_eq!("AB12", &*unpacked.serial);
+}
+
+#[test]
+fn test_str_truncation_and_errors() {
+    let id = DeviceIdentification {
+        vendor_id: 0,
+        model: "A model name that is too long".into(),
+        serial: Default::default(),
+        revision: FixedString::new("rev1").unwrap()
+    };
+
+    let packed = id.pack();
+    let unpacked = DeviceIdentification::unpack(&packed).unwrap();
+    assert_eq!("A model name tha", unpacked.model);
+    // one byte is reserved for the terminator
+    assert_eq!("rev", unpacked.revision.as_str());
+
+    let mut invalid = packed;
+    invalid[4] = 0xFF;
+    assert_eq!(Err(PackingError::InvalidValue), DeviceIdentification::unpack(&invalid));
+
+    let mut unterminated = packed;
+    unterminated[31] = b'x';
+    assert_eq!(Err(PackingError::InvalidValue), DeviceIdentification::unpack(&unterminated));
+}
+
+#[derive(PackedStruct, Debug, Copy, Clone, PartialEq)]
+#[packed_struct(bit_numbering="msb0")]
+pub struct UnalignedText {
+    #[packed_field(bits="0:3")]
+    pub flags: Integer<u8, packed_bits::Bits4>,
+    #[packed_field(bits="4..", ty="str")]
+    pub name: FixedString<packed_bits::Bytes3>,
+    #[packed_field(bits="28:31")]
+    pub tail: Integer<u8, packed_bits::Bits4>
+}
+
+#[test]
+fn test_str_unaligned() {
+    let t = UnalignedText {
+        flags: 0xA.into(),
+        name: FixedString::new("abc").unwrap(),
+        tail: 0x5.into()
+    };
+
+    let packed = t.pack();
+    assert_eq!([0xA6, 0x16, 0x26, 0x35], packed);
+    assert_eq!(t, UnalignedText::unpack(&packed).unwrap());
+}
+
+pub type Label = FixedString<packed_bits::Bytes4>;
+
+#[derive(PackedStruct, Debug, Copy, Clone, PartialEq)]
+#[packed_struct(bit_numbering="msb0")]
+pub struct AliasedText {
+    #[packed_field(bytes="0:3", ty="str")]
+    pub label: Label
+}
+
+#[test]
+fn test_str_type_alias() {
+    assert_eq!(4, Label::CAPACITY);
+    assert_eq!(Some(4), <Label as PackedString>::BYTES);
+
+    let t = AliasedText { label: Label::new("ab").unwrap() };
+    assert_eq!(*b"ab\0\0", t.pack());
+    assert_eq!(t, AliasedText::unpack(&t.pack()).unwrap());
+}
@@ -0,0 +1,78 @@
+extern crate packed_struct;
+#[macro_use]
+extern crate packed_struct_codegen;
+
+use packed_struct::prelude::*;
+
+#[derive(PrimitiveEnum_u8, PartialEq, Debug, Clone, Copy)]
+pub enum PowerLevel {
+    Off = 0,
+    #[primitive(name = "low-power")]
+    Low = 1,
+    /// Both of the upper codes mean high power
+    #[primitive(alias = 3)]
+    High = 2,
+    #[primitive(range = "8..=15", alias = "0x20")]
+    Reserved = 4,
+    #[primitive(name = "boost", range = "16..32")]
+    Boost = 5
+}
+
+#[derive(PrimitiveEnum, PartialEq, Debug, Clone, Copy)]
+pub enum Trim {
+    #[primitive(range = "-8..-1")]
+    Down = -1,
+    Zero = 0,
+    #[primitive(range = "2..=7")]
+    Up = 1
+}
+
+#[derive(PackedStruct, Debug, PartialEq)]
+#[packed_struct(bit_numbering="msb0")]
+pub struct Power {
+    #[packed_field(bits="0:5", ty="enum")]
+    level: PowerLevel
+}
+
+#[test]
+fn test_enum_aliases_and_ranges() {
+    assert_eq!(Some(PowerLevel::High), PowerLevel::from_primitive(2));
+    assert_eq!(Some(PowerLevel::High), PowerLevel::from_primitive(3));
+    assert_eq!(2, PowerLevel::High.to_primitive());
+
+    assert_eq!(Some(PowerLevel::Reserved), PowerLevel::from_primitive(4));
+    assert_eq!(Some(PowerLevel::Reserved), PowerLevel::from_primitive(8));
+    assert_eq!(Some(PowerLevel::Reserved), PowerLevel::from_primitive(15));
+    assert_eq!(Some(PowerLevel::Reserved), PowerLevel::from_primitive(0x20));
+    assert_eq!(Some(PowerLevel::Boost), PowerLevel::from_primitive(31));
+    assert_eq!(None, PowerLevel::from_primitive(6));
+    assert_eq!(None, PowerLevel::from_primitive(33));
+
+    assert_eq!(Some(Trim::Down), Trim::from_primitive(-8));
+    assert_eq!(Some(Trim::Down), Trim::from_primitive(-2));
+    assert_eq!(Some(Trim::Up), Trim::from_primitive(7));
+    assert_eq!(None, Trim::from_primitive(8));
+    assert_eq!(-1i8, Trim::Down.to_primitive());
+}
+
+#[test]
+fn test_enum_names() {
+    assert_eq!("low-power", PowerLevel::Low.to_display_str());
+    assert_eq!("High", PowerLevel::High.to_display_str());
+    assert_eq!(Some(PowerLevel::Low), PowerLevel::from_str("low-power"));
+    assert_eq!(None, PowerLevel::from_str("Low"));
+    assert_eq!(Some(PowerLevel::Boost), PowerLevel::from_str_lower("boost"));
+    assert_eq!(Some(PowerLevel::High), PowerLevel::from_str_lower("high"));
+}
+
+#[test]
+fn test_enum_aliases_unpack() {
+    let p = Power::unpack(&[3 << 2]).unwrap();
+    assert_eq!(PowerLevel::High, p.level);
+    // packs back into the canonical value
+    assert_eq!([2 << 2], p.pack());
+
+    let p = Power::unpack(&[10 << 2]).unwrap();
+    assert_eq!(PowerLevel::Reserved, p.level);
+    assert_eq!([4 << 2], p.pack());
+}
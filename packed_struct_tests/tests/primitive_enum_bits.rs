@@ -0,0 +1,122 @@
+extern crate packed_struct;
+#[macro_use]
+extern crate packed_struct_codegen;
+
+use packed_struct::prelude::*;
+
+#[derive(PrimitiveEnum_u8, PartialEq, Debug, Clone, Copy)]
+pub enum Speed {
+    Slow = 2,
+    Medium = 5,
+    Fast = 7
+}
+
+#[derive(PrimitiveEnum, PartialEq, Debug, Clone, Copy)]
+pub enum Trim {
+    Down = -4,
+    Zero = 0,
+    Up = 3
+}
+
+#[derive(PrimitiveEnum, PartialEq, Debug, Clone, Copy)]
+#[repr(u16)]
+pub enum Threshold {
+    High = u16::MAX >> 7,
+    Low = 1
+}
+
+#[derive(PackedStruct, Debug, PartialEq)]
+#[packed_struct(bit_numbering="msb0")]
+pub struct Settings {
+    #[packed_field(bits="0:2", ty="enum")]
+    speed: Speed,
+    #[packed_field(bits="3:5", ty="enum")]
+    trim: Trim,
+    #[packed_field(bits="6:7", ty="enum")]
+    _unused: [EnumCatchAll<Binary>; 1]
+}
+
+#[derive(PrimitiveEnum_u8, PartialEq, Debug, Clone, Copy)]
+pub enum Binary {
+    Zero = 0,
+    One = 1
+}
+
+/// Implemented by hand, without the bounds.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Manual {
+    Off,
+    On
+}
+
+impl PrimitiveEnum for Manual {
+    type Primitive = u8;
+
+    fn from_primitive(val: u8) -> Option<Self> {
+        match val {
+            0 => Some(Manual::Off),
+            1 => Some(Manual::On),
+            _ => None
+        }
+    }
+
+    fn to_primitive(&self) -> u8 {
+        *self as u8
+    }
+
+    fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "Off" => Some(Manual::Off),
+            "On" => Some(Manual::On),
+            _ => None
+        }
+    }
+
+    fn from_str_lower(s: &str) -> Option<Self> {
+        match s {
+            "off" => Some(Manual::Off),
+            "on" => Some(Manual::On),
+            _ => None
+        }
+    }
+}
+
+#[derive(PackedStruct, Debug, PartialEq)]
+#[packed_struct(bit_numbering="msb0")]
+pub struct Switches {
+    #[packed_field(bits="0", ty="enum")]
+    main: Manual,
+    #[packed_field(bits="1:7", ty="enum")]
+    backup: EnumCatchAll<Manual>
+}
+
+#[test]
+fn test_enum_min_max_bits() {
+    assert_eq!(2, Speed::MIN);
+    assert_eq!(7, Speed::MAX);
+    assert_eq!(3, Speed::MAX_BITS);
+    assert_eq!(3, Speed::max_bits());
+
+    assert_eq!(-4, Trim::MIN);
+    assert_eq!(3, Trim::MAX);
+    assert_eq!(3, Trim::max_bits());
+
+    assert_eq!(1, Threshold::MIN);
+    assert_eq!(0x1FF, Threshold::MAX);
+    assert_eq!(9, Threshold::max_bits());
+
+    assert_eq!(1, <EnumCatchAll<Binary> as PrimitiveEnumBounds>::MAX_BITS);
+}
+
+#[test]
+fn test_enum_fields_fit() {
+    let s = Settings { speed: Speed::Fast, trim: Trim::Zero, _unused: [Binary::One.into()] };
+    assert_eq!([0b111_000_01], s.pack());
+}
+
+#[test]
+fn test_enum_fields_without_bounds() {
+    let s = Switches { main: Manual::On, backup: EnumCatchAll::CatchAll(0x7F) };
+    assert_eq!([0xFF], s.pack());
+    assert_eq!(s, Switches::unpack(&[0xFF]).unwrap());
+}
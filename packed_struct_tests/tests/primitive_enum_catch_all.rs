@@ -3,8 +3,10 @@ extern crate packed_struct;
 extern crate packed_struct_codegen;
 
 use packed_struct::prelude::*;
+use std::collections::{BTreeSet, HashMap};
 
 #[derive(PrimitiveEnum_u8, PartialEq, Debug, Clone, Copy)]
+#[primitive(display)]
 pub enum Field {
     A = 1,
     B = 2,
@@ -40,3 +42,78 @@ fn prim_catch_all() {
     println!("unknown: {:#?}", unpacked_unknown_value);
 }
 
+
+#[test]
+fn prim_catch_all_accessors() {
+    let known: EnumCatchAll<Field> = Field::C.into();
+    let unknown: EnumCatchAll<Field> = EnumCatchAll::from_raw(9);
+    assert_eq!(Some(Field::C), known.as_enum());
+    assert_eq!(None, unknown.as_enum());
+    assert_eq!(9, unknown.raw());
+    assert!(known.is_known());
+    assert!(!unknown.is_known());
+
+    // a known value held as a raw value is still the same variant
+    let raw_known: EnumCatchAll<Field> = EnumCatchAll::CatchAll(2);
+    assert_eq!(Some(Field::B), raw_known.as_enum());
+    assert_eq!(EnumCatchAll::Enum(Field::B), raw_known);
+    assert_eq!(EnumCatchAll::Enum(Field::B), EnumCatchAll::from_raw(2));
+}
+
+#[test]
+fn prim_catch_all_ordering_and_hashing() {
+    let values: BTreeSet<EnumCatchAll<Field>> = vec![
+        EnumCatchAll::CatchAll(9), Field::C.into(), EnumCatchAll::CatchAll(0), Field::A.into(), EnumCatchAll::CatchAll(3)
+    ].into_iter().collect();
+    let raw: Vec<u8> = values.iter().map(|v| v.raw()).collect();
+    assert_eq!(vec![0, 1, 3, 9], raw);
+    assert!(EnumCatchAll::<Field>::Enum(Field::B) < EnumCatchAll::CatchAll(4));
+
+    let mut counts = HashMap::new();
+    *counts.entry(EnumCatchAll::<Field>::Enum(Field::B)).or_insert(0) += 1;
+    *counts.entry(EnumCatchAll::CatchAll(2)).or_insert(0) += 1;
+    *counts.entry(EnumCatchAll::CatchAll(7)).or_insert(0) += 1;
+    assert_eq!(2, counts.len());
+    assert_eq!(Some(&2), counts.get(&Field::B.into()));
+}
+
+/// Only needs ```PrimitiveEnum``` of the wrapped enum.
+fn catch_all_raw<E: PrimitiveEnum>(value: E::Primitive) -> E::Primitive {
+    <EnumCatchAll<E> as PrimitiveEnum>::from_primitive(value).unwrap().to_primitive()
+}
+
+#[test]
+fn prim_catch_all_display_and_parse() {
+    assert_eq!(9, catch_all_raw::<Field>(9));
+
+    let unknown: EnumCatchAll<Field> = EnumCatchAll::CatchAll(9);
+    assert_eq!("B", format!("{}", EnumCatchAll::<Field>::Enum(Field::B)));
+    assert_eq!("Unknown value: 9", format!("{}", unknown));
+    assert_eq!("Unknown value: 9", unknown.to_display_str());
+
+    assert_eq!(Ok(unknown), "Unknown value: 9".parse());
+    assert_eq!(Ok(unknown), "9".parse());
+    assert_eq!(Ok(EnumCatchAll::Enum(Field::B)), "B".parse());
+    assert_eq!(Ok(EnumCatchAll::Enum(Field::B)), "2".parse());
+    assert!("D".parse::<EnumCatchAll<Field>>().is_err());
+    assert!("Unknown value: 256".parse::<EnumCatchAll<Field>>().is_err());
+
+    assert_eq!(Some(EnumCatchAll::Enum(Field::B)), <EnumCatchAll<Field> as PrimitiveEnum>::from_str("B"));
+    assert_eq!(None, <EnumCatchAll<Field> as PrimitiveEnum>::from_str("9"));
+
+    for v in 0..=255u8 {
+        let value: EnumCatchAll<Field> = EnumCatchAll::from_raw(v);
+        assert_eq!(Ok(value), value.to_display_str().parse());
+    }
+}
+
+#[test]
+fn prim_catch_all_packing() {
+    let r = Register { field: EnumCatchAll::CatchAll(3) };
+    assert_eq!([0b0011_0000], r.pack());
+    assert_eq!(EnumCatchAll::Enum(Field::C), Register::unpack(&r.pack()).unwrap().field);
+
+    // the unknown values are cut to the bits of the field
+    let r = Register { field: EnumCatchAll::CatchAll(0x14) };
+    assert_eq!([0b0100_0000], r.pack());
+}
@@ -0,0 +1,76 @@
+extern crate packed_struct;
+#[macro_use]
+extern crate packed_struct_codegen;
+
+use packed_struct::prelude::*;
+
+const COMMAND_BASE: u8 = 0x20;
+
+#[derive(PrimitiveEnum, PartialEq, Debug, Clone, Copy)]
+#[repr(u8)]
+pub enum Command {
+    Reset = 1 << 3,
+    Read = COMMAND_BASE,
+    ReadNext,
+    Write = (COMMAND_BASE | 0x40),
+    Erase = (2 + 3) * 16
+}
+
+#[derive(PrimitiveEnum, PartialEq, Debug, Clone, Copy)]
+#[primitive(i32)]
+pub enum Offset {
+    Back = -1,
+    Stay,
+    Forward
+}
+
+#[derive(PrimitiveEnum, PartialEq, Debug, Clone, Copy)]
+#[repr(u16)]
+pub enum Implicit {
+    First,
+    Second,
+    Third = 0x100,
+    Fourth
+}
+
+#[derive(PrimitiveEnum_u8, PartialEq, Debug, Clone, Copy)]
+pub enum Shifted {
+    Low = 1 << 1,
+    High = 0xF0 >> 2
+}
+
+#[test]
+fn test_const_expr_discriminants() {
+    assert_eq!(8u8, Command::Reset.to_primitive());
+    assert_eq!(Some(Command::Reset), Command::from_primitive(8));
+    assert_eq!(Some(Command::Read), Command::from_primitive(0x20));
+    assert_eq!(Some(Command::ReadNext), Command::from_primitive(0x21));
+    assert_eq!(Some(Command::Write), Command::from_primitive(0x60));
+    assert_eq!(Some(Command::Erase), Command::from_primitive(80));
+    assert_eq!(None, Command::from_primitive(0x22));
+
+    assert_eq!(Some(Shifted::Low), Shifted::from_primitive(2));
+    assert_eq!(Some(Shifted::High), Shifted::from_primitive(0x3C));
+}
+
+#[test]
+fn test_primitive_type_attributes() {
+    let offset: i32 = Offset::Forward.to_primitive();
+    assert_eq!(1, offset);
+    assert_eq!(Some(Offset::Back), Offset::from_primitive(-1));
+    assert_eq!(Some(Offset::Stay), Offset::from_primitive(0));
+
+    let v: u16 = Implicit::Fourth.to_primitive();
+    assert_eq!(0x101, v);
+    assert_eq!("u16", Implicit::SCHEMA.primitive);
+}
+
+#[test]
+fn test_implicit_discriminants() {
+    assert_eq!(0, Implicit::First as u16);
+    assert_eq!(Some(Implicit::First), Implicit::from_primitive(0));
+    assert_eq!(Some(Implicit::Second), Implicit::from_primitive(1));
+    assert_eq!(Some(Implicit::Third), Implicit::from_primitive(0x100));
+    assert_eq!(Some(Implicit::Fourth), Implicit::from_primitive(0x101));
+    assert_eq!(None, Implicit::from_primitive(2));
+}
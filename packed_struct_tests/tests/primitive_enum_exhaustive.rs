@@ -0,0 +1,63 @@
+extern crate packed_struct;
+#[macro_use]
+extern crate packed_struct_codegen;
+
+use packed_struct::prelude::*;
+use packed_struct::ExhaustivePrimitiveEnum;
+
+#[derive(PrimitiveEnum_u8, PartialEq, Debug, Clone, Copy)]
+#[primitive(bits = 2)]
+pub enum Direction {
+    North = 0,
+    East = 1,
+    South = 2,
+    West = 3
+}
+
+#[derive(PrimitiveEnum_u8, PartialEq, Debug, Clone, Copy)]
+#[primitive(bits = 3)]
+pub enum Gain {
+    Off = 0,
+    Low = 1,
+    #[primitive(range = "3..=5")]
+    Medium = 2,
+    #[primitive(alias = 7)]
+    High = 6
+}
+
+#[derive(PackedStruct, Debug, PartialEq)]
+#[packed_struct(bit_numbering="msb0")]
+pub struct Motion {
+    #[packed_field(bits="0:1", ty="exhaustive_enum")]
+    direction: Direction,
+    #[packed_field(bits="2:4", ty="exhaustive_enum")]
+    gain: Gain,
+    #[packed_field(bits="5:7", ty="enum")]
+    fallback: Gain
+}
+
+#[test]
+fn test_exhaustive_from_bits() {
+    assert_eq!(2, Direction::BITS);
+    assert_eq!(Direction::South, Direction::from_bits(2));
+    // the bits above are ignored
+    assert_eq!(Direction::West, Direction::from_bits(0b1111_0111));
+
+    assert_eq!(3, Gain::BITS);
+    assert_eq!(Gain::Medium, Gain::from_bits(4));
+    assert_eq!(Gain::High, Gain::from_bits(7));
+    assert_eq!(Gain::Off, Gain::from_bits(8));
+}
+
+#[test]
+fn test_exhaustive_unpack() {
+    for b in 0..=255u8 {
+        let m = Motion::unpack(&[b]).unwrap();
+        assert_eq!(Direction::from_bits(b >> 6), m.direction);
+        assert_eq!(Gain::from_bits(b >> 3), m.gain);
+    }
+
+    let m = Motion { direction: Direction::East, gain: Gain::High, fallback: Gain::Low };
+    assert_eq!([0b01_110_001], m.pack());
+    assert_eq!(m, Motion::unpack(&m.pack()).unwrap());
+}
@@ -0,0 +1,53 @@
+extern crate packed_struct;
+#[macro_use]
+extern crate packed_struct_codegen;
+
+use std::convert::TryFrom;
+use std::str::FromStr;
+
+use packed_struct::{ParseEnumError, TryFromPrimitiveError};
+
+#[derive(PrimitiveEnum_u8, PartialEq, Debug, Clone, Copy)]
+#[primitive(display, from_str, try_from, into)]
+pub enum Mode {
+    Idle = 0,
+    #[primitive(name = "low-power")]
+    LowPower = 1,
+    Run = 2
+}
+
+#[derive(PrimitiveEnum, PartialEq, Debug, Clone, Copy)]
+#[primitive(i16, try_from, into)]
+pub enum Offset {
+    Back = -300,
+    Forward = 300
+}
+
+#[test]
+fn test_display() {
+    assert_eq!("Run", Mode::Run.to_string());
+    assert_eq!("low-power", format!("{}", Mode::LowPower));
+    assert_eq!("[Idle  ]", format!("[{:<6}]", Mode::Idle));
+}
+
+#[test]
+fn test_from_str() {
+    assert_eq!(Ok(Mode::Run), "Run".parse::<Mode>());
+    assert_eq!(Ok(Mode::Run), "RUN".parse::<Mode>());
+    assert_eq!(Ok(Mode::LowPower), <Mode as FromStr>::from_str("Low-Power"));
+    assert_eq!(Err(ParseEnumError { enum_name: "Mode" }), "Stop".parse::<Mode>());
+    assert_eq!("Unknown variant of Mode", "Stop".parse::<Mode>().unwrap_err().to_string());
+}
+
+#[test]
+fn test_try_from_and_into() {
+    assert_eq!(Ok(Mode::Run), Mode::try_from(2));
+    assert_eq!(Err(TryFromPrimitiveError { enum_name: "Mode", value: 7 }), Mode::try_from(7));
+    assert_eq!("Invalid value 7 of Mode", Mode::try_from(7).unwrap_err().to_string());
+
+    let v: u8 = Mode::LowPower.into();
+    assert_eq!(1, v);
+
+    assert_eq!(Ok(Offset::Back), Offset::try_from(-300));
+    assert_eq!(300i16, Offset::Forward.into());
+}
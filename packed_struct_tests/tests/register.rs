@@ -0,0 +1,114 @@
+extern crate packed_struct;
+#[macro_use]
+extern crate packed_struct_codegen;
+
+use packed_struct::prelude::*;
+use packed_struct::register::*;
+
+#[derive(PackedStruct, Debug, Copy, Clone, PartialEq)]
+#[packed_struct(bit_numbering="msb0", address="0xA0", access="rw")]
+pub struct Control {
+    #[packed_field(bits="0")]
+    pub enabled: bool,
+    #[packed_field(bits="4:15", endian="msb")]
+    pub divider: Integer<u16, packed_bits::Bits12>
+}
+
+#[derive(PackedStruct, Debug, Copy, Clone, PartialEq)]
+#[packed_struct(address="0xA2", access="ro")]
+pub struct Status {
+    pub value: u8
+}
+
+#[derive(PackedStruct, Debug, Copy, Clone, PartialEq)]
+#[packed_struct(address="0xA3", access="wo")]
+pub struct Command {
+    pub value: u8
+}
+
+#[derive(PackedStruct, Debug, Copy, Clone, PartialEq)]
+#[packed_struct(address="16")]
+pub struct Scratch {
+    pub value: u8
+}
+
+fn readable_address<R: ReadableRegister>() -> u64 { R::ADDRESS }
+fn writable_address<R: WritableRegister>() -> u64 { R::ADDRESS }
+
+#[test]
+fn test_register_attributes() {
+    assert_eq!(0xA0, Control::ADDRESS);
+    assert_eq!(RegisterAccess::ReadWrite, Control::ACCESS);
+    assert_eq!([0, 0], Control::ZEROED);
+    assert_eq!(RegisterAccess::ReadOnly, Status::ACCESS);
+    assert_eq!(RegisterAccess::WriteOnly, Command::ACCESS);
+
+    assert_eq!(16, Scratch::ADDRESS);
+    assert_eq!(RegisterAccess::ReadWrite, Scratch::ACCESS);
+
+    assert_eq!(0xA0, readable_address::<Control>());
+    assert_eq!(0xA0, writable_address::<Control>());
+    assert_eq!(0xA2, readable_address::<Status>());
+    assert_eq!(0xA3, writable_address::<Command>());
+}
+
+#[test]
+fn test_register_interface() {
+    let mut bus = MemoryInterface::new();
+    bus.set(0xA2, &[0x55]);
+
+    let status: Status = bus.read().unwrap();
+    assert_eq!(0x55, status.value);
+
+    bus.write(&Command { value: 0x12 }).unwrap();
+    assert_eq!(vec![0x12], bus.get(0xA3, 1));
+
+    let control = Control { enabled: true, divider: 0x123.into() };
+    bus.write(&control).unwrap();
+    assert_eq!(vec![0x81, 0x23], bus.get(0xA0, 2));
+    assert_eq!(control, bus.read::<Control>().unwrap());
+
+    assert_eq!(vec![
+        MemoryAccess::Read { address: 0xA2, len: 1 },
+        MemoryAccess::Write { address: 0xA3, bytes: vec![0x12] },
+        MemoryAccess::Write { address: 0xA0, bytes: vec![0x81, 0x23] },
+        MemoryAccess::Read { address: 0xA0, len: 2 }
+    ], bus.accesses);
+}
+
+#[test]
+fn test_register_modify() {
+    let mut bus = MemoryInterface::new();
+    bus.set(0xA0, &[0x00, 0x10]);
+
+    let written = bus.modify(|c: &mut Control| {
+        assert_eq!(0x10, *c.divider);
+        c.enabled = true;
+    }).unwrap();
+
+    assert!(written.enabled);
+    assert_eq!(vec![0x80, 0x10], bus.get(0xA0, 2));
+    assert_eq!(2, bus.accesses.len());
+}
+
+/// A driver that is generic over the interface.
+struct Driver<I> {
+    interface: I
+}
+
+impl<I: RegisterInterface> Driver<I> {
+    fn enable(&mut self) -> Result<(), I::Error> {
+        self.interface.modify(|c: &mut Control| c.enabled = true)?;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_register_driver() {
+    let mut bus = MemoryInterface::new();
+    {
+        let mut driver = Driver { interface: &mut bus };
+        driver.enable().unwrap();
+    }
+    assert_eq!(vec![0x80, 0x00], bus.get(0xA0, 2));
+}
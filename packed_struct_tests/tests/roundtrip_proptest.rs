@@ -0,0 +1,119 @@
+extern crate packed_struct;
+#[macro_use]
+extern crate packed_struct_codegen;
+extern crate proptest;
+
+use packed_struct::prelude::*;
+use packed_struct::arbitrary::*;
+use proptest::prelude::*;
+
+#[derive(PrimitiveEnum_u8, Clone, Copy, Debug, PartialEq)]
+pub enum Mode {
+    Off = 0,
+    Sleep = 1,
+    Active = 5
+}
+
+#[derive(PrimitiveEnum_u16, Clone, Copy, Debug, PartialEq)]
+pub enum Command {
+    Reset = 0x0102,
+    Start = 0x0A0B
+}
+
+#[derive(PackedStruct, Debug, Copy, Clone, PartialEq)]
+#[packed_struct(bit_numbering="msb0", schema)]
+pub struct Flags {
+    #[packed_field(bits="0")]
+    pub ready: bool,
+    #[packed_field(bits="1:3")]
+    pub _reserved: ReservedOne<packed_bits::Bits3>,
+    #[packed_field(bits="4:7")]
+    pub level: Integer<i8, packed_bits::Bits4>
+}
+
+#[derive(PackedStruct, Debug, Clone, PartialEq)]
+#[packed_struct(bit_numbering="msb0", proptest)]
+pub struct Frame {
+    #[packed_field(bits="0:2", ty="enum")]
+    pub mode: Mode,
+    #[packed_field(bits="3:7", ty="enum")]
+    pub other: EnumCatchAll<Mode>,
+    #[packed_field(bytes="1:2", ty="enum", endian="lsb")]
+    pub command: Command,
+    #[packed_field(bytes="3")]
+    pub flags: Flags,
+    #[packed_field(bytes="4:6")]
+    pub history: [u8; 3],
+    #[packed_field(bits="56:75", endian="msb")]
+    pub counter: Integer<u32, packed_bits::Bits20>,
+    #[packed_field(bits="76:79")]
+    pub _padding: ReservedZero<packed_bits::Bits4>,
+    #[packed_field(bytes="10:13", ty="str")]
+    pub label: FixedString<packed_bits::Bytes4>
+}
+
+/// Fields that only unpack from a few of the patterns of their bits
+#[derive(PackedStruct, Debug, Clone, PartialEq)]
+#[packed_struct(bit_numbering="msb0", endian="msb", proptest)]
+pub struct Strict {
+    #[packed_field(bits="0:15", bool_true="0xA5A5", bool_false="0x0000")]
+    pub unlocked: bool,
+    #[packed_field(bits="16", active="low")]
+    pub enabled: bool,
+    #[packed_field(bits="17:19", bool_true="0b101", bool_false="0b010", bool_mode="nonzero")]
+    pub lenient: bool,
+    #[packed_field(bits="20:51", ty="str", padding="nul_terminated")]
+    pub name: FixedString<packed_bits::Bytes4>,
+    #[packed_field(bits="52:83", ty="str", padding="space")]
+    pub model: String
+}
+
+#[test]
+fn test_roundtrip() {
+    assert_roundtrip::<Flags>();
+    assert_roundtrip::<Frame>();
+    assert_roundtrip::<Strict>();
+}
+
+proptest! {
+    #[test]
+    fn test_generated_frames(frame in any::<Frame>()) {
+        prop_assert_eq!(0b111, frame.flags._reserved.pack()[0] >> 5);
+        prop_assert!(*frame.counter < (1 << 20));
+        prop_assert!(frame.label.len() <= 4);
+        prop_assert!(frame.label.chars().all(|c| c.is_ascii_lowercase()));
+    }
+
+    #[test]
+    fn test_generated_strict(strict in any::<Strict>()) {
+        prop_assert!(strict.name.len() <= 3);
+        prop_assert!(!strict.model.ends_with(' '));
+        prop_assert!(strict.model.len() <= 4);
+    }
+
+    #[test]
+    fn test_generated_bytes(bytes in packed_bytes_strategy::<Frame>()) {
+        prop_assert_eq!(14, bytes.len());
+        prop_assert!(Frame::unpack_from_slice(&bytes).is_ok());
+    }
+}
+
+#[test]
+fn test_generated_variants() {
+    use proptest::strategy::{Strategy, ValueTree};
+    use proptest::test_runner::TestRunner;
+
+    let mut runner = TestRunner::deterministic();
+    let strategy = packed_struct_strategy::<Frame>();
+    let mut commands = vec![];
+    let mut catch_all = false;
+    for _ in 0..200 {
+        let frame = strategy.new_tree(&mut runner).unwrap().current();
+        if !commands.contains(&frame.command) {
+            commands.push(frame.command);
+        }
+        catch_all |= match frame.other { EnumCatchAll::CatchAll(_) => true, _ => false };
+    }
+    assert_eq!(2, commands.len());
+    assert!(catch_all);
+}
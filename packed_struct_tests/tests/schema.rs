@@ -0,0 +1,83 @@
+extern crate packed_struct;
+#[macro_use]
+extern crate packed_struct_codegen;
+
+use packed_struct::prelude::*;
+use packed_struct::schema::*;
+
+mod fixtures;
+use fixtures::*;
+
+#[test]
+fn test_struct_schema() {
+    let schema = Control::packed_struct_schema();
+    assert_eq!("Control", schema.name);
+    assert_eq!("Control register of the \"device\"", schema.doc);
+    assert_eq!(10, schema.num_bytes);
+    assert_eq!(80, schema.num_bits);
+    assert_eq!(7, schema.fields.len());
+
+    let mode = schema.field("mode").unwrap();
+    assert_eq!("PowerMode", mode.type_name);
+    assert_eq!("The power mode\nof the device", mode.doc);
+    assert_eq!(PackedFieldKind::Enum, mode.kind);
+    assert_eq!(BitRange { start: 0, end: 2 }, mode.bits_msb0);
+    assert_eq!(BitRange { start: 77, end: 79 }, mode.bits_lsb0);
+    assert_eq!(3, mode.bits());
+    assert_eq!(None, mode.endianness);
+
+    let variants = mode.enum_schema.unwrap();
+    assert_eq!("PowerMode", variants.name);
+    assert_eq!("u8", variants.primitive);
+    assert_eq!(3, variants.variants.len());
+    assert_eq!("Active", variants.variant_by_value(5).unwrap().name);
+    assert_eq!("Fully powered", variants.variant_by_value(5).unwrap().doc);
+    assert_eq!(1, variants.variant_by_name("Sleep").unwrap().value);
+
+    let other = schema.field("other").unwrap();
+    assert_eq!("EnumCatchAll<PowerMode>", other.type_name);
+    assert_eq!(3, other.enum_schema.unwrap().variants.len());
+
+    let counter = schema.field("counter").unwrap();
+    assert_eq!(PackedFieldKind::Integer { signed: false }, counter.kind);
+    assert_eq!(Some(IntegerEndianness::Lsb), counter.endianness);
+    assert_eq!("8:23", counter.bits_msb0.to_string());
+    assert_eq!("56:71", counter.bits_lsb0.to_string());
+    assert_eq!("", counter.doc);
+
+    let history = schema.field("history[1]").unwrap();
+    assert_eq!("u8", history.type_name);
+    assert_eq!(BitRange { start: 40, end: 47 }, history.bits_msb0);
+    assert_eq!(BitRange { start: 32, end: 39 }, history.bits_lsb0);
+
+    let status = schema.field("status").unwrap();
+    assert_eq!(PackedFieldKind::Struct, status.kind);
+    let status = (status.nested.unwrap())();
+    assert_eq!("Status", status.name);
+    assert_eq!(1, status.num_bytes);
+}
+
+#[test]
+fn test_nested_schema() {
+    let schema = Status::packed_struct_schema();
+
+    let ready = schema.field("ready").unwrap();
+    assert_eq!(PackedFieldKind::Bool { true_bits: 1, false_bits: 0, nonzero: false }, ready.kind);
+    assert!(!ready.reserved);
+
+    let reserved = schema.field("_reserved").unwrap();
+    assert_eq!(PackedFieldKind::Reserved { ones: false }, reserved.kind);
+    assert_eq!("ReservedZero<packed_bits::Bits3>", reserved.type_name);
+    assert!(reserved.reserved);
+
+    let level = schema.field("level").unwrap();
+    assert_eq!(PackedFieldKind::Integer { signed: true }, level.kind);
+    assert_eq!("4:7", level.bits_msb0.to_string());
+    assert_eq!("0:3", level.bits_lsb0.to_string());
+}
+
+#[test]
+fn test_enum_schema() {
+    assert_eq!("Everything is off", PowerMode::SCHEMA.variant_by_name("Off").unwrap().doc);
+    assert!(PowerMode::SCHEMA.variant_by_value(2).is_none());
+}
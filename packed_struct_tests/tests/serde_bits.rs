@@ -0,0 +1,169 @@
+extern crate packed_struct;
+#[macro_use]
+extern crate packed_struct_codegen;
+#[macro_use]
+extern crate serde_derive;
+
+use packed_struct::prelude::*;
+use packed_struct::serde_bits::*;
+
+#[derive(PrimitiveEnum_u8, Copy, Clone, Debug, PartialEq)]
+#[primitive(serde)]
+pub enum Mode {
+    Idle = 0,
+    Run = 1,
+    Sleep = 2
+}
+
+#[derive(PackedStruct, Debug, PartialEq)]
+#[packed_struct(bit_numbering="msb0", schema)]
+pub struct PackedReading {
+    #[packed_field(bits="0")]
+    valid: bool,
+    #[packed_field(bits="1:3")]
+    channel: Integer<u8, packed_bits::Bits3>,
+    #[packed_field(bits="4:7", ty="enum")]
+    mode: Mode,
+    #[packed_field(bits="8:19", endian="msb")]
+    value: Integer<i16, packed_bits::Bits12>
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Reading {
+    valid: bool,
+    channel: u8,
+    mode: Mode,
+    value: i16
+}
+
+#[derive(PackedStruct, Debug, PartialEq)]
+#[packed_struct(bit_numbering="msb0", schema)]
+pub struct PackedStatus {
+    #[packed_field(bits="0")]
+    ready: bool,
+    #[packed_field(bits="1:7")]
+    level: Integer<i8, packed_bits::Bits7>
+}
+
+#[derive(PackedStruct, Debug, PartialEq)]
+#[packed_struct(bit_numbering="msb0", schema)]
+pub struct PackedFrame {
+    #[packed_field(bytes="0")]
+    status: PackedStatus,
+    #[packed_field(bytes="1:3")]
+    samples: [u8; 3],
+    #[packed_field(bits="32:35")]
+    gain: Integer<u8, packed_bits::Bits4>,
+    #[packed_field(bits="36:39", ty="enum")]
+    mode: Mode
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Status {
+    ready: bool,
+    level: i8
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Frame {
+    status: Status,
+    samples: [u8; 3],
+    gain: u8,
+    mode: Mode
+}
+
+#[test]
+fn test_serde_bits_matches_packed_struct() {
+    let widths = BitWidths::from_schema(PackedReading::packed_struct_schema());
+    assert_eq!(Some(3), widths.get("channel"));
+    assert_eq!(Some(12), widths.get("value"));
+
+    let reading = Reading { valid: true, channel: 5, mode: Mode::Sleep, value: -300 };
+    let packed = PackedReading { valid: true, channel: 5.into(), mode: Mode::Sleep, value: (-300).into() };
+
+    let bytes = to_bytes(&reading, &widths).unwrap();
+    assert_eq!(&packed.pack()[..], &bytes[..]);
+    assert_eq!(reading, from_bytes::<Reading>(&bytes, &widths).unwrap());
+    assert_eq!(packed, PackedReading::unpack_from_slice(&bytes).unwrap());
+}
+
+#[test]
+fn test_serde_bits_nested() {
+    let widths = BitWidths::from_schema(PackedFrame::packed_struct_schema());
+    assert_eq!(Some(7), widths.get("status.level"));
+    assert_eq!(Some(8), widths.get("samples"));
+
+    let frame = Frame {
+        status: Status { ready: true, level: -3 },
+        samples: [0x10, 0x20, 0x30],
+        gain: 9,
+        mode: Mode::Run
+    };
+    let packed = PackedFrame {
+        status: PackedStatus { ready: true, level: (-3).into() },
+        samples: [0x10, 0x20, 0x30],
+        gain: 9.into(),
+        mode: Mode::Run
+    };
+
+    let bytes = to_bytes(&frame, &widths).unwrap();
+    assert_eq!(&packed.pack()[..], &bytes[..]);
+    assert_eq!(frame, from_bytes::<Frame>(&bytes, &widths).unwrap());
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub enum Command {
+    Stop,
+    Move { x: i8, y: i8 },
+    Set(u8, u8),
+    Label(String)
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Message {
+    id: u16,
+    commands: Vec<Command>,
+    reply_to: Option<u8>,
+    ratio: f32
+}
+
+#[test]
+fn test_serde_bits_roundtrip() {
+    let widths = BitWidths::new()
+        .field("id", 10)
+        .field("commands", 2)
+        .field("commands.x", 5)
+        .field("commands.y", 5)
+        .length_bits(4);
+
+    let message = Message {
+        id: 1000,
+        commands: vec![Command::Stop, Command::Move { x: -16, y: 15 }, Command::Set(1, 2), Command::Label("on".into())],
+        reply_to: None,
+        ratio: 0.5
+    };
+
+    let bytes = to_bytes(&message, &widths).unwrap();
+    // the elements of the tuple variant take the 2 bits of the commands
+    // 10 + 4 + 2 + (2 + 5 + 5) + (2 + 2 + 2) + (2 + 4 + 16) + 1 + 32 bits
+    assert_eq!(12, bytes.len());
+    assert_eq!(message, from_bytes::<Message>(&bytes, &widths).unwrap());
+
+    let message = Message { reply_to: Some(7), commands: vec![], ..message };
+    assert_eq!(message, from_bytes::<Message>(&to_bytes(&message, &widths).unwrap(), &widths).unwrap());
+}
+
+#[test]
+fn test_serde_bits_errors() {
+    let widths = BitWidths::new().field("channel", 3).field("value", 12);
+
+    let reading = Reading { valid: true, channel: 8, mode: Mode::Idle, value: 0 };
+    assert_eq!(Err(BitsError::ValueTooWide { field: "channel".into(), bits: 3 }), to_bytes(&reading, &widths));
+
+    let reading = Reading { valid: true, channel: 1, mode: Mode::Idle, value: -2049 };
+    assert_eq!(Err(BitsError::ValueTooWide { field: "value".into(), bits: 12 }), to_bytes(&reading, &widths));
+
+    assert_eq!(Err(BitsError::UnexpectedEnd), from_bytes::<Reading>(&[0x00, 0x00], &widths));
+    assert_eq!(Err(BitsError::TrailingBytes), from_bytes::<Reading>(&[0, 0, 0, 0], &widths));
+    assert!(from_bytes::<Reading>(&[0x00, 0x30, 0x00], &widths).is_err());
+}
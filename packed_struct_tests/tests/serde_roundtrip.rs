@@ -0,0 +1,144 @@
+extern crate packed_struct;
+#[macro_use]
+extern crate packed_struct_codegen;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_json;
+extern crate toml;
+
+use packed_struct::prelude::*;
+
+#[derive(PrimitiveEnum_u8, Copy, Clone, Debug, PartialEq)]
+#[primitive(serde)]
+pub enum Mode {
+    Idle = 0,
+    Run = 1,
+    Sleep = 2
+}
+
+/// Without ```#[primitive(serde)]```, the enum derives its own serde implementations.
+#[derive(PrimitiveEnum_u8, Serialize, Deserialize, Copy, Clone, Debug, PartialEq)]
+pub enum Level {
+    Low = 0,
+    High = 1
+}
+
+#[derive(PackedStruct, Debug, PartialEq)]
+#[packed_struct(bit_numbering="msb0", serde)]
+pub struct Control {
+    #[packed_field(bits="0")]
+    enabled: bool,
+    #[packed_field(bits="1:3", ty="enum")]
+    mode: Mode,
+    #[packed_field(bits="4:7", ty="enum")]
+    fallback: EnumCatchAll<Mode>,
+    #[packed_field(bits="8:11")]
+    _reserved: ReservedOne<packed_bits::Bits4>,
+    #[packed_field(bits="12:15")]
+    gain: Integer<u8, packed_bits::Bits4>,
+    #[packed_field(bytes="2:5", ty="str")]
+    label: FixedString<packed_bits::Bytes4>
+}
+
+#[derive(PackedStruct, Debug, PartialEq)]
+#[packed_struct(bit_numbering="msb0", serde)]
+pub struct Tagged {
+    #[packed_field(bits="0:3", ty="enum")]
+    mode: Mode,
+    #[packed_field(bits="4:7")]
+    _reserved: [ReservedOne<packed_bits::Bits2>; 2]
+}
+
+fn control() -> Control {
+    Control {
+        enabled: true,
+        mode: Mode::Run,
+        fallback: EnumCatchAll::CatchAll(9),
+        _reserved: Default::default(),
+        gain: 7.into(),
+        label: FixedString::new("adc").unwrap()
+    }
+}
+
+#[test]
+fn test_serde_enum() {
+    assert_eq!("\"Sleep\"", serde_json::to_string(&Mode::Sleep).unwrap());
+    assert_eq!(Mode::Sleep, serde_json::from_str::<Mode>("\"Sleep\"").unwrap());
+    assert_eq!(Mode::Sleep, serde_json::from_str::<Mode>("2").unwrap());
+    assert!(serde_json::from_str::<Mode>("\"Off\"").is_err());
+    assert!(serde_json::from_str::<Mode>("3").is_err());
+    assert!(serde_json::from_str::<Mode>("-1").is_err());
+}
+
+#[test]
+fn test_serde_enum_derived() {
+    assert_eq!("\"High\"", serde_json::to_string(&Level::High).unwrap());
+    assert_eq!(Level::High, serde_json::from_str::<Level>("\"High\"").unwrap());
+    assert!(serde_json::from_str::<Level>("1").is_err());
+}
+
+#[test]
+fn test_serde_enum_catch_all() {
+    let known: EnumCatchAll<Mode> = Mode::Run.into();
+    let unknown: EnumCatchAll<Mode> = EnumCatchAll::CatchAll(200);
+    assert_eq!("\"Run\"", serde_json::to_string(&known).unwrap());
+    assert_eq!("200", serde_json::to_string(&unknown).unwrap());
+
+    assert_eq!(known, serde_json::from_str::<EnumCatchAll<Mode>>("\"Run\"").unwrap());
+    assert_eq!(known, serde_json::from_str::<EnumCatchAll<Mode>>("1").unwrap());
+    assert_eq!(unknown, serde_json::from_str::<EnumCatchAll<Mode>>("200").unwrap());
+    assert_eq!(unknown, serde_json::from_str::<EnumCatchAll<Mode>>("\"Unknown value: 200\"").unwrap());
+    assert!(serde_json::from_str::<EnumCatchAll<Mode>>("\"Unknown\"").is_err());
+    assert!(serde_json::from_str::<EnumCatchAll<Mode>>("256").is_err());
+}
+
+#[test]
+fn test_serde_enum_catch_all_map_key() {
+    let mut counts = std::collections::BTreeMap::new();
+    counts.insert(EnumCatchAll::Enum(Mode::Run), 3);
+    counts.insert(EnumCatchAll::CatchAll(200), 1);
+    let json = serde_json::to_string(&counts).unwrap();
+    assert_eq!("{\"Run\":3,\"200\":1}", json);
+    assert_eq!(counts, serde_json::from_str(&json).unwrap());
+
+    // a known value held as a raw value is stored by its name
+    assert_eq!("\"Sleep\"", serde_json::to_string(&EnumCatchAll::<Mode>::CatchAll(2)).unwrap());
+}
+
+#[test]
+fn test_serde_struct_json() {
+    let json = serde_json::to_string(&control()).unwrap();
+    assert_eq!(r#"{"enabled":true,"mode":"Run","fallback":9,"gain":7,"label":"adc"}"#, json);
+    assert_eq!(control(), serde_json::from_str::<Control>(&json).unwrap());
+
+    // the reserved fields are ignored, they always pack into their fixed bits
+    let with_reserved = r#"{"enabled":true,"mode":1,"fallback":9,"_reserved":null,"gain":7,"label":"adc"}"#;
+    let unpacked = serde_json::from_str::<Control>(with_reserved).unwrap();
+    assert_eq!(control(), unpacked);
+    assert_eq!(control().pack(), unpacked.pack());
+
+    assert!(serde_json::from_str::<Control>(r#"{"enabled":true,"mode":"Run","fallback":9,"gain":7,"label":"adc 1"}"#).is_err());
+}
+
+#[test]
+fn test_serde_struct_toml() {
+    let config = toml::to_string(&control()).unwrap();
+    assert_eq!("enabled = true\nmode = \"Run\"\nfallback = 9\ngain = 7\nlabel = \"adc\"\n", config);
+    assert_eq!(control(), toml::from_str::<Control>(&config).unwrap());
+}
+
+#[test]
+fn test_serde_struct_reserved_array() {
+    let tagged = Tagged { mode: Mode::Sleep, _reserved: Default::default() };
+    let json = serde_json::to_string(&tagged).unwrap();
+    assert_eq!(r#"{"mode":"Sleep"}"#, json);
+    assert_eq!(tagged, serde_json::from_str::<Tagged>(&json).unwrap());
+    assert_eq!([0x2F], serde_json::from_str::<Tagged>(&json).unwrap().pack());
+}
+
+#[test]
+fn test_serde_packing_error() {
+    let e = PackingError::BufferSizeMismatch { expected: 4, actual: 2 };
+    let json = serde_json::to_string(&e).unwrap();
+    assert_eq!(e, serde_json::from_str::<PackingError>(&json).unwrap());
+}
@@ -0,0 +1,61 @@
+extern crate packed_struct;
+#[macro_use]
+extern crate packed_struct_codegen;
+
+use packed_struct::prelude::*;
+use packed_struct::register::{MemoryInterface, RegisterAccess};
+
+include!(concat!(env!("OUT_DIR"), "/timer_svd.rs"));
+
+use timer0::*;
+
+#[test]
+fn test_svd_register() {
+    let cr = Cr {
+        en: true,
+        _reserved_1: Default::default(),
+        mode: EnumCatchAll::Enum(CrMode::OneShot),
+        dir: CrDir::Down,
+        _reserved_7: Default::default(),
+        prescaler: 0xABC.into(),
+        _reserved_28: Default::default()
+    };
+
+    let packed = cr.pack();
+    assert_eq!(0x0ABC_0051, u32::from_be_bytes(packed));
+    assert_eq!(cr, Cr::unpack(&packed).unwrap());
+
+    let reset = Cr::unpack(&(Cr::RESET_VALUE as u32).to_be_bytes()).unwrap();
+    assert_eq!(EnumCatchAll::Enum(CrMode::OneShot), reset.mode);
+    assert!(!reset.en);
+
+    let unknown = Cr::unpack(&0x20u32.to_be_bytes()).unwrap();
+    assert_eq!(EnumCatchAll::CatchAll(2), unknown.mode);
+
+    assert_eq!(0x4001_0000, Cr::ADDRESS);
+    assert_eq!(0x4001_1014, timer1::Ccr1::ADDRESS);
+}
+
+#[test]
+fn test_svd_small_registers() {
+    let sr = Sr::unpack(&[0x51]).unwrap();
+    assert!(sr.uif);
+    assert_eq!(5, *sr.type_);
+
+    let cnt = Cnt { value: 0x1234 };
+    assert_eq!([0x12, 0x34], cnt.pack());
+    assert_eq!(4, Ccr0::packed_struct_schema().num_bytes);
+}
+
+#[test]
+fn test_svd_register_access() {
+    assert_eq!(RegisterAccess::ReadOnly, Sr::ACCESS);
+    assert_eq!(RegisterAccess::ReadWrite, Cr::ACCESS);
+
+    let mut bus = MemoryInterface::new();
+    bus.set(0x4001_0004, &[0x01]);
+    assert!(bus.read::<Sr>().unwrap().uif);
+
+    bus.modify(|cr: &mut Cr| cr.en = true).unwrap();
+    assert_eq!(vec![0, 0, 0, 1], bus.get(0x4001_0000, 4));
+}